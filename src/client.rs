@@ -5,12 +5,18 @@ pub mod structs;
 pub mod traits;
 pub mod webdav_request;
 
+use crate::client::structs::account_config::AccountConfig;
+use crate::client::structs::client_key::ClientKey;
 use crate::client::structs::client_value::HttpClient;
-use crate::client::structs::reactive_child_clients::ReactiveChildClients;
+use crate::client::structs::reactive_child_clients::{
+    ReactiveChildClients, TAccountEventReceiver,
+};
+use crate::client::traits::account::{Account, AccountError};
 use crate::global_config::global_config::GlobalConfig;
+use crate::transfer_manager::TransferManager;
 use std::sync::Arc;
 
-pub type THttpClientArc = Arc<HttpClient>; // 这里的Arc是共享的，并且永远不会被修改，只会被删除，所以可以设计无锁结构
+pub type THttpClientArc = Arc<HttpClient>; // 这里的Arc是共享的，更新凭据时只替换内部状态，不会替换Arc本身
 
 /// WebDav客户端对象
 /// - clients就是存储的账号
@@ -19,6 +25,7 @@ pub type THttpClientArc = Arc<HttpClient>; // 这里的Arc是共享的，并且
 pub struct WebDavClient {
     child_clients: ReactiveChildClients,
     global_config: GlobalConfig,
+    transfer_manager: TransferManager,
 }
 
 impl WebDavClient {
@@ -26,10 +33,33 @@ impl WebDavClient {
         let child_clients = ReactiveChildClients::new();
         let global_config = GlobalConfig::default();
 
-        Self { child_clients, global_config }
+        let transfer_manager = TransferManager::new(
+            child_clients.clone(),
+            global_config.clone(),
+        );
+
+        Self { child_clients, global_config, transfer_manager }
     }
 
     pub fn get_global_config(&self) -> GlobalConfig {
         self.global_config.clone()
     }
+
+    /// 获取传输管理器，所有副本共享同一个队列
+    pub fn get_transfer_manager(&self) -> TransferManager {
+        self.transfer_manager.clone()
+    }
+
+    /// 订阅账号变更事件（新增、删除、更新）
+    pub fn subscribe_account_events(&self) -> TAccountEventReceiver {
+        self.child_clients.subscribe_account_events()
+    }
+
+    /// 获取账号级别的配置，修改后会实时作用于该账号下的所有下载
+    pub fn get_account_config(
+        &self,
+        key: &ClientKey,
+    ) -> Result<AccountConfig, AccountError> {
+        Ok(self.get_http_client(key)?.get_account_config())
+    }
 }
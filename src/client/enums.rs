@@ -1,2 +1,4 @@
+pub mod account_event;
+pub mod auth_kind;
 pub mod depth;
 pub mod webdav_method;
\ No newline at end of file
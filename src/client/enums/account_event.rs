@@ -0,0 +1,14 @@
+use crate::client::structs::client_key::ClientKey;
+
+/// 账号列表的变更事件
+///
+/// 订阅者只需要关心发生了什么，而不需要对比整张账号表的快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountEvent {
+    /// 新增了账号
+    AccountAdded(ClientKey),
+    /// 删除了账号（包括强制删除）
+    AccountRemoved(ClientKey),
+    /// 已有账号被替换或更新
+    AccountUpdated(ClientKey),
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// 账号的鉴权方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthKind {
+    /// HTTP Basic 鉴权（用户名 + 密码）
+    Basic,
+}
@@ -2,5 +2,8 @@ mod to_resource_file_data;
 mod impl_folders;
 mod impl_url_format;
 mod impl_account_for_webdav_client;
+mod impl_account_registry;
 mod impl_reactive_child_clients;
-mod impl_local_folders;
\ No newline at end of file
+mod impl_local_folders;
+mod impl_verify_account;
+mod impl_download_recursive;
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::client::structs::account_config::AccountConfigData;
+use crate::client::structs::account_credentials::AccountCredentials;
 use crate::client::structs::client_key::ClientKey;
 use crate::client::traits::account::{Account, AccountError};
 use crate::client::{THttpClientArc, WebDavClient};
@@ -27,6 +29,15 @@ impl Account for WebDavClient {
         self.child_clients.get_http_client(key)
     }
 
+    fn update_account(
+        &self,
+        key: &ClientKey,
+        credentials: &AccountCredentials,
+        options: Option<AccountConfigData>,
+    ) -> Result<ClientKey, AccountError> {
+        self.child_clients.update_account(key, credentials, options)
+    }
+
     fn remove_account_force(
         &self,
         key: &ClientKey,
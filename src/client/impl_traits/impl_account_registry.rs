@@ -0,0 +1,184 @@
+use crate::client::WebDavClient;
+use crate::client::structs::account_registry_file::{
+    ACCOUNT_REGISTRY_VERSION, AccountRecord, AccountRegistryFile,
+    RegistryKdf, RegistryKey,
+};
+use crate::client::structs::client_key::ClientKey;
+use crate::client::structs::client_value::HttpClient;
+use crate::client::structs::reactive_child_clients::ReactiveChildClients;
+use crate::client::traits::account::{AccountError, AddAccountError};
+use crate::client::traits::account_registry::{
+    AccountRegistry, AccountRegistryError,
+};
+use aes_gcm::Aes256Gcm;
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use zeroize::Zeroizing;
+
+/// PBKDF2 计算量很大，放到阻塞线程池里执行，避免卡住异步运行时
+async fn build_cipher(
+    key: &RegistryKey,
+    kdf: Option<RegistryKdf>,
+) -> Result<Aes256Gcm, AccountRegistryError> {
+    let key = key.clone();
+
+    tokio::task::spawn_blocking(move || key.to_cipher(kdf.as_ref()))
+        .await?
+}
+
+/// 账号文件只允许当前用户读写（unix 下为 0600）
+async fn write_private_file(
+    path: &Path,
+    data: &[u8],
+) -> Result<(), std::io::Error> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path).await?;
+
+    // 文件已存在时 mode 不会生效，需要显式收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))
+            .await?;
+    }
+
+    file.write_all(data).await?;
+    file.sync_all().await
+}
+
+#[async_trait]
+impl AccountRegistry for ReactiveChildClients {
+    async fn export_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<usize, AccountRegistryError> {
+        let kdf = match key {
+            RegistryKey::Passphrase(_) => Some(RegistryKdf::generate()),
+            RegistryKey::Key(_) => None,
+        };
+
+        let cipher = build_cipher(key, kdf.clone()).await?;
+
+        // 先拍一份快照，避免在加密期间长时间借用 watch 的值
+        let clients: Vec<_> = self
+            .receiver
+            .borrow()
+            .iter()
+            .map(|(client_key, client)| {
+                (client_key.clone(), client.clone())
+            })
+            .collect();
+
+        let mut accounts = Vec::with_capacity(clients.len());
+
+        for (client_key, client) in clients {
+            let (credentials, config) = client.get_credentials();
+            // 明文密码的副本加密后立即清零
+            let password = Zeroizing::new(credentials.password);
+
+            accounts.push(AccountRecord::seal(
+                &cipher,
+                client_key.get_base_url().as_str(),
+                &credentials.username,
+                credentials.auth_kind,
+                &password,
+                config,
+            )?);
+        }
+
+        let count = accounts.len();
+
+        let file = AccountRegistryFile {
+            version: ACCOUNT_REGISTRY_VERSION,
+            kdf,
+            accounts,
+        };
+
+        let json = serde_json::to_vec_pretty(&file)?;
+        write_private_file(path, &json).await?;
+
+        Ok(count)
+    }
+
+    async fn import_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<Vec<ClientKey>, AccountRegistryError> {
+        let json = tokio::fs::read(path).await?;
+        let file: AccountRegistryFile = serde_json::from_slice(&json)?;
+
+        if file.version != ACCOUNT_REGISTRY_VERSION {
+            return Err(AccountRegistryError::UnsupportedVersion(
+                file.version,
+            ));
+        }
+
+        let cipher = build_cipher(key, file.kdf.clone()).await?;
+
+        // 先全部解密并构建客户端，全部成功后再统一写入
+        let mut restored = Vec::with_capacity(file.accounts.len());
+
+        for record in &file.accounts {
+            let password = record.open(&cipher)?;
+
+            let client_key =
+                ClientKey::new(&record.base_url, &record.username)
+                    .map_err(|e| {
+                        AccountError::from(
+                            AddAccountError::CreateKeyError(e),
+                        )
+                    })?;
+
+            let http_client = HttpClient::new_with_config(
+                &record.base_url,
+                &record.username,
+                &password,
+                record.config.clone(),
+            )
+            .map_err(|e| {
+                AccountError::from(AddAccountError::CreateHttpClientError(
+                    e,
+                ))
+            })?;
+
+            restored.push((client_key, http_client));
+        }
+
+        let mut keys = Vec::with_capacity(restored.len());
+
+        for (client_key, http_client) in restored {
+            self.insert(client_key.clone(), Arc::new(http_client));
+            keys.push(client_key);
+        }
+
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl AccountRegistry for WebDavClient {
+    async fn export_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<usize, AccountRegistryError> {
+        self.child_clients.export_accounts(path, key).await
+    }
+
+    async fn import_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<Vec<ClientKey>, AccountRegistryError> {
+        self.child_clients.import_accounts(path, key).await
+    }
+}
@@ -0,0 +1,70 @@
+use crate::client::WebDavClient;
+use crate::client::enums::depth::Depth;
+use crate::client::structs::client_key::ClientKey;
+use crate::client::traits::account::Account;
+use crate::client::traits::download_recursive::{
+    DownloadRecursivePath, DownloadRecursivePathError,
+};
+use crate::client::traits::url_format::UrlFormat;
+use crate::client::webdav_request::get_folders_public_impl::{
+    GetFoldersError, get_folders_with_client,
+};
+use crate::resource_file::structs::download_config::DownloadConfig;
+use crate::resource_file::structs::recursive_download::{
+    RecursiveDownloadOptions, RecursiveDownloadReport,
+};
+use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::traits::download_recursive::DownloadRecursive;
+use crate::resource_file::traits::to_resource_file_data::ToResourceFileData;
+use async_trait::async_trait;
+
+#[async_trait]
+impl DownloadRecursivePath for WebDavClient {
+    async fn download_recursive(
+        &self,
+        key: &ClientKey,
+        path: &str,
+        save_absolute_path: &str,
+        options: RecursiveDownloadOptions,
+    ) -> Result<RecursiveDownloadReport, DownloadRecursivePathError> {
+        let http_client_arc =
+            self.get_http_client(key).map_err(GetFoldersError::from)?;
+
+        // 还没有资源文件，用默认的资源配置按 账号 → 全局 解析超时
+        let timeout_secs = DownloadConfig::new(
+            self.get_global_config(),
+            http_client_arc.get_account_config(),
+            ResourceConfig::default(),
+        )
+        .timeout_secs();
+
+        let url = self
+            .format_url_path(key, path)
+            .map_err(GetFoldersError::from)?;
+
+        // 只查询路径本身，判断是文件还是文件夹
+        let multi_status = get_folders_with_client(
+            http_client_arc.get_client(),
+            &url,
+            &Depth::Zero,
+            timeout_secs,
+        )
+        .await?;
+
+        let data = multi_status
+            .to_resource_file_data(&key.get_base_url())
+            .map_err(GetFoldersError::from)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                DownloadRecursivePathError::NotFound(path.to_string())
+            })?;
+
+        let resources_file = data
+            .to_resources_file(http_client_arc, self.get_global_config());
+
+        Ok(resources_file
+            .download_recursive(save_absolute_path, options)
+            .await?)
+    }
+}
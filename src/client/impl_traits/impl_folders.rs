@@ -39,7 +39,7 @@ pub fn handle_result(
                 for resource_file_data in resource_data_list {
                     resources_files.push(
                         resource_file_data.to_resources_file(
-                            arg.http_client_arc.get_client(),
+                            arg.http_client_arc.clone(),
                             arg.global_config.clone(),
                         ),
                     )
@@ -65,6 +65,14 @@ impl Folders for WebDavClient {
     ) -> Result<TResourcesFileCollectionList, GetFoldersError> {
         let http_client_arc = self.get_http_client(key)?;
 
+        // 账号配置优先，没有设置时使用全局配置，每次调用都重新读取
+        let timeout_secs =
+            http_client_arc.get_config_data().timeout_secs.or_else(|| {
+                self.get_global_config()
+                    .get_current()
+                    .map(|cfg| cfg.timeout_secs)
+            });
+
         // 构建所有任务（这里只做并发请求）
         let tasks = paths.iter().map(|path| {
             let http_client_entity = http_client_arc.get_client();
@@ -73,8 +81,13 @@ impl Folders for WebDavClient {
                 let url = self.format_url_path(key, path)?;
 
                 // 调用已有的单次请求函数
-                get_folders_with_client(http_client_entity, &url, depth)
-                    .await
+                get_folders_with_client(
+                    http_client_entity,
+                    &url,
+                    depth,
+                    timeout_secs,
+                )
+                .await
             }
         });
 
@@ -1,9 +1,11 @@
+use crate::client::structs::account_config::AccountConfigData;
+use crate::client::structs::account_credentials::AccountCredentials;
 use crate::client::structs::client_key::ClientKey;
 use crate::client::structs::client_value::HttpClient;
 use crate::client::structs::reactive_child_clients::ReactiveChildClients;
 use crate::client::traits::account::{
     Account, AccountError, AddAccountError, GetHttpClientError,
-    RemoveAccountError, RemoveAccountForceError,
+    RemoveAccountError, RemoveAccountForceError, UpdateAccountError,
 };
 use crate::client::THttpClientArc;
 use std::sync::Arc;
@@ -37,12 +39,8 @@ impl Account for ReactiveChildClients {
             .into());
         }
 
-        let mut map = self.receiver.borrow().clone();
-        match map.remove(key) {
-            Some(_) => {
-                let _ = self.sender.send(map);
-                Ok(())
-            }
+        match self.remove(key) {
+            Some(_) => Ok(()),
             None => Err(RemoveAccountError::DeleteFailed(format!(
                 "删除失败：地址='{}', 账号='{}'",
                 key.get_base_url(),
@@ -67,16 +65,31 @@ impl Account for ReactiveChildClients {
         }
     }
 
+    fn update_account(
+        &self,
+        key: &ClientKey,
+        credentials: &AccountCredentials,
+        options: Option<AccountConfigData>,
+    ) -> Result<ClientKey, AccountError> {
+        let new_key =
+            ClientKey::new(key.get_base_url().as_str(), &credentials.username)
+                .map_err(UpdateAccountError::CreateKeyError)?;
+
+        self.replace(key, &new_key, |client| {
+            client
+                .replace_credentials(credentials, options)
+                .map_err(UpdateAccountError::CreateHttpClientError)
+        })?;
+
+        Ok(new_key)
+    }
+
     fn remove_account_force(
         &self,
         key: &ClientKey,
     ) -> Result<(), AccountError> {
-        let mut map = self.receiver.borrow().clone();
-        match map.remove(key) {
-            Some(_) => {
-                let _ = self.sender.send(map);
-                Ok(())
-            }
+        match self.remove(key) {
+            Some(_) => Ok(()),
             None => Err(RemoveAccountForceError::RemoveError(format!(
                 "强制删除失败：地址='{}', 账号='{}'",
                 key.get_base_url(),
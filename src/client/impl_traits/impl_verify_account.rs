@@ -0,0 +1,132 @@
+use crate::client::WebDavClient;
+use crate::client::structs::client_key::ClientKey;
+use crate::client::structs::client_value::HttpClient;
+use crate::client::structs::reactive_child_clients::ReactiveChildClients;
+use crate::client::traits::account::{
+    Account, AccountError, AddAccountError,
+};
+use crate::client::traits::verify_account::{
+    VerifyAccount, VerifyAccountError,
+};
+use crate::client::webdav_request::verify_account_impl::verify_account_with_client;
+use crate::global_config::global_config::ConfigData;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+impl ReactiveChildClients {
+    /// 校验已添加的账号，账号没有设置超时时使用 `global_timeout_secs`
+    pub(crate) async fn verify_account_with_timeout(
+        &self,
+        key: &ClientKey,
+        global_timeout_secs: Option<u64>,
+    ) -> Result<(), VerifyAccountError> {
+        let http_client = self.get_http_client(key)?;
+        let timeout_secs = http_client
+            .get_config_data()
+            .timeout_secs
+            .or(global_timeout_secs);
+
+        verify_account_with_client(
+            http_client.get_client(),
+            http_client.get_base_url().as_str(),
+            timeout_secs,
+        )
+        .await
+    }
+
+    /// 校验通过后才添加账号，新账号还没有自己的配置，使用 `timeout_secs`
+    pub(crate) async fn add_account_verified_with_timeout(
+        &self,
+        base_url: &str,
+        username: &str,
+        password: &str,
+        timeout_secs: Option<u64>,
+    ) -> Result<ClientKey, VerifyAccountError> {
+        let key = ClientKey::new(base_url, username).map_err(|e| {
+            AccountError::from(AddAccountError::CreateKeyError(e))
+        })?;
+
+        let http_client = HttpClient::new(base_url, username, password)
+            .map_err(|e| {
+                AccountError::from(AddAccountError::CreateHttpClientError(
+                    e,
+                ))
+            })?;
+
+        // 先校验，校验通过才写入账号列表，避免覆盖掉可用的旧账号
+        verify_account_with_client(
+            http_client.get_client(),
+            http_client.get_base_url().as_str(),
+            timeout_secs,
+        )
+        .await?;
+
+        self.insert(key.clone(), Arc::new(http_client));
+        Ok(key)
+    }
+}
+
+/// 单独使用账号列表时拿不到全局配置，超时使用全局配置的默认值
+#[async_trait]
+impl VerifyAccount for ReactiveChildClients {
+    async fn verify_account(
+        &self,
+        key: &ClientKey,
+    ) -> Result<(), VerifyAccountError> {
+        let timeout_secs = ConfigData::default().timeout_secs;
+        self.verify_account_with_timeout(key, Some(timeout_secs)).await
+    }
+
+    async fn add_account_verified(
+        &self,
+        base_url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<ClientKey, VerifyAccountError> {
+        let timeout_secs = ConfigData::default().timeout_secs;
+        self.add_account_verified_with_timeout(
+            base_url,
+            username,
+            password,
+            Some(timeout_secs),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl VerifyAccount for WebDavClient {
+    async fn verify_account(
+        &self,
+        key: &ClientKey,
+    ) -> Result<(), VerifyAccountError> {
+        self.child_clients
+            .verify_account_with_timeout(key, self.global_timeout_secs())
+            .await
+    }
+
+    async fn add_account_verified(
+        &self,
+        base_url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<ClientKey, VerifyAccountError> {
+        self.child_clients
+            .add_account_verified_with_timeout(
+                base_url,
+                username,
+                password,
+                self.global_timeout_secs(),
+            )
+            .await
+    }
+}
+
+impl WebDavClient {
+    /// 每次校验前重新读取全局超时，修改配置后立即生效
+    fn global_timeout_secs(&self) -> Option<u64> {
+        self.get_global_config()
+            .get_current()
+            .map(|cfg| cfg.timeout_secs)
+    }
+}
@@ -1,6 +1,8 @@
 use crate::client::structs::raw_file_xml::{
-    CurrentUserPrivilegeSet, MultiStatus, Prop, PropStat, Response,
+    Checksums, CurrentUserPrivilegeSet, MultiStatus, Prop, PropStat,
+    Response,
 };
+use crate::resource_file::structs::checksum::Checksum;
 use crate::resource_file::structs::resource_file_data::ResourceFileData;
 use crate::resource_file::traits::to_resource_file_data::{
     ToResourceFileData, ToResourceFileDataError,
@@ -61,6 +63,15 @@ fn extract_privileges(
     }
 }
 
+fn extract_checksums(checksums: Option<Checksums>) -> Vec<Checksum> {
+    // 一个 <oc:checksum> 里可能有多个算法，用空格分隔
+    checksums
+        .map(|c| {
+            c.values.iter().flat_map(|v| Checksum::parse_list(v)).collect()
+        })
+        .unwrap_or_default()
+}
+
 fn clean_etag(raw: Option<String>) -> Option<String> {
     // 去掉 ETag 的首尾引号以及多余空格
     raw.map(|s| s.trim().trim_matches('"').to_string())
@@ -101,6 +112,7 @@ impl ToResourceFileData for MultiStatus {
                 owner,
                 etag,
                 current_user_privilege_set,
+                checksums,
                 ..
             } = prop;
 
@@ -131,6 +143,7 @@ impl ToResourceFileData for MultiStatus {
                 owner,         // move
                 etag: clean_etag(etag),
                 privileges: extract_privileges(current_user_privilege_set),
+                checksums: extract_checksums(checksums),
             });
         }
 
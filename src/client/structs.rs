@@ -1,3 +1,6 @@
+pub mod account_config;
+pub mod account_credentials;
+pub mod account_registry_file;
 pub mod client_key;
 pub mod client_value;
 pub mod raw_file_xml;
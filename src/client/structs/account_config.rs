@@ -0,0 +1,236 @@
+use crate::global_config::global_config::GlobalFunctionSymbol;
+use crate::global_config::speed_limiter::SpeedLimiter;
+use crate::reactive::reactive::{ReactiveProperty, ReactivePropertyError};
+use crate::resource_file::enums::chunk_strategy::ChunkStrategy;
+use crate::resource_file::enums::conflict_policy::ConflictPolicy;
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AccountConfigError {
+    /// ReactiveProperty 内部更新失败
+    #[error("账号配置更新失败: {fun_symbol}")]
+    UpdateFailed {
+        fun_symbol: GlobalFunctionSymbol,
+        #[source]
+        source: ReactivePropertyError,
+    },
+}
+
+/// 账号级别的配置覆盖
+///
+/// 所有字段为 `None` 时表示沿用全局配置，
+/// 下载时按 资源 → 账号 → 全局 的顺序取第一个有值的配置。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountConfigData {
+    pub max_speed: Option<u64>,            // 限速
+    pub timeout_secs: Option<u64>,         // 超时
+    pub max_retries: Option<u32>,          // 最大重试次数
+    pub large_file_threshold: Option<u64>, // 如果文件大于该值，则自动分片下载
+    pub max_thread_count: Option<u32>,     // 最大线程数
+    pub chunk_strategy: Option<ChunkStrategy>, // 分片大小和并发数的调整方式
+    pub remote_changed_policy: Option<RemoteChangedPolicy>, // 远程文件变化的处理方式
+    pub atomic_write: Option<bool>,        // 原子下载
+    pub sidecar_checksum: Option<bool>,    // 读取同名 .sha256 文件校验
+    pub conflict_policy: Option<ConflictPolicy>, // 本地文件已存在时的处理方式
+    pub preserve_mtime: Option<bool>,      // 保留远程修改时间
+    pub preserve_readonly: Option<bool>,   // 没有写权限的文件设为只读
+    pub pause: bool,                       // 暂停该账号下的所有下载
+}
+
+type TAccountConfigData = ReactiveProperty<AccountConfigData>;
+
+/// # 账号配置
+///
+/// 与 [`GlobalConfig`](crate::global_config::global_config::GlobalConfig)
+/// 一样可以安全 `clone`，所有副本共享同一份状态，修改会实时影响该账号下正在进行的下载。
+#[derive(Debug, Clone)]
+pub struct AccountConfig {
+    inner: TAccountConfigData,
+    speed_limiter: Arc<SpeedLimiter>, // 该账号下所有下载共用的限速器
+}
+
+impl AccountConfig {
+    pub fn new(config: AccountConfigData) -> Self {
+        Self {
+            inner: ReactiveProperty::new(config),
+            speed_limiter: Arc::new(SpeedLimiter::new()),
+        }
+    }
+
+    /// 账号限速器，按账号的 `max_speed` 限制该账号下所有下载的总速度
+    pub(crate) fn get_speed_limiter(&self) -> &SpeedLimiter {
+        &self.speed_limiter
+    }
+
+    /// 当前账号限速，`None` 表示该账号不单独限速
+    pub fn max_speed(&self) -> Option<u64> {
+        self.get_current_borrow().as_ref().and_then(|cfg| cfg.max_speed)
+    }
+
+    /// 判断该账号是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        self.get_current().map(|cfg| cfg.pause).unwrap_or(false)
+    }
+
+    fn update_config(
+        &self,
+        fun_symbol: &str,
+        updater: impl FnOnce(&mut AccountConfigData),
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_field(updater).map_err(|e| {
+            AccountConfigError::UpdateFailed {
+                fun_symbol: fun_symbol.into(),
+                source: e,
+            }
+        })?;
+
+        Ok(self)
+    }
+
+    /// 暂停该账号下的所有下载
+    pub fn pause(&self) -> Result<&Self, AccountConfigError> {
+        self.update_config("pause", |cfg| cfg.pause = true)
+    }
+
+    /// 恢复该账号下的所有下载
+    pub fn resume(&self) -> Result<&Self, AccountConfigError> {
+        self.update_config("resume", |cfg| cfg.pause = false)
+    }
+
+    /// 设置该账号所有下载的总速度上限，`None` 表示该账号不单独限速
+    ///
+    /// 全局限速依然有效，实际速度取两者中更小的一个
+    pub fn set_max_speed(
+        &self,
+        speed: Option<u64>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_max_speed", |cfg| cfg.max_speed = speed)
+    }
+
+    /// 设置超时时间，`None` 表示沿用全局配置
+    pub fn set_timeout(
+        &self,
+        seconds: Option<u64>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_timeout", |cfg| cfg.timeout_secs = seconds)
+    }
+
+    /// 设置最大重试次数，`None` 表示沿用全局配置
+    pub fn set_max_retries(
+        &self,
+        retries: Option<u32>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_max_retries", |cfg| {
+            cfg.max_retries = retries
+        })
+    }
+
+    /// 设置大文件阈值，`None` 表示沿用全局配置
+    pub fn set_large_file_threshold(
+        &self,
+        threshold: Option<u64>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_large_file_threshold", |cfg| {
+            cfg.large_file_threshold = threshold
+        })
+    }
+
+    /// 设置最大线程数，`None` 表示按文件大小自动计算
+    pub fn set_max_thread_count(
+        &self,
+        count: Option<u32>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_max_thread_count", |cfg| {
+            cfg.max_thread_count = count
+        })
+    }
+
+    /// 设置分片策略，`None` 表示沿用全局配置
+    pub fn set_chunk_strategy(
+        &self,
+        strategy: Option<ChunkStrategy>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_chunk_strategy", |cfg| {
+            cfg.chunk_strategy = strategy
+        })
+    }
+
+    /// 设置续传时发现远程文件变化的处理方式，`None` 表示沿用全局配置
+    pub fn set_remote_changed_policy(
+        &self,
+        policy: Option<RemoteChangedPolicy>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_remote_changed_policy", |cfg| {
+            cfg.remote_changed_policy = policy
+        })
+    }
+
+    /// 设置是否使用原子下载，`None` 表示沿用全局配置
+    pub fn set_atomic_write(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_atomic_write", |cfg| {
+            cfg.atomic_write = enabled
+        })
+    }
+
+    /// 设置是否读取同名 `.sha256` 文件校验，`None` 表示沿用全局配置
+    pub fn set_sidecar_checksum(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_sidecar_checksum", |cfg| {
+            cfg.sidecar_checksum = enabled
+        })
+    }
+
+    /// 设置本地文件已存在时的处理方式，`None` 表示沿用全局配置
+    pub fn set_conflict_policy(
+        &self,
+        policy: Option<ConflictPolicy>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_conflict_policy", |cfg| {
+            cfg.conflict_policy = policy
+        })
+    }
+
+    /// 设置是否保留远程修改时间，`None` 表示沿用全局配置
+    pub fn set_preserve_mtime(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_preserve_mtime", |cfg| {
+            cfg.preserve_mtime = enabled
+        })
+    }
+
+    /// 设置是否把没有写权限的文件设为只读，`None` 表示沿用全局配置
+    pub fn set_preserve_readonly(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, AccountConfigError> {
+        self.update_config("set_preserve_readonly", |cfg| {
+            cfg.preserve_readonly = enabled
+        })
+    }
+}
+
+impl Default for AccountConfig {
+    fn default() -> Self {
+        Self::new(AccountConfigData::default())
+    }
+}
+
+impl Deref for AccountConfig {
+    type Target = TAccountConfigData;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
@@ -0,0 +1,34 @@
+use crate::client::enums::auth_kind::AuthKind;
+use std::fmt;
+
+/// 账号凭据，用于原地更新账号
+///
+/// 用户名参与生成 [`ClientKey`](crate::client::structs::client_key::ClientKey)，
+/// 修改用户名后账号会换到新的 key 下，但 `THttpClientArc` 保持不变。
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccountCredentials {
+    pub username: String,
+    pub password: String,
+    pub auth_kind: AuthKind,
+}
+
+impl AccountCredentials {
+    /// 使用 HTTP Basic 鉴权的凭据
+    pub fn basic(username: &str, password: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            auth_kind: AuthKind::Basic,
+        }
+    }
+}
+
+impl fmt::Debug for AccountCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountCredentials")
+            .field("username", &self.username)
+            .field("password", &"<hidden>")
+            .field("auth_kind", &self.auth_kind)
+            .finish()
+    }
+}
@@ -0,0 +1,213 @@
+use crate::client::enums::auth_kind::AuthKind;
+use crate::client::structs::account_config::AccountConfigData;
+use crate::client::traits::account_registry::AccountRegistryError;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, OsRng, Payload};
+use aes_gcm::{AeadCore, Aes256Gcm, KeyInit, Nonce};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt;
+
+/// 当前账号文件的格式版本
+pub const ACCOUNT_REGISTRY_VERSION: u32 = 1;
+
+const PBKDF2_ALGORITHM: &str = "pbkdf2-hmac-sha256";
+const PBKDF2_ROUNDS: u32 = 600_000;
+/// 导入时允许的派生轮数范围，防止构造的文件用超大轮数拖死导入
+const PBKDF2_MIN_ROUNDS: u32 = PBKDF2_ROUNDS;
+const PBKDF2_MAX_ROUNDS: u32 = PBKDF2_ROUNDS * 4;
+const SALT_LEN: usize = 16;
+
+/// 加密账号文件所用的密钥
+///
+/// - `Passphrase`：用户口令，会通过 PBKDF2 派生出真正的密钥，盐值保存在文件里
+/// - `Key`：调用方自己管理的 32 字节密钥（例如存放在系统钥匙串里），直接使用
+#[derive(Clone)]
+pub enum RegistryKey {
+    Passphrase(String),
+    Key([u8; 32]),
+}
+
+impl fmt::Debug for RegistryKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryKey::Passphrase(_) => {
+                f.write_str("Passphrase(<hidden>)")
+            }
+            RegistryKey::Key(_) => f.write_str("Key(<hidden>)"),
+        }
+    }
+}
+
+/// 口令派生参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryKdf {
+    pub algorithm: String,
+    pub iterations: u32,
+    pub salt: String,
+}
+
+/// 单个账号的持久化记录，只有密码是加密存储的
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRecord {
+    pub base_url: String,
+    pub username: String,
+    pub auth_kind: AuthKind,
+    pub nonce: String,
+    pub secret: String,
+    /// 账号级别的配置覆盖，旧版本导出的文件没有这一项
+    #[serde(default)]
+    pub config: AccountConfigData,
+}
+
+/// 账号文件的顶层结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRegistryFile {
+    pub version: u32,
+    /// 使用 [`RegistryKey::Key`] 导出时为空
+    pub kdf: Option<RegistryKdf>,
+    pub accounts: Vec<AccountRecord>,
+}
+
+fn encode(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, AccountRegistryError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| AccountRegistryError::InvalidEncoding(e.to_string()))
+}
+
+/// 把地址和账号绑定为附加数据，防止记录之间的密文被互相替换
+fn associated_data(base_url: &str, username: &str) -> Vec<u8> {
+    format!("{}\n{}", base_url, username).into_bytes()
+}
+
+impl RegistryKdf {
+    /// 生成一组新的派生参数（随机盐值）
+    pub(crate) fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        Self {
+            algorithm: PBKDF2_ALGORITHM.to_string(),
+            iterations: PBKDF2_ROUNDS,
+            salt: encode(&salt),
+        }
+    }
+}
+
+impl RegistryKey {
+    /// 根据文件里的派生参数得到加解密器
+    pub(crate) fn to_cipher(
+        &self,
+        kdf: Option<&RegistryKdf>,
+    ) -> Result<Aes256Gcm, AccountRegistryError> {
+        let key = match (self, kdf) {
+            (RegistryKey::Key(key), None) => *key,
+            (RegistryKey::Passphrase(passphrase), Some(kdf)) => {
+                if kdf.algorithm != PBKDF2_ALGORITHM {
+                    return Err(AccountRegistryError::KeyMismatch(
+                        format!("不支持的密钥派生算法：{}", kdf.algorithm),
+                    ));
+                }
+
+                if !(PBKDF2_MIN_ROUNDS..=PBKDF2_MAX_ROUNDS)
+                    .contains(&kdf.iterations)
+                {
+                    return Err(AccountRegistryError::KeyMismatch(
+                        format!("密钥派生轮数超出允许范围：{}", kdf.iterations),
+                    ));
+                }
+
+                let salt = decode(&kdf.salt)?;
+                let mut key = [0u8; 32];
+                pbkdf2::pbkdf2_hmac::<Sha256>(
+                    passphrase.as_bytes(),
+                    &salt,
+                    kdf.iterations,
+                    &mut key,
+                );
+                key
+            }
+            (RegistryKey::Key(_), Some(_)) => {
+                return Err(AccountRegistryError::KeyMismatch(
+                    "该文件使用口令加密，请提供口令".to_string(),
+                ));
+            }
+            (RegistryKey::Passphrase(_), None) => {
+                return Err(AccountRegistryError::KeyMismatch(
+                    "该文件使用密钥加密，请提供密钥".to_string(),
+                ));
+            }
+        };
+
+        Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| AccountRegistryError::Encrypt(e.to_string()))
+    }
+}
+
+impl AccountRecord {
+    /// 加密密码并生成记录
+    pub(crate) fn seal(
+        cipher: &Aes256Gcm,
+        base_url: &str,
+        username: &str,
+        auth_kind: AuthKind,
+        password: &str,
+        config: AccountConfigData,
+    ) -> Result<Self, AccountRegistryError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let aad = associated_data(base_url, username);
+
+        let secret = cipher
+            .encrypt(
+                &nonce,
+                Payload { msg: password.as_bytes(), aad: &aad },
+            )
+            .map_err(|e| AccountRegistryError::Encrypt(e.to_string()))?;
+
+        Ok(Self {
+            base_url: base_url.to_string(),
+            username: username.to_string(),
+            auth_kind,
+            nonce: encode(&nonce),
+            secret: encode(&secret),
+            config,
+        })
+    }
+
+    /// 解密出明文密码
+    pub(crate) fn open(
+        &self,
+        cipher: &Aes256Gcm,
+    ) -> Result<String, AccountRegistryError> {
+        let nonce = decode(&self.nonce)?;
+        if nonce.len() != 12 {
+            return Err(AccountRegistryError::InvalidEncoding(format!(
+                "nonce 长度错误：{}",
+                nonce.len()
+            )));
+        }
+
+        let secret = decode(&self.secret)?;
+        let aad = associated_data(&self.base_url, &self.username);
+
+        let password = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: &secret, aad: &aad },
+            )
+            .map_err(|_| {
+                AccountRegistryError::Decrypt(format!(
+                    "地址='{}', 账号='{}'",
+                    self.base_url, self.username
+                ))
+            })?;
+
+        String::from_utf8(password)
+            .map_err(|e| AccountRegistryError::Decrypt(e.to_string()))
+    }
+}
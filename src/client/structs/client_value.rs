@@ -1,11 +1,17 @@
 use crate::client::THttpClientArc;
+use crate::client::enums::auth_kind::AuthKind;
+use crate::client::structs::account_config::{
+    AccountConfig, AccountConfigData,
+};
+use crate::client::structs::account_credentials::AccountCredentials;
 use base64::Engine;
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 use reqwest::{Client, Url};
 use sha2::{Digest, Sha256};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use crate::client::format_base_url::format_base_url;
 use std::fmt;
+use zeroize::Zeroizing;
 
 pub fn encrypt_str(data: &str) -> String {
     let mut hasher = Sha256::new();
@@ -13,22 +19,74 @@ pub fn encrypt_str(data: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// 账号的可替换部分，更新凭据时整体替换
 #[derive(Clone)]
-pub struct HttpClient {
+struct HttpClientState {
     client: Client, // 这个客户端本身的clone就已经在内部实现了Rc，所以就不用Arc了
-    base_url: Url,
     encrypted_username: String,
     encrypted_password: String,
+    auth_kind: AuthKind,
+    // 明文凭据只用于导出账号列表（Basic 鉴权的请求头里本来也带着它们），
+    // 不实现 Debug，替换或释放时把内存清零
+    username: Zeroizing<String>,
+    password: Zeroizing<String>,
+}
+
+impl HttpClientState {
+    fn new(credentials: &AccountCredentials) -> Result<Self, String> {
+        let AccountCredentials { username, password, auth_kind } =
+            credentials;
+
+        // 构建失败必须向上抛出，否则会得到一个不带鉴权信息的默认客户端
+        let client = match auth_kind {
+            AuthKind::Basic => gen_http_client(username, password)?,
+        };
+
+        Ok(Self {
+            client,
+            encrypted_username: encrypt_str(username),
+            encrypted_password: encrypt_str(password),
+            auth_kind: *auth_kind,
+            username: Zeroizing::new(username.to_string()),
+            password: Zeroizing::new(password.to_string()),
+        })
+    }
+}
+
+/// 账号对应的 HTTP 客户端
+///
+/// 内部状态放在读写锁里，更新凭据时原地替换，
+/// 所有持有同一个 [`THttpClientArc`] 的地方（例如 `ResourcesFile`）
+/// 之后发出的请求都会自动使用新的客户端。
+pub struct HttpClient {
+    base_url: Url,
+    state: RwLock<HttpClientState>,
+    config: AccountConfig, // 账号级别的配置覆盖，不随凭据更新而替换
 }
 
 impl fmt::Debug for HttpClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("HttpClient")
-            .field("client", &"<Client with hidden authorization>")
-            .field("base_url", &self.base_url)
-            .field("encrypted_username", &self.encrypted_username)
-            .field("encrypted_password", &self.encrypted_password)
-            .finish()
+        self.with_state(|state| {
+            f.debug_struct("HttpClient")
+                .field("client", &"<Client with hidden authorization>")
+                .field("base_url", &self.base_url)
+                .field("encrypted_username", &state.encrypted_username)
+                .field("encrypted_password", &state.encrypted_password)
+                .field("auth_kind", &state.auth_kind)
+                .field("config", &self.config)
+                .finish()
+        })
+    }
+}
+
+impl Clone for HttpClient {
+    /// 克隆出的是当前状态的独立副本，之后两者的凭据更新互不影响
+    fn clone(&self) -> Self {
+        Self {
+            base_url: self.base_url.clone(),
+            state: RwLock::new(self.with_state(|state| state.clone())),
+            config: AccountConfig::new(self.get_config_data()),
+        }
     }
 }
 
@@ -38,18 +96,107 @@ impl HttpClient {
         username: &str,
         password: &str,
     ) -> Result<Self, String> {
-        let encrypted_username = encrypt_str(username);
-        let encrypted_password = encrypt_str(password);
+        Self::new_with_config(
+            base_url,
+            username,
+            password,
+            AccountConfigData::default(),
+        )
+    }
+
+    /// 创建客户端并带上账号级别的配置，主要用于从账号文件恢复账号
+    pub fn new_with_config(
+        base_url: &str,
+        username: &str,
+        password: &str,
+        config: AccountConfigData,
+    ) -> Result<Self, String> {
         let base_url =
             format_base_url(base_url).map_err(|e| e.to_string())?;
-        let client = gen_http_client(username, password)
-            .unwrap_or(Client::default());
+        let state = HttpClientState::new(&AccountCredentials::basic(
+            username, password,
+        ))?;
 
         Ok(Self {
-            client,
             base_url,
-            encrypted_username,
-            encrypted_password,
+            state: RwLock::new(state),
+            config: AccountConfig::new(config),
+        })
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&HttpClientState) -> R) -> R {
+        // 锁中毒只可能发生在替换状态时 panic，此时旧状态依然完整，可以继续使用
+        match self.state.read() {
+            Ok(state) => f(&state),
+            Err(poisoned) => f(&poisoned.into_inner()),
+        }
+    }
+
+    /// 原地替换账号的凭据，`options` 不为空时一并替换账号配置
+    ///
+    /// 新客户端构建成功、配置更新成功后才会替换凭据，
+    /// 任何一步失败时账号保持不变。
+    /// 凭据和配置在同一次写锁内替换，通过本对象读取的一方
+    /// 不会看到新凭据配旧配置的中间状态。
+    pub(crate) fn replace_credentials(
+        &self,
+        credentials: &AccountCredentials,
+        options: Option<AccountConfigData>,
+    ) -> Result<(), String> {
+        let new_state = HttpClientState::new(credentials)?;
+
+        let mut state = match self.state.write() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        // 先更新配置，失败时凭据还没替换，账号保持原样
+        if let Some(options) = options {
+            self.config.update(options).map_err(|e| e.to_string())?;
+        }
+
+        *state = new_state;
+
+        Ok(())
+    }
+
+    pub fn get_auth_kind(&self) -> AuthKind {
+        self.with_state(|state| state.auth_kind)
+    }
+
+    /// 获取明文凭据和账号配置的一致快照，仅供库内部导出账号列表时使用
+    pub(crate) fn get_credentials(
+        &self,
+    ) -> (AccountCredentials, AccountConfigData) {
+        self.with_state(|state| {
+            let credentials = AccountCredentials {
+                username: state.username.to_string(),
+                password: state.password.to_string(),
+                auth_kind: state.auth_kind,
+            };
+            let config = self
+                .config
+                .get_current()
+                .map(|cfg| cfg.as_ref().clone())
+                .unwrap_or_default();
+
+            (credentials, config)
+        })
+    }
+
+    /// 获取账号配置，返回的对象与账号共享状态，修改会实时生效
+    pub fn get_account_config(&self) -> AccountConfig {
+        self.config.clone()
+    }
+
+    /// 获取账号配置当前值的快照
+    pub fn get_config_data(&self) -> AccountConfigData {
+        // 持有读锁，保证不会和凭据更新交错
+        self.with_state(|_| {
+            self.config
+                .get_current()
+                .map(|cfg| cfg.as_ref().clone())
+                .unwrap_or_default()
         })
     }
 
@@ -62,15 +209,26 @@ impl HttpClient {
     }
 
     /// 这个函数获取的是一个客户端实体，但是它是被Arc内部克隆的，所以并不会有资源损耗
+    ///
+    /// 每次调用都会拿到当前最新的客户端，所以不要长期缓存返回值
     pub fn get_client(&self) -> Client {
-        self.client.clone()
+        self.with_state(|state| state.client.clone())
     }
 }
 
 impl PartialEq for HttpClient {
     fn eq(&self, other: &Self) -> bool {
-        self.encrypted_username.eq(&other.encrypted_username)
-            && self.encrypted_password.eq(&other.encrypted_password)
+        let (username, password) = self.with_state(|state| {
+            (
+                state.encrypted_username.clone(),
+                state.encrypted_password.clone(),
+            )
+        });
+
+        other.with_state(|other_state| {
+            username.eq(&other_state.encrypted_username)
+                && password.eq(&other_state.encrypted_password)
+        })
     }
 }
 
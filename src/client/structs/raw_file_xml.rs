@@ -73,6 +73,9 @@ pub struct Prop {
     /// `<current-user-privilege-set>`：当前用户对该资源的权限集合
     #[serde(rename = "current-user-privilege-set")]
     pub current_user_privilege_set: Option<CurrentUserPrivilegeSet>,
+
+    /// `<oc:checksums>`：ownCloud/Nextcloud 保存的文件校验值
+    pub checksums: Option<Checksums>,
 }
 
 /// 将 HTTP-date 格式的时间解析为 `DateTime<FixedOffset>`
@@ -105,6 +108,14 @@ pub struct ResourceType {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct EmptyElement {}
 
+/// `<oc:checksums>` 节点
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Checksums {
+    /// `<oc:checksum>` 子节点，内容形如 `SHA1:abc MD5:def`
+    #[serde(rename = "checksum", default)]
+    pub values: Vec<String>,
+}
+
 /// `<current-user-privilege-set>` 节点
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "kebab-case")]
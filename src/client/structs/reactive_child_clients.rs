@@ -1,8 +1,10 @@
+use crate::client::enums::account_event::AccountEvent;
 use crate::client::structs::client_key::ClientKey;
+use crate::client::traits::account::UpdateAccountError;
 use crate::client::THttpClientArc;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::watch;
+use tokio::sync::{broadcast, watch};
 
 pub type TWebDavChildClients = HashMap<ClientKey, THttpClientArc>;
 
@@ -10,22 +12,107 @@ pub type TReactiveChildClientsReceiver =
     watch::Receiver<TWebDavChildClients>;
 pub type TReactiveChildClientsSender = watch::Sender<TWebDavChildClients>;
 
+pub type TAccountEventReceiver = broadcast::Receiver<AccountEvent>;
+
+// 事件缓冲区大小，订阅者落后超过这个数量会收到 Lagged
+const ACCOUNT_EVENT_CAPACITY: usize = 64;
+
 #[derive(Debug, Clone)]
 pub struct ReactiveChildClients {
     pub(crate) sender: TReactiveChildClientsSender,
     pub(crate) receiver: TReactiveChildClientsReceiver,
+    event_sender: broadcast::Sender<AccountEvent>,
 }
 
 impl ReactiveChildClients {
     pub fn new() -> Self {
         let (sender, receiver) = watch::channel(HashMap::new());
-        Self { sender, receiver }
+        let (event_sender, _) = broadcast::channel(ACCOUNT_EVENT_CAPACITY);
+        Self { sender, receiver, event_sender }
     }
 
     pub fn insert(&self, key: ClientKey, client: THttpClientArc) {
-        let mut map = self.receiver.borrow().clone();
-        map.insert(key, client);
-        let _ = self.sender.send(map);
+        // 在 watch 的写锁内修改并发出事件，并发写入不会互相覆盖，
+        // 事件顺序也与实际修改顺序一致
+        self.sender.send_modify(|map| {
+            let event = match map.insert(key.clone(), client) {
+                Some(_) => AccountEvent::AccountUpdated(key),
+                None => AccountEvent::AccountAdded(key),
+            };
+            self.emit(event);
+        });
+    }
+
+    /// 从账号表中移除账号，不做任何引用计数检查
+    pub(crate) fn remove(&self, key: &ClientKey) -> Option<THttpClientArc> {
+        let mut removed = None;
+
+        self.sender.send_if_modified(|map| {
+            removed = map.remove(key);
+            if removed.is_some() {
+                self.emit(AccountEvent::AccountRemoved(key.clone()));
+            }
+            removed.is_some()
+        });
+
+        removed
+    }
+
+    /// 在写锁内更新账号，`new_key` 与 `key` 不同时把同一个客户端移到新的 key 下
+    ///
+    /// `apply` 失败或新 key 已被占用时账号表保持不变。
+    pub(crate) fn replace(
+        &self,
+        key: &ClientKey,
+        new_key: &ClientKey,
+        apply: impl FnOnce(&THttpClientArc) -> Result<(), UpdateAccountError>,
+    ) -> Result<(), UpdateAccountError> {
+        let mut result = Ok(());
+
+        self.sender.send_if_modified(|map| {
+            let Some(client) = map.get(key).cloned() else {
+                result = Err(UpdateAccountError::NotFound(format!(
+                    "地址='{}', 账号='{}'",
+                    key.get_base_url(),
+                    key.get_username()
+                )));
+                return false;
+            };
+
+            if new_key != key && map.contains_key(new_key) {
+                result = Err(UpdateAccountError::KeyConflict(format!(
+                    "地址='{}', 账号='{}'",
+                    new_key.get_base_url(),
+                    new_key.get_username()
+                )));
+                return false;
+            }
+
+            if let Err(e) = apply(&client) {
+                result = Err(e);
+                return false;
+            }
+
+            if new_key == key {
+                // 客户端是原地更新的，账号表的 key 没有变化，
+                // 但订阅者需要知道账号的凭据或配置已经更新
+                self.emit(AccountEvent::AccountUpdated(key.clone()));
+                return true;
+            }
+
+            map.remove(key);
+            map.insert(new_key.clone(), client);
+            self.emit(AccountEvent::AccountRemoved(key.clone()));
+            self.emit(AccountEvent::AccountAdded(new_key.clone()));
+            true
+        });
+
+        result
+    }
+
+    pub(crate) fn emit(&self, event: AccountEvent) {
+        // 没有订阅者时发送会失败，忽略即可
+        let _ = self.event_sender.send(event);
     }
 
     pub(crate) fn can_modify_value<T>(arc_client: &Arc<T>) -> bool {
@@ -36,4 +123,14 @@ impl ReactiveChildClients {
     pub fn get_reactive_receiver(&self) -> TReactiveChildClientsReceiver {
         self.receiver.clone()
     }
+
+    /// 订阅账号变更事件
+    ///
+    /// 只会收到订阅之后发生的事件；如果处理太慢导致落后，
+    /// 会收到 [`broadcast::error::RecvError::Lagged`]，
+    /// 此时可以通过 [`get_reactive_receiver`](Self::get_reactive_receiver)
+    /// 重新获取完整的账号表进行同步。
+    pub fn subscribe_account_events(&self) -> TAccountEventReceiver {
+        self.event_sender.subscribe()
+    }
 }
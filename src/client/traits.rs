@@ -1,4 +1,7 @@
 pub mod folders;
 pub mod account;
+pub mod account_registry;
 pub mod url_format;
 pub mod local_folders;
+pub mod verify_account;
+pub mod download_recursive;
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::client::structs::account_config::AccountConfigData;
+use crate::client::structs::account_credentials::AccountCredentials;
 use crate::client::structs::client_key::ClientKey;
 use crate::client::THttpClientArc;
 
@@ -27,6 +29,18 @@ pub enum RemoveAccountForceError {
     RemoveError(String),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateAccountError {
+    #[error("创建Key错误->{0}")]
+    CreateKeyError(String),
+    #[error("创建HTTP客户端错误->{0}")]
+    CreateHttpClientError(String),
+    #[error("新的账号已存在->{0}")]
+    KeyConflict(String),
+    #[error("账号不存在->{0}")]
+    NotFound(String),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum GetHttpClientError {
     #[error("查找失败->{0}")]
@@ -43,6 +57,8 @@ pub enum AccountError {
     RemoveAccountForceError(#[from] RemoveAccountForceError),
     #[error("[remove_account_force] 获取HTTP客户端函数出错->{0}")]
     GetHttpClientError(#[from] GetHttpClientError),
+    #[error("[update_account] 更新账号函数出错->{0}")]
+    UpdateAccountError(#[from] UpdateAccountError),
 }
 
 /// 定义账户管理功能的通用接口，
@@ -101,6 +117,32 @@ pub trait Account {
         key: &ClientKey,
     ) -> Result<THttpClientArc, AccountError>;
 
+    /// 原地更新指定账户的凭据，并可选地替换账号配置。
+    ///
+    /// 与先删除再添加不同，这里不做引用计数检查：
+    /// 账号内部的 HTTP 客户端会被原子替换，
+    /// 已经拿到的 `ResourcesFile` 之后发出的请求会自动使用新凭据，
+    /// 正在进行中的请求不受影响。
+    ///
+    /// 修改用户名会让账号换到新的 `ClientKey` 下，
+    /// 此时会依次发出 `AccountRemoved(旧 key)` 和 `AccountAdded(新 key)`；
+    /// 用户名不变时只发出 `AccountUpdated`。
+    ///
+    /// # 参数
+    /// * `key` - 对应的客户端唯一标识。
+    /// * `credentials` - 新的凭据
+    /// * `options` - 新的账号配置，`None` 表示保留当前配置
+    ///
+    /// # 返回值
+    /// 成功返回账号现在所在的 `ClientKey`；
+    /// 新客户端创建失败或新 key 已被其他账号占用时，旧的账号保持不变。
+    fn update_account(
+        &self,
+        key: &ClientKey,
+        credentials: &AccountCredentials,
+        options: Option<AccountConfigData>,
+    ) -> Result<ClientKey, AccountError>;
+
     /// **强制删除**指定账户的客户端实例，不做任何引用计数检查。
     ///
     /// ⚠️ **危险操作（DANGEROUS OPERATION）**
@@ -0,0 +1,77 @@
+use crate::client::structs::account_registry_file::RegistryKey;
+use crate::client::structs::client_key::ClientKey;
+use crate::client::traits::account::AccountError;
+use async_trait::async_trait;
+use std::path::Path;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccountRegistryError {
+    #[error("读写账号文件失败->{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("账号文件格式错误->{0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("不支持的账号文件版本->{0}")]
+    UnsupportedVersion(u32),
+
+    #[error("密钥与账号文件不匹配->{0}")]
+    KeyMismatch(String),
+
+    #[error("账号文件编码错误->{0}")]
+    InvalidEncoding(String),
+
+    #[error("加密失败->{0}")]
+    Encrypt(String),
+
+    /// 密钥错误或文件被篡改都会导致解密失败
+    #[error("解密失败，密钥错误或文件已损坏->{0}")]
+    Decrypt(String),
+
+    #[error("后台任务执行失败->{0}")]
+    Join(#[from] tokio::task::JoinError),
+
+    #[error("账号出错->{0}")]
+    AccountError(#[from] AccountError),
+}
+
+/// 账号列表的持久化接口
+///
+/// 导出的文件里只有密码是加密的（AES-256-GCM），
+/// 地址、用户名、鉴权方式以明文保存，方便排查问题。
+#[async_trait]
+pub trait AccountRegistry {
+    /// 把当前所有账号导出到文件。
+    ///
+    /// # 参数
+    /// * `path` - 导出的文件路径，已存在时会被覆盖；unix 下权限为 0600
+    /// * `key` - 加密密钥，见 [`RegistryKey`]
+    ///
+    /// # 返回值
+    /// 成功时返回导出的账号数量。
+    async fn export_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<usize, AccountRegistryError>;
+
+    /// 从文件导入账号。
+    ///
+    /// `ClientKey` 只由地址和用户名决定，所以导入后得到的 key
+    /// 与导出前完全一致，之前持久化的 key 引用可以继续使用。
+    ///
+    /// 所有记录都解密成功后才会写入账号列表，
+    /// 任意一条失败都不会导入任何账号；同名账号会被覆盖。
+    ///
+    /// # 参数
+    /// * `path` - 账号文件路径
+    /// * `key` - 导出时使用的密钥
+    ///
+    /// # 返回值
+    /// 成功时返回导入的全部 `ClientKey`。
+    async fn import_accounts(
+        &self,
+        path: &Path,
+        key: &RegistryKey,
+    ) -> Result<Vec<ClientKey>, AccountRegistryError>;
+}
@@ -0,0 +1,43 @@
+use crate::client::structs::client_key::ClientKey;
+use crate::client::webdav_request::get_folders_public_impl::GetFoldersError;
+use crate::resource_file::structs::recursive_download::{
+    RecursiveDownloadOptions, RecursiveDownloadReport,
+};
+use crate::resource_file::traits::download_recursive::DownloadRecursiveError;
+use async_trait::async_trait;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadRecursivePathError {
+    #[error("[download_recursive] 获取路径信息出错->{0}")]
+    GetFoldersError(#[from] GetFoldersError),
+
+    #[error("[download_recursive] 远程路径不存在->{0}")]
+    NotFound(String),
+
+    #[error("[download_recursive] 递归下载出错->{0}")]
+    DownloadRecursiveError(#[from] DownloadRecursiveError),
+}
+
+#[async_trait]
+pub trait DownloadRecursivePath {
+    /// 按路径递归下载远程文件夹或文件。
+    ///
+    /// # 参数
+    ///
+    /// * `key` - [`ClientKey`]，用于鉴权和标识客户端。
+    /// * `path` - 远程路径，写法与 `get_folders` 相同（Linux 标准路径，不能跳出根目录）。
+    /// * `save_absolute_path` - 本地保存目录，文件夹的内容直接保存在这里。
+    /// * `options` - 并发数和是否创建空文件夹。
+    ///
+    /// # 返回值
+    ///
+    /// - 成功时返回每个文件的处理结果，单个文件失败不影响其它文件。
+    /// - 路径不存在或者无法开始下载时返回 [`DownloadRecursivePathError`]。
+    async fn download_recursive(
+        &self,
+        key: &ClientKey,
+        path: &str,
+        save_absolute_path: &str,
+        options: RecursiveDownloadOptions,
+    ) -> Result<RecursiveDownloadReport, DownloadRecursivePathError>;
+}
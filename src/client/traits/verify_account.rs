@@ -0,0 +1,88 @@
+use crate::client::structs::client_key::ClientKey;
+use crate::client::traits::account::AccountError;
+use async_trait::async_trait;
+
+/// 校验账号时可能出现的错误
+///
+/// 每一种错误都对应一种明确的失败原因，方便调用方直接提示用户
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyAccountError {
+    /// 服务器返回 401，用户名或密码错误
+    #[error("账号或密码错误(401)->{0}")]
+    Unauthorized(String),
+
+    /// 服务器返回 403，账号没有访问该地址的权限
+    #[error("没有访问权限(403)->{0}")]
+    Forbidden(String),
+
+    /// 地址可以访问，但不是 WebDAV 服务（例如不支持 PROPFIND、路径不存在或返回了普通网页）
+    #[error("该地址不是 WebDAV 服务->{0}")]
+    NotWebDavEndpoint(String),
+
+    /// TLS 握手或证书校验失败
+    #[error("TLS 连接失败->{0}")]
+    Tls(String),
+
+    /// 域名解析失败
+    #[error("DNS 解析失败->{0}")]
+    Dns(String),
+
+    /// 无法建立连接（服务器不可达、端口未开放等）
+    #[error("无法连接服务器->{0}")]
+    Connect(String),
+
+    /// 请求超时
+    #[error("请求超时->{0}")]
+    Timeout(String),
+
+    /// 其它非预期的 HTTP 状态码
+    #[error("非预期的状态码 {0}->{1}")]
+    UnexpectedStatus(u16, String),
+
+    /// 其它 HTTP 错误
+    #[error("HTTP 请求失败->{0}")]
+    Http(reqwest::Error),
+
+    #[error("转换HeadMethod失败->{0}")]
+    ToHeadMethodError(String),
+
+    #[error("账号出错->{0}")]
+    AccountError(#[from] AccountError),
+}
+
+#[async_trait]
+pub trait VerifyAccount {
+    /// 校验已添加账号的凭据与可达性。
+    ///
+    /// 会对账号的基础 URL 发送一次 `Depth: 0` 的 `PROPFIND` 请求，
+    /// 只有服务器返回 `207 Multi-Status` 且响应体可以正常解析时才算校验通过。
+    ///
+    /// # 参数
+    /// * `key` - 对应的客户端唯一标识。
+    ///
+    /// # 返回值
+    /// 校验通过返回 `()`，否则返回 [`VerifyAccountError`] 说明具体的失败原因。
+    async fn verify_account(
+        &self,
+        key: &ClientKey,
+    ) -> Result<(), VerifyAccountError>;
+
+    /// 先校验凭据与可达性，校验通过后才添加账号。
+    ///
+    /// 与 [`Account::add_account`](crate::client::traits::account::Account::add_account)
+    /// 不同，校验失败时不会写入账号列表，也不会覆盖同名的已有账号。
+    ///
+    /// # 参数
+    /// * `base_url` - WebDAV 服务的基础 URL，例如 `https://example.com/dav`
+    /// * `username` - 登录用户名
+    /// * `password` - 登录密码
+    ///
+    /// # 返回值
+    /// 成功时返回 `ClientKey`；失败时返回 [`VerifyAccountError`]。
+    async fn add_account_verified(
+        &self,
+        base_url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<ClientKey, VerifyAccountError>;
+}
@@ -1 +1,3 @@
-pub mod get_folders_public_impl;
\ No newline at end of file
+pub mod get_folders_public_impl;
+pub mod timeout;
+pub(crate) mod verify_account_impl;
\ No newline at end of file
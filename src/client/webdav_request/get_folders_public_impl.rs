@@ -7,12 +7,18 @@ use reqwest::Client;
 use crate::client::enums::depth::Depth;
 use crate::client::enums::webdav_method::WebDavMethod;
 use crate::client::traits::url_format::UrlFormatError;
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, idle_timeout, response_timeout,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum GetFoldersError {
     #[error("HTTP 请求失败->{0}")]
     Http(#[from] reqwest::Error),
 
+    #[error("请求超时->{0}")]
+    Timeout(#[from] RequestTimeoutError),
+
     #[error("XML 解析失败->{0}")]
     XmlParse(#[from] quick_xml::DeError),
 
@@ -38,15 +44,19 @@ pub enum GetFoldersError {
     NotFindResourceCollector(String, String),
 }
 
-const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
-<D:propfind xmlns:D="DAV:">
+pub(crate) const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<D:propfind xmlns:D="DAV:" xmlns:oc="http://owncloud.org/ns">
   <D:allprop/>
+  <D:include>
+    <oc:checksums/>
+  </D:include>
 </D:propfind>"#;
 
 pub(crate) async fn get_folders_with_client(
     http_client: Client,
     absolute_url: &str,
     depth: &Depth,
+    timeout_secs: Option<u64>,
 ) -> Result<MultiStatus, GetFoldersError> {
     // 组装请求头
     let mut headers = HeaderMap::new();
@@ -60,16 +70,26 @@ pub(crate) async fn get_folders_with_client(
         .map_err(|e| GetFoldersError::ToHeadMethodError(e))?;
 
     // 发送 PROPFIND 到基准目录（已保证有尾部斜杠）
-    let res = http_client
+    let request = http_client
         .request(method, absolute_url)
         .headers(headers)
         .body(PROPFIND_BODY)
-        .send()
-        .await?;
+        .send();
+
+    let res = response_timeout(timeout_secs, request).await??;
 
     let status = res.status();
 
-    let xml_text = res.text().await?;
+    // 目录很大时响应体也很大，按块读取，每收到一块数据就重新计时
+    let mut body = res.bytes_stream();
+    let mut xml_bytes = Vec::new();
+
+    while let Some(chunk) = idle_timeout(timeout_secs, &mut body).await? {
+        xml_bytes.extend_from_slice(&chunk?);
+    }
+
+    // 与 `Response::text` 一样，非法的 UTF-8 字节替换成占位符
+    let xml_text = String::from_utf8_lossy(&xml_bytes);
 
     if !status.is_success() && status.as_u16() != 207 {
         return Err(GetFoldersError::StatusParseError(format!(
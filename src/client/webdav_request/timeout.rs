@@ -0,0 +1,52 @@
+use futures_util::{Stream, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RequestTimeoutError {
+    /// 连接建立或等待响应头超时
+    #[error("等待服务器响应超时（{0} 秒）")]
+    Response(u64),
+
+    /// 响应体读取过程中太久没有收到新数据
+    #[error("读取数据超时，{0} 秒内没有收到新数据")]
+    Idle(u64),
+}
+
+/// `None` 或 `0` 表示不限制超时
+fn to_duration(timeout_secs: Option<u64>) -> Option<Duration> {
+    timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs)
+}
+
+/// 给“发送请求 → 收到响应头”这一段加上超时，包括建立连接和 TLS 握手
+///
+/// 超时时间由调用方在每次请求前从响应式配置里读取，修改配置后下一个请求立即生效。
+pub(crate) async fn response_timeout<F: Future>(
+    timeout_secs: Option<u64>,
+    future: F,
+) -> Result<F::Output, RequestTimeoutError> {
+    match to_duration(timeout_secs) {
+        Some(duration) => tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| RequestTimeoutError::Response(duration.as_secs())),
+        None => Ok(future.await),
+    }
+}
+
+/// 从响应体里读取下一块数据，`timeout_secs` 内没有收到数据就超时
+///
+/// 超时只针对相邻两块数据之间的间隔，每读到一块数据就重新计时，
+/// 不限制整个响应体的读取时间，大文件只要数据一直在流动就不会被打断。
+/// 响应体读完时返回 `Ok(None)`。
+pub(crate) async fn idle_timeout<S: Stream + Unpin>(
+    timeout_secs: Option<u64>,
+    stream: &mut S,
+) -> Result<Option<S::Item>, RequestTimeoutError> {
+    match to_duration(timeout_secs) {
+        Some(duration) => tokio::time::timeout(duration, stream.next())
+            .await
+            .map_err(|_| RequestTimeoutError::Idle(duration.as_secs())),
+        None => Ok(stream.next().await),
+    }
+}
@@ -0,0 +1,181 @@
+use crate::client::enums::depth::Depth;
+use crate::client::enums::webdav_method::WebDavMethod;
+use crate::client::structs::raw_file_xml::MultiStatus;
+use crate::client::traits::verify_account::VerifyAccountError;
+use crate::client::webdav_request::get_folders_public_impl::PROPFIND_BODY;
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, response_timeout,
+};
+use quick_xml::de::from_str;
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::{Client, StatusCode};
+use std::error::Error;
+use std::io;
+
+/// 把整条错误链拼成一个字符串，作为错误信息返回给使用者
+fn error_chain_text(e: &reqwest::Error) -> String {
+    let mut text = e.to_string();
+    let mut source = e.source();
+
+    while let Some(err) = source {
+        text.push_str(": ");
+        text.push_str(&err.to_string());
+        source = err.source();
+    }
+
+    text
+}
+
+/// 沿着错误链查找 `T` 类型的底层错误
+///
+/// `io::Error` 的 `source()` 会跳过它包着的那一层错误，而 rustls 的错误
+/// 往往包在好几层 `io::Error` 里，所以遇到 `io::Error` 时改用 `get_ref()` 往下找。
+fn find_source<T: Error + 'static>(e: &reqwest::Error) -> Option<&T> {
+    let mut source: Option<&(dyn Error + 'static)> = Some(e);
+
+    while let Some(err) = source {
+        if let Some(found) = err.downcast_ref::<T>() {
+            return Some(found);
+        }
+
+        source = match err.downcast_ref::<io::Error>() {
+            Some(io_error) => io_error
+                .get_ref()
+                .map(|inner| inner as &(dyn Error + 'static)),
+            None => err.source(),
+        };
+    }
+
+    None
+}
+
+/// 建立连接时域名解析失败，hyper 用固定的 `dns error` 描述这一层错误
+fn is_dns_error(e: &reqwest::Error) -> bool {
+    let mut source = e.source();
+
+    while let Some(err) = source {
+        if err.to_string() == "dns error" {
+            return true;
+        }
+        source = err.source();
+    }
+
+    false
+}
+
+/// 按错误链里的底层错误类型分类，不匹配 URL、主机名等会出现在描述里的文字
+fn classify_request_error(e: reqwest::Error) -> VerifyAccountError {
+    let chain = error_chain_text(&e);
+
+    if e.is_timeout() {
+        return VerifyAccountError::Timeout(chain);
+    }
+
+    // DNS、TLS 都发生在建立连接的阶段，其它错误原样返回
+    if !e.is_connect() {
+        return VerifyAccountError::Http(e);
+    }
+
+    if is_dns_error(&e) {
+        return VerifyAccountError::Dns(chain);
+    }
+
+    if find_source::<rustls::Error>(&e).is_some() {
+        return VerifyAccountError::Tls(chain);
+    }
+
+    VerifyAccountError::Connect(chain)
+}
+
+/// 服务器一直不响应时按超时处理，和 reqwest 自己报告的超时归为一类
+fn classify_timeout_error(e: RequestTimeoutError) -> VerifyAccountError {
+    VerifyAccountError::Timeout(e.to_string())
+}
+
+/// 对基础 URL 发送 `Depth: 0` 的 PROPFIND，用于校验账号
+///
+/// `timeout_secs` 同时限制等待响应头和读取响应体的时间，`None` 或 `0` 表示不限制。
+pub(crate) async fn verify_account_with_client(
+    http_client: Client,
+    absolute_url: &str,
+    timeout_secs: Option<u64>,
+) -> Result<(), VerifyAccountError> {
+    let mut headers = HeaderMap::new();
+    headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/xml"));
+    headers
+        .insert("Depth", HeaderValue::from_static(Depth::Zero.as_str()));
+    headers.insert("Accept", HeaderValue::from_static("application/xml"));
+
+    let method = WebDavMethod::PROPFIND
+        .to_head_method()
+        .map_err(VerifyAccountError::ToHeadMethodError)?;
+
+    let request = http_client
+        .request(method, absolute_url)
+        .headers(headers)
+        .body(PROPFIND_BODY)
+        .send();
+
+    let res = response_timeout(timeout_secs, request)
+        .await
+        .map_err(classify_timeout_error)?
+        .map_err(classify_request_error)?;
+
+    let status = res.status();
+
+    match status {
+        StatusCode::MULTI_STATUS => {}
+        StatusCode::UNAUTHORIZED => {
+            return Err(VerifyAccountError::Unauthorized(
+                absolute_url.to_string(),
+            ));
+        }
+        StatusCode::FORBIDDEN => {
+            return Err(VerifyAccountError::Forbidden(
+                absolute_url.to_string(),
+            ));
+        }
+        // 对基础地址的 PROPFIND 返回 404，说明 WebDAV 路径填错了
+        StatusCode::NOT_FOUND => {
+            return Err(VerifyAccountError::NotWebDavEndpoint(format!(
+                "{} 不存在，请检查 WebDAV 路径",
+                absolute_url
+            )));
+        }
+        // 不认识 PROPFIND，或者返回的是普通页面
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => {
+            return Err(VerifyAccountError::NotWebDavEndpoint(format!(
+                "{} 不支持 PROPFIND，状态码 {}",
+                absolute_url, status
+            )));
+        }
+        status if status.is_success() => {
+            return Err(VerifyAccountError::NotWebDavEndpoint(format!(
+                "{} 返回了 {} 而不是 207 Multi-Status",
+                absolute_url, status
+            )));
+        }
+        status => {
+            return Err(VerifyAccountError::UnexpectedStatus(
+                status.as_u16(),
+                absolute_url.to_string(),
+            ));
+        }
+    }
+
+    let xml_text = response_timeout(timeout_secs, res.text())
+        .await
+        .map_err(classify_timeout_error)?
+        .map_err(classify_request_error)?;
+
+    // 207 但内容不是 multistatus，同样视为非 WebDAV 服务
+    from_str::<MultiStatus>(&xml_text).map_err(|e| {
+        VerifyAccountError::NotWebDavEndpoint(format!(
+            "{} 的响应无法解析为 multistatus: {}",
+            absolute_url, e
+        ))
+    })?;
+
+    Ok(())
+}
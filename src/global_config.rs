@@ -1 +1,5 @@
-pub mod global_config;
\ No newline at end of file
+pub mod global_config;
+pub mod host_policy;
+pub mod host_policy_registry;
+pub mod request_rate_limiter;
+pub mod speed_limiter;
\ No newline at end of file
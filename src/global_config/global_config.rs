@@ -1,5 +1,11 @@
+use crate::global_config::host_policy_registry::HostPolicyRegistry;
+use crate::global_config::speed_limiter::SpeedLimiter;
 use crate::reactive::reactive::{ReactiveProperty, ReactivePropertyError};
+use crate::resource_file::enums::chunk_strategy::ChunkStrategy;
+use crate::resource_file::enums::conflict_policy::ConflictPolicy;
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
 use std::ops::Deref;
+use std::sync::Arc;
 use thiserror::Error;
 
 pub const DEFAULT_LARGE_FILE_THRESHOLD: u64 = 5 * 1024 * 1024;
@@ -29,10 +35,17 @@ pub enum GlobalConfigError {
 pub struct ConfigData {
     pub max_speed: Option<u64>,    // 限速
     pub timeout_secs: u64,         // 超时
-    pub max_retries: u32,          // 最大重试次数
+    pub max_retries: u32,          // 最大重试次数（按文件或分片累计）
     pub large_file_threshold: u64, // 如果文件大于该值，则自动分片下载
+    pub chunk_strategy: ChunkStrategy, // 分片大小和并发数的调整方式
     pub enable_global_pause: bool, // 打开全局暂停功能
     pub global_pause: bool,        // 全局暂停标志
+    pub remote_changed_policy: RemoteChangedPolicy, // 续传时远程文件变化的处理方式
+    pub atomic_write: bool, // 先写临时文件，完成后再替换目标文件
+    pub sidecar_checksum: bool, // 下载完成后读取同名 .sha256 文件校验
+    pub conflict_policy: ConflictPolicy, // 本地文件已存在时的处理方式
+    pub preserve_mtime: bool, // 下载完成后把本地修改时间设为远程的修改时间
+    pub preserve_readonly: bool, // 没有写权限的远程文件在本地设为只读
 }
 
 impl Default for ConfigData {
@@ -42,8 +55,15 @@ impl Default for ConfigData {
             timeout_secs: 30,
             max_retries: 4,
             large_file_threshold: DEFAULT_LARGE_FILE_THRESHOLD,
+            chunk_strategy: ChunkStrategy::default(),
             enable_global_pause: false,
             global_pause: false,
+            remote_changed_policy: RemoteChangedPolicy::default(),
+            atomic_write: false,
+            sidecar_checksum: false,
+            conflict_policy: ConflictPolicy::default(),
+            preserve_mtime: false,
+            preserve_readonly: false,
         }
     }
 }
@@ -73,6 +93,8 @@ impl Default for ConfigData {
 #[derive(Clone, Debug)]
 pub struct GlobalConfig {
     inner: ReactiveProperty<ConfigData>,
+    speed_limiter: Arc<SpeedLimiter>, // 所有下载共用的限速器
+    host_policies: HostPolicyRegistry, // 按服务器区分的访问限制
 }
 
 impl GlobalConfig {
@@ -80,7 +102,29 @@ impl GlobalConfig {
     ///
     /// 一般使用GlobalConfig::default()而不是new()
     pub fn new(config: ConfigData) -> Self {
-        Self { inner: ReactiveProperty::new(config) }
+        Self {
+            inner: ReactiveProperty::new(config),
+            speed_limiter: Arc::new(SpeedLimiter::new()),
+            host_policies: HostPolicyRegistry::default(),
+        }
+    }
+
+    /// 全局限速器，按 `max_speed` 限制所有下载的总速度
+    pub(crate) fn get_speed_limiter(&self) -> &SpeedLimiter {
+        &self.speed_limiter
+    }
+
+    /// 主机策略表，决定哪些服务器允许分片下载、并发数和请求频率的上限
+    ///
+    /// 默认只包含坚果云一条，可以在运行中修改或从文件读取，
+    /// 见 [`HostPolicyRegistry`]。
+    pub fn get_host_policies(&self) -> &HostPolicyRegistry {
+        &self.host_policies
+    }
+
+    /// 当前全局限速
+    pub fn max_speed(&self) -> Option<u64> {
+        self.get_current_borrow().as_ref().and_then(|cfg| cfg.max_speed)
     }
 
     /// 判断是否启用了全局暂停功能
@@ -154,7 +198,8 @@ impl GlobalConfig {
         }
     }
 
-    /// 设置最大下载速度
+    /// 设置最大下载速度（字节/秒），所有下载共享这个速度，
+    /// 正在进行的下载也会立即按新的速度限速
     pub fn set_max_speed(
         &self,
         speed: Option<u64>,
@@ -184,6 +229,9 @@ impl GlobalConfig {
     }
 
     /// 设置最大重试次数
+    ///
+    /// 次数按整个文件（分片下载时按每个分片）累计，
+    /// 断点续传的重试也计入，不会因为某次写入了部分数据而重新计数。
     pub fn set_max_retries(
         &self,
         retries: u32,
@@ -211,11 +259,127 @@ impl GlobalConfig {
 
         Ok(self)
     }
+
+    /// 设置分片策略
+    ///
+    /// 只影响分片下载，对之后开始的下载生效。
+    pub fn set_chunk_strategy(
+        &self,
+        strategy: ChunkStrategy,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.chunk_strategy = strategy).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_chunk_strategy".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
+
+    /// 设置续传时发现远程文件变化的处理方式
+    pub fn set_remote_changed_policy(
+        &self,
+        policy: RemoteChangedPolicy,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.remote_changed_policy = policy)
+            .map_err(|e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_remote_changed_policy".into(),
+                source: e,
+            })?;
+
+        Ok(self)
+    }
+
+    /// 设置是否使用原子下载
+    ///
+    /// 开启后先下载到同目录下的临时文件，校验通过后再替换目标文件，
+    /// 下载失败不会破坏已有的本地文件，其它程序也不会读到写了一半的文件。
+    pub fn set_atomic_write(
+        &self,
+        enabled: bool,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.atomic_write = enabled).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_atomic_write".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
+
+    /// 设置是否读取同名 `.sha256` 文件校验下载结果
+    ///
+    /// 每个文件会多发一次请求，服务器没有这个文件时跳过。
+    pub fn set_sidecar_checksum(
+        &self,
+        enabled: bool,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.sidecar_checksum = enabled).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_sidecar_checksum".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
+
+    /// 设置本地目标文件已经存在时的处理方式，见 [`ConflictPolicy`]
+    pub fn set_conflict_policy(
+        &self,
+        policy: ConflictPolicy,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.conflict_policy = policy).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_conflict_policy".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
+
+    /// 设置是否保留远程文件的修改时间
+    ///
+    /// 开启后下载完成的文件、递归下载创建的文件夹都会使用远程的 `last_modified`，
+    /// 同步和备份工具不会把每个下载过的文件都当成已修改。
+    pub fn set_preserve_mtime(
+        &self,
+        enabled: bool,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.preserve_mtime = enabled).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_preserve_mtime".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
+
+    /// 设置是否把当前账号没有写权限的远程文件在本地设为只读
+    ///
+    /// 服务器没有返回权限信息时不做修改。
+    pub fn set_preserve_readonly(
+        &self,
+        enabled: bool,
+    ) -> Result<&Self, GlobalConfigError> {
+        self.update_field(|cfg| cfg.preserve_readonly = enabled).map_err(
+            |e| GlobalConfigError::UpdateFailed {
+                fun_symbol: "set_preserve_readonly".into(),
+                source: e,
+            },
+        )?;
+
+        Ok(self)
+    }
 }
 
 impl Default for GlobalConfig {
     fn default() -> Self {
-        Self { inner: ReactiveProperty::new(ConfigData::default()) }
+        Self::new(ConfigData::default())
     }
 }
 
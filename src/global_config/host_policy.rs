@@ -0,0 +1,110 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 当前主机策略文件的格式版本
+pub const HOST_POLICY_VERSION: u32 = 1;
+
+/// 主机策略匹配哪些地址
+///
+/// - `UrlPrefix`：地址以这个前缀开头，例如 `https://dav.jianguoyun.com/`
+/// - `Host`：主机名完全相同（不区分大小写）；以 `*.` 开头时匹配它的所有子域名，
+///   例如 `*.example.com` 匹配 `dav.example.com`，但不匹配 `example.com` 本身
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HostPattern {
+    UrlPrefix(String),
+    Host(String),
+}
+
+impl HostPattern {
+    /// 判断地址是否匹配，无法解析出主机名的地址不匹配任何 `Host`
+    pub fn matches(&self, url: &str) -> bool {
+        match self {
+            HostPattern::UrlPrefix(prefix) => url.starts_with(prefix),
+            HostPattern::Host(pattern) => {
+                let Some(host) = Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_lowercase))
+                else {
+                    return false;
+                };
+
+                let pattern = pattern.to_lowercase();
+
+                match pattern.strip_prefix("*.") {
+                    Some(domain) => host
+                        .strip_suffix(domain)
+                        .is_some_and(|sub| sub.ends_with('.')),
+                    None => host == pattern,
+                }
+            }
+        }
+    }
+}
+
+/// # 主机策略
+///
+/// 限制对某一类服务器的访问方式。同一个策略的所有下载共用并发数和请求频率的名额，
+/// 有的厂商按请求次数计费或者对 Range 请求限流，可以在这里单独限制。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostPolicy {
+    pub pattern: HostPattern,
+    /// 自动模式下是否允许分片下载，不允许时连探测请求都不发
+    #[serde(default = "default_allow_chunked")]
+    pub allow_chunked: bool,
+    /// 同时进行的下载请求数上限，`None` 或 `0` 表示不限制
+    #[serde(default)]
+    pub max_concurrency: Option<u32>,
+    /// 每分钟最多发出的请求数，重试也算在内，`None` 或 `0` 表示不限制
+    #[serde(default)]
+    pub max_requests_per_minute: Option<u32>,
+}
+
+fn default_allow_chunked() -> bool {
+    true
+}
+
+impl HostPolicy {
+    /// 创建一个不做任何限制的策略
+    pub fn new(pattern: HostPattern) -> Self {
+        Self {
+            pattern,
+            allow_chunked: true,
+            max_concurrency: None,
+            max_requests_per_minute: None,
+        }
+    }
+
+    /// 默认策略
+    ///
+    /// 坚果云拒绝分片请求，甚至拿 1 比特数据都要算下载了整个文件的流量。
+    /// 分片下载前虽然会先探测服务器是否支持 Range，
+    /// 但探测请求本身也会被算流量，所以直接禁止分片。
+    pub fn defaults() -> Vec<HostPolicy> {
+        vec![HostPolicy {
+            allow_chunked: false,
+            ..HostPolicy::new(HostPattern::UrlPrefix(
+                "https://dav.jianguoyun.com/".into(),
+            ))
+        }]
+    }
+}
+
+/// 主机策略文件的顶层结构，`policies` 按匹配顺序排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostPolicyFile {
+    pub version: u32,
+    pub policies: Vec<HostPolicy>,
+}
+
+#[derive(Debug, Error)]
+pub enum HostPolicyError {
+    #[error("读写主机策略文件失败: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("主机策略文件格式错误: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("不支持的主机策略文件版本: {0}")]
+    UnsupportedVersion(u32),
+}
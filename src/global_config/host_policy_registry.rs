@@ -0,0 +1,197 @@
+use crate::global_config::host_policy::{
+    HOST_POLICY_VERSION, HostPattern, HostPolicy, HostPolicyError,
+    HostPolicyFile,
+};
+use crate::global_config::request_rate_limiter::RequestRateLimiter;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+/// 一个策略运行时的名额，策略被修改时整个替换，
+/// 已经拿到的名额在旧的限制器上归还，新的限制对之后的请求生效
+#[derive(Debug)]
+struct HostLimiter {
+    connections: Option<Arc<Semaphore>>,
+    requests: Option<RequestRateLimiter>,
+}
+
+impl HostLimiter {
+    fn new(policy: &HostPolicy) -> Self {
+        Self {
+            connections: policy
+                .max_concurrency
+                .filter(|count| *count > 0)
+                .map(|count| Arc::new(Semaphore::new(count as usize))),
+            requests: policy
+                .max_requests_per_minute
+                .filter(|count| *count > 0)
+                .map(RequestRateLimiter::new),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PolicyEntry {
+    policy: HostPolicy,
+    limiter: Arc<HostLimiter>,
+}
+
+impl PolicyEntry {
+    fn new(policy: HostPolicy) -> Self {
+        let limiter = Arc::new(HostLimiter::new(&policy));
+        Self { policy, limiter }
+    }
+}
+
+/// # 主机策略表
+///
+/// 按顺序保存 [`HostPolicy`]，一个地址只使用第一个匹配的策略，
+/// 新加入的策略排在最前面，可以覆盖默认策略和更宽泛的匹配规则。
+///
+/// 与 [`GlobalConfig`](super::global_config::GlobalConfig) 一样可以安全地 `clone`，
+/// 所有副本共享同一张表，运行中修改会立即影响之后发出的请求。
+#[derive(Debug, Clone)]
+pub struct HostPolicyRegistry {
+    entries: Arc<Mutex<Vec<PolicyEntry>>>,
+}
+
+impl HostPolicyRegistry {
+    /// 使用给定的策略创建，`policies` 按匹配顺序排列
+    pub fn new(policies: Vec<HostPolicy>) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(
+                policies.into_iter().map(PolicyEntry::new).collect(),
+            )),
+        }
+    }
+
+    fn lock_entries(&self) -> MutexGuard<'_, Vec<PolicyEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 全部策略，按匹配顺序排列
+    pub fn list(&self) -> Vec<HostPolicy> {
+        self.lock_entries()
+            .iter()
+            .map(|entry| entry.policy.clone())
+            .collect()
+    }
+
+    /// 地址使用的策略，没有匹配的策略时返回 `None`
+    pub fn find(&self, url: &str) -> Option<HostPolicy> {
+        self.lock_entries()
+            .iter()
+            .find(|entry| entry.policy.pattern.matches(url))
+            .map(|entry| entry.policy.clone())
+    }
+
+    /// 自动模式下是否允许对这个地址分片下载
+    pub fn allows_chunked(&self, url: &str) -> bool {
+        self.find(url).is_none_or(|policy| policy.allow_chunked)
+    }
+
+    /// 添加策略并放到最前面；已有相同匹配规则的策略时原地替换，顺序不变
+    pub fn set(&self, policy: HostPolicy) -> &Self {
+        let mut entries = self.lock_entries();
+
+        match entries
+            .iter_mut()
+            .find(|entry| entry.policy.pattern == policy.pattern)
+        {
+            Some(entry) => *entry = PolicyEntry::new(policy),
+            None => entries.insert(0, PolicyEntry::new(policy)),
+        }
+
+        drop(entries);
+        self
+    }
+
+    /// 删除匹配规则相同的策略，返回被删除的策略
+    pub fn remove(&self, pattern: &HostPattern) -> Option<HostPolicy> {
+        let mut entries = self.lock_entries();
+        let index = entries
+            .iter()
+            .position(|entry| &entry.policy.pattern == pattern)?;
+
+        Some(entries.remove(index).policy)
+    }
+
+    /// 用新的策略替换整张表，`policies` 按匹配顺序排列
+    pub fn replace_all(&self, policies: Vec<HostPolicy>) -> &Self {
+        *self.lock_entries() =
+            policies.into_iter().map(PolicyEntry::new).collect();
+        self
+    }
+
+    /// 从文件读取策略并替换整张表，返回读取到的策略数量
+    ///
+    /// 文件内容有误时不会修改当前的策略。
+    pub async fn load(
+        &self,
+        path: &Path,
+    ) -> Result<usize, HostPolicyError> {
+        let json = tokio::fs::read(path).await?;
+        let file: HostPolicyFile = serde_json::from_slice(&json)?;
+
+        if file.version != HOST_POLICY_VERSION {
+            return Err(HostPolicyError::UnsupportedVersion(file.version));
+        }
+
+        let count = file.policies.len();
+        self.replace_all(file.policies);
+
+        Ok(count)
+    }
+
+    /// 把当前的策略写入文件
+    pub async fn save(&self, path: &Path) -> Result<(), HostPolicyError> {
+        let file = HostPolicyFile {
+            version: HOST_POLICY_VERSION,
+            policies: self.list(),
+        };
+
+        let json = serde_json::to_vec_pretty(&file)?;
+        tokio::fs::write(path, json).await?;
+
+        Ok(())
+    }
+
+    fn limiter(&self, url: &str) -> Option<Arc<HostLimiter>> {
+        self.lock_entries()
+            .iter()
+            .find(|entry| entry.policy.pattern.matches(url))
+            .map(|entry| Arc::clone(&entry.limiter))
+    }
+
+    /// 占用一个连接名额，策略没有限制并发数时返回 `None`
+    pub(crate) async fn acquire_connection(
+        &self,
+        url: &str,
+    ) -> Result<Option<OwnedSemaphorePermit>, AcquireError> {
+        let connections = self
+            .limiter(url)
+            .and_then(|limiter| limiter.connections.clone());
+
+        match connections {
+            Some(connections) => {
+                Ok(Some(connections.acquire_owned().await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 发出请求前调用，超过每分钟请求数时在这里等待
+    pub(crate) async fn wait_request_slot(&self, url: &str) {
+        if let Some(limiter) = self.limiter(url)
+            && let Some(requests) = &limiter.requests
+        {
+            requests.acquire().await;
+        }
+    }
+}
+
+impl Default for HostPolicyRegistry {
+    fn default() -> Self {
+        Self::new(HostPolicy::defaults())
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+// 统计请求数的时间窗口
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// # 请求频率限制器
+///
+/// 记录最近一分钟内发出的每个请求的时间（滑动窗口），
+/// 窗口内的请求数到达上限时等待最早的那个请求移出窗口。
+///
+/// 和 [`SpeedLimiter`](super::speed_limiter::SpeedLimiter) 一样使用 tokio 的 `Mutex`，
+/// 等待者按先来后到的顺序发出请求。
+#[derive(Debug)]
+pub struct RequestRateLimiter {
+    max_requests_per_minute: u32,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl RequestRateLimiter {
+    pub fn new(max_requests_per_minute: u32) -> Self {
+        Self {
+            max_requests_per_minute: max_requests_per_minute.max(1),
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// 每分钟最多允许的请求数
+    pub fn max_requests_per_minute(&self) -> u32 {
+        self.max_requests_per_minute
+    }
+
+    /// 等待到可以再发出一个请求为止，并把这个请求记入窗口
+    pub async fn acquire(&self) {
+        let mut sent = self.sent.lock().await;
+
+        loop {
+            let now = Instant::now();
+
+            while sent.front().is_some_and(|&at| at + WINDOW <= now) {
+                sent.pop_front();
+            }
+
+            if sent.len() < self.max_requests_per_minute as usize {
+                sent.push_back(now);
+                return;
+            }
+
+            if let Some(&oldest) = sent.front() {
+                tokio::time::sleep_until(oldest + WINDOW).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+// 单次等待的最长时间，等待期间会重新读取限速值，保证修改限速后很快生效
+const MAX_WAIT_SLICE: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64, // 可以为负数，表示已经透支的字节数
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, rate: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        // 桶容量为一秒的流量，空闲再久也只允许突发一秒
+        let capacity = rate as f64;
+        self.tokens = (self.tokens + elapsed * rate as f64).min(capacity);
+    }
+
+    fn reset(&mut self) {
+        self.tokens = 0.0;
+        self.last_refill = Instant::now();
+    }
+}
+
+/// # 令牌桶限速器
+///
+/// 每读到一块数据就调用一次 [`acquire`](Self::acquire)，
+/// 令牌不足时在这里等待。限速值不保存在限速器里，
+/// 而是每次等待前通过闭包从响应式配置中读取，所以传输过程中修改限速会立即生效。
+///
+/// 内部使用 tokio 的 `Mutex`，等待者按先来后到的顺序获取令牌，
+/// 多个分片任务共用同一个限速器时带宽会被平均分配。
+#[derive(Debug)]
+pub struct SpeedLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+impl SpeedLimiter {
+    pub fn new() -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                tokens: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// 消耗 `amount` 字节的令牌
+    ///
+    /// # 参数
+    /// * `amount` - 本次读到的字节数
+    /// * `rate` - 读取当前限速（字节/秒），返回 `None` 或 `0` 表示不限速
+    ///
+    /// 采用“先透支后等待”的方式：只要桶里没有欠账就立即放行，
+    /// 这样单块数据比桶容量还大时也不会卡死。
+    pub async fn acquire(
+        &self,
+        amount: u64,
+        rate: impl Fn() -> Option<u64>,
+    ) {
+        // 不限速时连锁都不需要拿
+        if rate().filter(|rate| *rate > 0).is_none() {
+            return;
+        }
+
+        let mut bucket = self.bucket.lock().await;
+
+        loop {
+            let Some(rate) = rate().filter(|rate| *rate > 0) else {
+                bucket.reset();
+                return;
+            };
+
+            bucket.refill(rate);
+
+            if bucket.tokens >= 0.0 {
+                bucket.tokens -= amount as f64;
+                return;
+            }
+
+            let wait =
+                Duration::from_secs_f64(-bucket.tokens / rate as f64);
+            tokio::time::sleep(wait.min(MAX_WAIT_SLICE)).await;
+        }
+    }
+}
+
+impl Default for SpeedLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
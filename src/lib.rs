@@ -2,4 +2,5 @@ pub mod client;
 pub mod global_config;
 mod local_file;
 pub mod reactive;
-pub mod resource_file;
\ No newline at end of file
+pub mod resource_file;
+pub mod transfer_manager;
\ No newline at end of file
@@ -1,3 +1,4 @@
+pub mod enums;
 pub mod structs;
 pub mod traits;
-mod impl_traits;
+mod impl_traits;
\ No newline at end of file
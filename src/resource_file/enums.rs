@@ -0,0 +1,6 @@
+pub mod checksum_algorithm;
+pub mod chunk_strategy;
+pub mod conflict_policy;
+pub mod download_mode;
+pub mod download_outcome;
+pub mod remote_changed_policy;
\ No newline at end of file
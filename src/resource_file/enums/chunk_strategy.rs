@@ -0,0 +1,16 @@
+use crate::resource_file::structs::adaptive_chunking::AdaptiveChunking;
+use serde::{Deserialize, Serialize};
+
+/// 分片下载时如何决定分片大小和并发数
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum ChunkStrategy {
+    /// 固定 4M 分片，并发数取 `max_thread_count`，没有配置时按文件大小计算
+    #[default]
+    Fixed,
+    /// 按测得的吞吐量和延迟动态调整，见 [`AdaptiveChunking`]
+    ///
+    /// 配置了 `max_thread_count` 时，并发上限不超过它。
+    Adaptive(AdaptiveChunking),
+}
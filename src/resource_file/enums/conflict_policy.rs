@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// 本地目标文件已经存在时的处理方式
+///
+/// 目标文件旁边有分片记录时说明是上次没下载完的文件，总是续传，不按冲突处理。
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum ConflictPolicy {
+    /// 重新下载并覆盖本地文件
+    #[default]
+    Overwrite,
+    /// 保留本地文件，不发任何请求
+    Skip,
+    /// 保存为 `name (1).ext`，编号依次递增直到不冲突
+    Rename,
+    /// 远程文件的 `last_modified` 比本地文件新时才覆盖，
+    /// 远程没有修改时间时保留本地文件
+    KeepNewer,
+    /// 直接返回 `LocalFileExists` 错误，由使用者决定怎么处理
+    Fail,
+}
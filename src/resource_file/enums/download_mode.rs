@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// 下载方式
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum DownloadMode {
+    /// 根据主机策略和大文件阈值自动选择
+    #[default]
+    Auto,
+    /// 强制分片下载，忽略阈值和主机策略，文件大小未知时会失败
+    Chunked,
+    /// 强制整个文件一次性下载
+    NotChunked,
+}
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+/// 一次下载的结果，成功后写入 `ResourceFileProperty::outcome`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// 已下载到目标路径
+    Downloaded(PathBuf),
+    /// 目标路径已被占用，按 [`Rename`](super::conflict_policy::ConflictPolicy::Rename)
+    /// 下载到了这个新路径
+    Renamed(PathBuf),
+    /// 目标路径已存在，按冲突策略保留了本地文件
+    Skipped(PathBuf),
+}
+
+impl DownloadOutcome {
+    /// 本地文件的实际路径
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            DownloadOutcome::Downloaded(path)
+            | DownloadOutcome::Renamed(path)
+            | DownloadOutcome::Skipped(path) => path,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DownloadOutcome::Downloaded(_) => "downloaded",
+            DownloadOutcome::Renamed(_) => "renamed",
+            DownloadOutcome::Skipped(_) => "skipped",
+        }
+    }
+}
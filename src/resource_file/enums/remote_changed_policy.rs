@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// 续传时发现远程文件已经变化的处理方式
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum RemoteChangedPolicy {
+    /// 丢弃已下载的内容，从头下载新文件
+    #[default]
+    Restart,
+    /// 直接返回 `RemoteChanged` 错误，由使用者决定怎么处理
+    Fail,
+}
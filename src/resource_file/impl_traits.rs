@@ -1 +1,5 @@
 pub(super) mod impl_download;
+pub(super) mod impl_download_recursive;
+pub(super) mod impl_download_stream;
+pub(super) mod impl_open_reader;
+pub(super) mod impl_start_download;
\ No newline at end of file
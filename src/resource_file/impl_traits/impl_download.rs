@@ -1,6 +1,11 @@
+pub(crate) mod atomic_write;
 pub(crate) mod chunked_download;
+pub(crate) mod conflict;
 pub(crate) mod handle_download;
+pub(crate) mod integrity;
 pub(crate) mod not_chunked_download;
+pub(crate) mod preserve_metadata;
+pub(crate) mod retry;
 
 use crate::resource_file::impl_traits::impl_download::handle_download::{
     HandleDownloadArgs, handle_download,
@@ -73,10 +78,9 @@ impl Download for ResourcesFile {
         let handle_download_args = HandleDownloadArgs {
             resource_file_data: self.get_data(),
             save_absolute_path,
-            http_client: http_client.clone(),
-            global_config: self.get_global_config(),
+            http_client,
             inner_state: self.get_reactive_state(),
-            inner_config: self.get_reactive_config(),
+            download_config: self.get_download_config(),
         };
 
         let download_result = handle_download(handle_download_args).await;
@@ -91,8 +95,18 @@ impl Download for ResourcesFile {
 
         handle_unmounted().await?;
 
-        // 处理可能的失败结果
-        download_result?;
+        // 处理可能的失败结果，远程文件变化、校验失败和本地文件已存在单独返回，
+        // 方便使用者按类型处理
+        download_result.map_err(|e| match e.into_remote_changed() {
+            Ok(e) => DownloadError::RemoteChanged(e),
+            Err(e) => match e.into_integrity() {
+                Ok(e) => DownloadError::Integrity(e),
+                Err(e) => match e.into_local_file_exists() {
+                    Ok(path) => DownloadError::LocalFileExists(path),
+                    Err(e) => e.into(),
+                },
+            },
+        })?;
 
         Ok(Arc::new(self))
     }
@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+
+// 临时文件的后缀，文件名前面再加一个点，在大多数系统里默认隐藏
+const TEMP_EXTENSION: &str = "wdtmp";
+
+#[derive(Debug, Error)]
+pub enum FinalizeAtomicDownloadError {
+    #[error("临时文件[{0}] 落盘失败: {1}")]
+    Sync(PathBuf, std::io::Error),
+
+    #[error("临时文件[{0}] 替换目标文件失败: {1}")]
+    Rename(PathBuf, std::io::Error),
+}
+
+/// 目标文件对应的临时文件路径：同一目录下的 `.<文件名>.wdtmp`
+///
+/// 放在同一目录是为了保证最后的重命名在同一个文件系统内完成，不会退化成复制。
+pub fn temp_path(save_absolute_path: &Path) -> PathBuf {
+    let file_name = save_absolute_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    save_absolute_path
+        .with_file_name(format!(".{}.{}", file_name, TEMP_EXTENSION))
+}
+
+/// 临时文件下载并校验完成后调用：落盘，再重命名覆盖目标文件
+pub async fn finalize_atomic_download(
+    temp_path: &Path,
+    save_absolute_path: &Path,
+) -> Result<(), FinalizeAtomicDownloadError> {
+    let sync_error =
+        |e| FinalizeAtomicDownloadError::Sync(temp_path.to_path_buf(), e);
+
+    // 各下载任务只 flush 到了系统缓存，重命名之前确保数据真正写到磁盘
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .open(temp_path)
+        .await
+        .map_err(sync_error)?;
+    file.sync_all().await.map_err(sync_error)?;
+    drop(file);
+
+    fs::rename(temp_path, save_absolute_path).await.map_err(|e| {
+        FinalizeAtomicDownloadError::Rename(temp_path.to_path_buf(), e)
+    })?;
+
+    Ok(())
+}
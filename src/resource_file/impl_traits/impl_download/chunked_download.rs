@@ -1,33 +1,35 @@
-pub mod black_list;
+pub(crate) mod adaptive;
+pub(crate) mod chunk_writer;
 pub(crate) mod file;
 pub(crate) mod http_stream;
+pub(crate) mod journal;
+pub(crate) mod range_probe;
 pub(crate) mod task;
 
-use crate::global_config::global_config::GlobalConfig;
-use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
+use crate::resource_file::enums::chunk_strategy::ChunkStrategy;
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
 use crate::resource_file::structs::resource_file_data::ResourceFileData;
-use crate::resource_file::impl_traits::impl_download::chunked_download::file::{computed_semaphore_count, get_local_file_size, open_file, GetLocalFileSizeError, OpenFileError};
+use crate::resource_file::traits::download::TDownloadConfig;
+use crate::resource_file::impl_traits::impl_download::chunked_download::adaptive::build_adaptive_download_tasks;
+use crate::resource_file::impl_traits::impl_download::chunked_download::file::{computed_semaphore_count, open_file, OpenFileError};
+use crate::resource_file::impl_traits::impl_download::chunked_download::journal::{journal_path, load_journal, remove_journal, ChunkJournal, ChunkJournalError, ChunkJournalFile, LoadedJournal};
+use crate::resource_file::impl_traits::impl_download::chunked_download::range_probe::{probe_range_support, ProbeRangeError, ProbeRangeSupportArgs};
 use crate::resource_file::impl_traits::impl_download::chunked_download::task::{build_download_tasks, join_all_and_handle_result, BuildDownloadTasksError, DownloadTaskArgs, JoinAllAndHandleResultError};
 use reqwest::Client;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
-use tokio::io::AsyncWriteExt;
 use tokio::sync::Semaphore;
 
 const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
 
-pub enum LocalFileDownloadState {
-    /// 已完成
-    Downloaded,
-    /// 未完成
-    Incomplete(u64),
-}
-
 #[derive(Debug, Error)]
 pub enum SetInitialProgressError {
-    #[error("更新 download_bytes 出错: {0}")]
+    #[error("更新下载进度出错: {0}")]
     UpdateFieldError(String),
 }
 
@@ -35,14 +37,9 @@ pub fn set_initial_progress(
     inner_state: &ResourceFileProperty,
     start: u64,
 ) -> Result<(), SetInitialProgressError> {
-    inner_state
-        .download_bytes
-        .update_field(|download_bytes| {
-            *download_bytes = start as usize;
-        })
-        .map_err(|e| {
-            SetInitialProgressError::UpdateFieldError(e.to_string())
-        })?;
+    inner_state.reporter.reset(start).map_err(|e| {
+        SetInitialProgressError::UpdateFieldError(e.to_string())
+    })?;
 
     Ok(())
 }
@@ -51,9 +48,8 @@ pub struct ChunkedDownloadArgs {
     pub(crate) resource_file_data: Arc<ResourceFileData>,
     pub(crate) http_client: Client,
     pub(crate) save_absolute_path: PathBuf,
-    pub(crate) global_config: GlobalConfig,
     pub(crate) inner_state: ResourceFileProperty,
-    pub(crate) inner_config: ResourceConfig,
+    pub(crate) download_config: TDownloadConfig,
 }
 
 #[derive(Debug, Error)]
@@ -61,9 +57,6 @@ pub enum ChunkedDownloadError {
     #[error("文件大小未知，无法分片下载 {0}")]
     UnknownFileSize(String),
 
-    #[error(transparent)]
-    GetLocalFileSizeError(#[from] GetLocalFileSizeError),
-
     #[error(transparent)]
     SetInitialProgressError(#[from] SetInitialProgressError),
 
@@ -76,8 +69,88 @@ pub enum ChunkedDownloadError {
     #[error(transparent)]
     OpenFileError(#[from] OpenFileError),
 
-    #[error("文件 flush 出错: {0}")]
-    FlushError(String),
+    #[error(transparent)]
+    ChunkJournalError(#[from] ChunkJournalError),
+
+    #[error("删除未完成的文件失败: {0}")]
+    RemoveFileError(std::io::Error),
+
+    #[error(transparent)]
+    RemoteChanged(RemoteChangedError),
+
+    #[error("服务器不支持 Range 请求，无法分片下载 {0}")]
+    RangeNotSupported(String),
+
+    #[error(transparent)]
+    ProbeRangeError(#[from] ProbeRangeError),
+}
+
+/// 删除本地文件，文件不存在时什么也不做
+pub async fn remove_local_file(
+    save_absolute_path: &Path,
+) -> Result<(), ChunkedDownloadError> {
+    match tokio::fs::remove_file(save_absolute_path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(ChunkedDownloadError::RemoveFileError(e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 准备分片记录
+///
+/// 分片是并发写入的，文件长度到达远程大小时前面的分片可能还没写完，
+/// 所以是否完成、还差哪些区间都以分片记录为准，不看本地文件长度。
+/// 没有记录的文件无法判断哪些字节有效，直接删掉重新下载：
+/// 需要保留的本地文件已经在冲突处理时跳过，走不到这里，
+/// 剩下的只有原子下载留下的临时文件和没来得及删除的旧文件。
+///
+/// 记录属于旧版本的远程文件时，处理方式为 [`RemoteChangedPolicy::Fail`]
+/// 就返回 `RemoteChanged`，记录和已下载的文件都保留给使用者处理。
+async fn prepare_journal(
+    save_absolute_path: &Path,
+    file_url: &str,
+    total_size: u64,
+    validator: RemoteValidator,
+    remote_changed_policy: RemoteChangedPolicy,
+) -> Result<ChunkJournal, ChunkedDownloadError> {
+    let has_journal =
+        tokio::fs::metadata(journal_path(save_absolute_path)).await.is_ok();
+
+    if has_journal {
+        let journal =
+            load_journal(save_absolute_path, total_size, &validator)
+                .await?;
+
+        // 有记录但文件被删掉了，记录已经没有意义
+        let has_file = tokio::fs::metadata(save_absolute_path).await.is_ok();
+
+        let fail_on_change =
+            remote_changed_policy == RemoteChangedPolicy::Fail;
+
+        return match journal {
+            LoadedJournal::Matched(journal) if has_file => Ok(journal),
+            LoadedJournal::Changed(journal)
+                if has_file && fail_on_change =>
+            {
+                let error = RemoteChangedError {
+                    url: file_url.to_string(),
+                    expected: journal.validator,
+                    current: validator,
+                };
+                Err(ChunkedDownloadError::RemoteChanged(error))
+            }
+            // 记录属于旧版本的远程文件，本地文件长度再完整也不能用
+            _ => {
+                remove_journal(save_absolute_path).await?;
+                remove_local_file(save_absolute_path).await?;
+                Ok(ChunkJournal::new(total_size, validator))
+            }
+        };
+    }
+
+    remove_local_file(save_absolute_path).await?;
+    Ok(ChunkJournal::new(total_size, validator))
 }
 
 pub async fn chunked_download(
@@ -89,24 +162,58 @@ pub async fn chunked_download(
         )
     })?;
 
-    // 本地文件已下载的大小
-    let local_file_downloaded_size =
-        get_local_file_size(&args.save_absolute_path, total_size)
-            .await
-            .map_err(|e| ChunkedDownloadError::GetLocalFileSizeError(e))?;
+    let journal = prepare_journal(
+        &args.save_absolute_path,
+        &args.resource_file_data.absolute_path,
+        total_size,
+        RemoteValidator::from_data(&args.resource_file_data),
+        args.download_config.remote_changed_policy(),
+    )
+    .await?;
 
-    let start = match local_file_downloaded_size {
-        LocalFileDownloadState::Downloaded => return Ok(()),
-        LocalFileDownloadState::Incomplete(size) => size,
-    };
+    // 先确认服务器支持 Range，探测请求失败时按重试策略重试，
+    // 重试不了的错误直接返回，不当成不支持 Range
+    let range_supported = probe_range_support(ProbeRangeSupportArgs {
+        http_client: &args.http_client,
+        file_url: &args.resource_file_data.absolute_path,
+        total_size,
+        inner_state: &args.inner_state,
+        download_config: &args.download_config,
+    })
+    .await?;
+
+    if !range_supported {
+        return Err(ChunkedDownloadError::RangeNotSupported(
+            args.resource_file_data.absolute_path.clone(),
+        ));
+    }
 
-    set_initial_progress(&args.inner_state, start)?;
+    set_initial_progress(&args.inner_state, journal.completed_bytes())?;
 
-    // 打开文件（续传时用 append + write）
-    let file = open_file(&args.save_absolute_path).await?;
+    let ranges = journal.missing_ranges();
 
-    let thread_count =
-        computed_semaphore_count(args.resource_file_data.size); // 计算线程数
+    // 续传时沿用第一次开始下载时记录的版本
+    let validator = Arc::new(journal.validator.clone());
+
+    // 先写一次记录，开始下载后中断也能找到它。
+    // 必须在预分配之前写好，否则中断后留下的文件长度完整却没有记录，
+    // 下次会被当成已经下载完成
+    let journal =
+        Arc::new(ChunkJournalFile::new(&args.save_absolute_path, journal));
+    journal.save().await?;
+
+    // 打开文件并预分配空间，各分片共用这个句柄按偏移量写入
+    let file =
+        Arc::new(open_file(&args.save_absolute_path, total_size).await?);
+
+    let max_thread_count = args.download_config.max_thread_count();
+    let chunk_strategy = args.download_config.chunk_strategy();
+
+    // 优先使用配置的线程数，没有配置时按文件大小计算
+    let thread_count = match max_thread_count {
+        Some(count) => count.max(1) as usize,
+        None => computed_semaphore_count(args.resource_file_data.size),
+    };
 
     let semaphore = Arc::new(Semaphore::new(thread_count));
 
@@ -114,26 +221,47 @@ pub async fn chunked_download(
         http_client: &args.http_client,
         file_url: &args.resource_file_data.absolute_path,
         semaphore,
-        start,
+        ranges,
         total_size,
         file,
+        journal,
+        validator,
         inner_state: &args.inner_state,
-        global_config: args.global_config,
-        inner_config: args.inner_config,
+        download_config: args.download_config,
     };
 
-    let (tasks, mut args) = build_download_tasks(download_task_args)
-        .await
-        .map_err(|e| ChunkedDownloadError::BuildDownloadTasksError(e))?;
+    let tasks = match chunk_strategy {
+        ChunkStrategy::Fixed => build_download_tasks(download_task_args)?,
+        ChunkStrategy::Adaptive(settings) => {
+            let mut settings = settings.normalized();
 
-    join_all_and_handle_result(tasks).await?;
+            // 配置了线程数时，并发上限不超过它
+            if let Some(count) = max_thread_count {
+                settings.max_connections =
+                    settings.max_connections.min(count.max(1));
+                settings.min_connections =
+                    settings.min_connections.min(settings.max_connections);
+            }
+
+            build_adaptive_download_tasks(download_task_args, settings)?
+        }
+    };
+
+    join_all_and_handle_result(tasks).await.map_err(|e| match e {
+        JoinAllAndHandleResultError::RemoteChanged(e) => {
+            ChunkedDownloadError::RemoteChanged(e)
+        }
+        // 探测时支持，分片请求却没有返回对应的区间
+        JoinAllAndHandleResultError::RangeNotSupported(_) => {
+            ChunkedDownloadError::RangeNotSupported(
+                args.resource_file_data.absolute_path.clone(),
+            )
+        }
+        e => e.into(),
+    })?;
 
-    // 关闭文件句柄访问
-    args.file
-        .flush()
-        .await
-        .map_err(|e| e.to_string())
-        .map_err(|e| ChunkedDownloadError::FlushError(e))?;
+    // 全部分片都已写入，记录不再需要
+    remove_journal(&args.save_absolute_path).await?;
 
     Ok(())
 }
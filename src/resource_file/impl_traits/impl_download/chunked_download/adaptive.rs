@@ -0,0 +1,266 @@
+use crate::resource_file::impl_traits::impl_download::chunked_download::http_stream::RangeStats;
+use crate::resource_file::impl_traits::impl_download::chunked_download::task::{
+    BuildDownloadTasksError, DownloadTaskArgs, DownloadTaskContext,
+    DownloadTasks,
+};
+use crate::resource_file::structs::adaptive_chunking::AdaptiveChunking;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+// 建立连接的延迟最多占请求时间的比例，分片太小时大部分时间都花在等待响应上
+const MAX_LATENCY_RATIO: f64 = 0.1;
+
+// 总吞吐量变化超过这个比例才算有效，避免被网络抖动带着来回调整
+const THROUGHPUT_TOLERANCE: f64 = 0.1;
+
+// 新测量值在平滑结果里的权重
+const SMOOTHING: f64 = 0.5;
+
+/// 还没有分配出去的区间，按文件顺序切出分片
+#[derive(Debug)]
+struct RangeQueue {
+    ranges: Mutex<VecDeque<(u64, u64)>>, // 包含两端
+}
+
+impl RangeQueue {
+    fn new(ranges: &[(u64, u64)]) -> Self {
+        Self { ranges: Mutex::new(ranges.iter().copied().collect()) }
+    }
+
+    /// 从第一个区间的开头切出最多 `max_len` 字节
+    fn next_piece(&self, max_len: u64) -> Option<(u64, u64)> {
+        let mut ranges =
+            self.ranges.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (start, end) = ranges.pop_front()?;
+        let piece_end = end.min(start.saturating_add(max_len.max(1) - 1));
+
+        if piece_end < end {
+            ranges.push_front((piece_end + 1, end));
+        }
+
+        Some((start, piece_end))
+    }
+}
+
+/// 一个窗口结束时对并发数的调整
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Adjustment {
+    Hold,
+    Grow,
+    Shrink,
+}
+
+#[derive(Debug)]
+struct ControllerState {
+    connections: u32,
+    chunk_size: u64,
+    throughput: Option<f64>, // 单个连接的传输速度（字节/秒），不含等待响应的时间
+    latency: Option<f64>,    // 等待响应的时间（秒）
+    window_started: Instant,
+    window_bytes: u64,
+    window_pieces: u32,
+    last_rate: Option<f64>, // 上一个窗口的总吞吐量
+    last_adjustment: Adjustment, // 上一个窗口结束时实际做出的调整
+}
+
+/// # 自适应控制器
+///
+/// 每个分片完成后汇报测量结果：
+/// - 分片大小按单连接速度换算成目标请求时长，同时保证延迟占比不超过
+///   [`MAX_LATENCY_RATIO`]，再限制在上下限之间
+/// - 每个连接平均完成一个分片算一个窗口，窗口结束时比较总吞吐量：
+///   加连接后有明显提升就继续加，没有提升就退回，明显下降时减少
+///
+/// 并发数通过 `permits` 控制：增加时放入一个许可，
+/// 减少时由刚完成分片的任务把自己的许可丢弃。
+#[derive(Debug)]
+struct AdaptiveController {
+    settings: AdaptiveChunking,
+    permits: Arc<Semaphore>,
+    state: Mutex<ControllerState>,
+}
+
+impl AdaptiveController {
+    fn new(settings: AdaptiveChunking, permits: Arc<Semaphore>) -> Self {
+        Self {
+            settings,
+            permits,
+            state: Mutex::new(ControllerState {
+                connections: settings.min_connections,
+                chunk_size: settings.min_chunk_size,
+                throughput: None,
+                latency: None,
+                window_started: Instant::now(),
+                window_bytes: 0,
+                window_pieces: 0,
+                last_rate: None,
+                last_adjustment: Adjustment::Hold,
+            }),
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ControllerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn chunk_size(&self) -> u64 {
+        self.lock_state().chunk_size
+    }
+
+    /// 记录一个完成的分片，返回 `true` 表示要减少一个连接
+    fn record(&self, stats: RangeStats) -> bool {
+        let mut state = self.lock_state();
+
+        self.update_chunk_size(&mut state, stats);
+
+        state.window_bytes += stats.bytes;
+        state.window_pieces += 1;
+        if state.window_pieces < state.connections {
+            return false;
+        }
+
+        let window = state.window_started.elapsed().as_secs_f64();
+        let rate = state.window_bytes as f64 / window.max(f64::EPSILON);
+        state.window_started = Instant::now();
+        state.window_bytes = 0;
+        state.window_pieces = 0;
+
+        let adjustment = match (state.last_rate, state.last_adjustment) {
+            (None, _) => Adjustment::Grow,
+            // 刚减少过连接，这个窗口的吞吐量只作为之后比较的基准
+            (Some(_), Adjustment::Shrink) => Adjustment::Hold,
+            (Some(last), _)
+                if rate > last * (1.0 + THROUGHPUT_TOLERANCE) =>
+            {
+                Adjustment::Grow
+            }
+            // 加了连接却没有明显提升，说明已经到瓶颈
+            (Some(_), Adjustment::Grow) => Adjustment::Shrink,
+            (Some(last), _)
+                if rate < last * (1.0 - THROUGHPUT_TOLERANCE) =>
+            {
+                Adjustment::Shrink
+            }
+            (Some(_), _) => Adjustment::Hold,
+        };
+        state.last_rate = Some(rate);
+
+        match adjustment {
+            Adjustment::Grow
+                if state.connections < self.settings.max_connections =>
+            {
+                state.connections += 1;
+                state.last_adjustment = Adjustment::Grow;
+                self.permits.add_permits(1);
+                false
+            }
+            Adjustment::Shrink
+                if state.connections > self.settings.min_connections =>
+            {
+                state.connections -= 1;
+                state.last_adjustment = Adjustment::Shrink;
+                true
+            }
+            _ => {
+                state.last_adjustment = Adjustment::Hold;
+                false
+            }
+        }
+    }
+
+    fn update_chunk_size(
+        &self,
+        state: &mut ControllerState,
+        stats: RangeStats,
+    ) {
+        let latency = stats.latency.as_secs_f64();
+        let transfer = stats.elapsed.saturating_sub(stats.latency);
+        if stats.bytes == 0 || transfer.is_zero() {
+            return;
+        }
+
+        let throughput = stats.bytes as f64 / transfer.as_secs_f64();
+        let throughput = smooth(state.throughput, throughput);
+        let latency = smooth(state.latency, latency);
+        state.throughput = Some(throughput);
+        state.latency = Some(latency);
+
+        let target =
+            Duration::from_millis(self.settings.target_request_ms)
+                .as_secs_f64()
+                .max(latency / MAX_LATENCY_RATIO);
+
+        state.chunk_size = ((throughput * target) as u64).clamp(
+            self.settings.min_chunk_size,
+            self.settings.max_chunk_size,
+        );
+    }
+}
+
+fn smooth(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(previous) => {
+            SMOOTHING * sample + (1.0 - SMOOTHING) * previous
+        }
+        None => sample,
+    }
+}
+
+/// 构建自适应分片的下载任务
+///
+/// 按并发上限启动同样数量的任务，每个任务拿到许可后从队列里切下一个分片，
+/// 分片大小和许可数量都由控制器随测量结果调整，队列空了任务就结束。
+/// `args.semaphore` 不使用，分片数量事先不确定，进度里的分片随下载逐个追加。
+pub fn build_adaptive_download_tasks(
+    args: DownloadTaskArgs<'_>,
+    settings: AdaptiveChunking,
+) -> Result<DownloadTasks, BuildDownloadTasksError> {
+    let mut tasks = JoinSet::new();
+
+    let permits =
+        Arc::new(Semaphore::new(settings.min_connections as usize));
+    let controller = Arc::new(AdaptiveController::new(settings, permits));
+    let queue = Arc::new(RangeQueue::new(&args.ranges));
+
+    args.inner_state.reporter.set_chunks(&[])?;
+
+    for _ in 0..settings.max_connections {
+        let mut context = DownloadTaskContext::new(&args, 0, 0, 0);
+
+        let controller = Arc::clone(&controller);
+        let queue = Arc::clone(&queue);
+        let journal = Arc::clone(&args.journal);
+
+        tasks.spawn(async move {
+            loop {
+                let permit = Arc::clone(&controller.permits)
+                    .acquire_owned()
+                    .await?;
+
+                let Some((start, end)) =
+                    queue.next_piece(controller.chunk_size())
+                else {
+                    return Ok(());
+                };
+
+                context.start = start;
+                context.end = end;
+                context.chunk_index =
+                    context.inner_state.reporter.add_chunk(start, end)?;
+
+                let stats = context.download_piece(&journal).await?;
+
+                if controller.record(stats) {
+                    permit.forget();
+                }
+            }
+        });
+    }
+
+    Ok(tasks)
+}
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::io;
+use std::sync::Arc;
+
+// 缓冲区攒够这么多数据才写一次文件，网络包通常只有几十 KB
+const WRITE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// 在 `offset` 处写入全部数据，不移动也不依赖文件的读写位置
+#[cfg(not(windows))]
+fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    file.write_all_at(buf, offset)
+}
+
+/// 在 `offset` 处写入全部数据
+///
+/// Windows 的 `seek_write` 会移动文件的读写位置，但所有分片都按偏移量写入，
+/// 没有人依赖这个位置。
+#[cfg(windows)]
+fn write_all_at(
+    file: &File,
+    mut buf: &[u8],
+    mut offset: u64,
+) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        match file.seek_write(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                buf = &buf[written..];
+                offset += written as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// # 分片写入器
+///
+/// 所有分片共用同一个文件句柄，各自按偏移量写入（`pwrite`），
+/// 不需要为每个分片复制文件描述符，也不需要每写一块就 `seek` 一次。
+///
+/// 收到的数据先放进缓冲区，攒够 [`WRITE_BUFFER_SIZE`] 再在阻塞线程里写入，
+/// 分片下载完成后必须调用 [`sync_data`](Self::sync_data)
+/// 写入剩下的数据并落盘，之后才能在分片记录里标记完成。
+#[derive(Debug)]
+pub(crate) struct ChunkWriter {
+    file: Arc<File>,
+    offset: u64, // 缓冲区第一个字节在文件里的位置
+    buffer: Vec<u8>,
+}
+
+impl ChunkWriter {
+    /// 从 `offset` 开始写入
+    pub(crate) fn new(file: Arc<File>, offset: u64) -> Self {
+        Self {
+            file,
+            offset,
+            buffer: Vec::with_capacity(WRITE_BUFFER_SIZE),
+        }
+    }
+
+    /// 追加数据，缓冲区满了才真正写入文件
+    pub(crate) async fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.buffer.extend_from_slice(data);
+
+        if self.buffer.len() >= WRITE_BUFFER_SIZE {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// 把缓冲区里的数据全部写入文件
+    pub(crate) async fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let file = Arc::clone(&self.file);
+        let offset = self.offset;
+        let buffer = std::mem::take(&mut self.buffer);
+
+        // 写完把缓冲区还回来，下一次不用重新分配
+        let mut buffer = tokio::task::spawn_blocking(move || {
+            write_all_at(&file, &buffer, offset).map(|_| buffer)
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        self.offset += buffer.len() as u64;
+        buffer.clear();
+        self.buffer = buffer;
+
+        Ok(())
+    }
+
+    /// 写入缓冲区里的数据，并等数据真正落到磁盘上
+    ///
+    /// `sync_data` 作用于整个文件，其它分片已经写入的数据也会一起落盘。
+    pub(crate) async fn sync_data(&mut self) -> io::Result<()> {
+        self.flush().await?;
+
+        let file = Arc::clone(&self.file);
+        tokio::task::spawn_blocking(move || file.sync_data())
+            .await
+            .map_err(io::Error::other)?
+    }
+}
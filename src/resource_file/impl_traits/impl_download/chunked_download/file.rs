@@ -1,8 +1,6 @@
-use crate::resource_file::impl_traits::impl_download::chunked_download::LocalFileDownloadState;
 use std::path::PathBuf;
 use thiserror::Error;
 use tokio::fs;
-use tokio::fs::File;
 
 const DEFAULT_MAX_CONCURRENT_CHUNKS: u64 = 4; // 最大并发分片数
 
@@ -34,102 +32,70 @@ pub fn computed_semaphore_count(size: Option<u64>) -> usize {
 pub enum OpenFileError {
     #[error("打开文件失败: {0}")]
     IoError(#[from] std::io::Error),
-}
-
-pub async fn open_file(
-    save_absolute_path: &PathBuf,
-) -> Result<File, OpenFileError> {
-    // 打开文件（续传时用 append + write）
-    let file = fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open(save_absolute_path)
-        .await?;
 
-    Ok(file)
+    #[error("预分配磁盘空间失败: {0}")]
+    Preallocate(std::io::Error),
 }
 
-#[derive(Debug, Error)]
-pub enum CloneFileHandleError {
-    #[error("文件句柄克隆失败: {0}")]
-    IoError(#[from] std::io::Error),
-}
+/// 申请磁盘空间，文件系统不支持时退回 `set_len` 的结果（稀疏文件）
+#[cfg(target_os = "linux")]
+fn allocate(file: &std::fs::File, total_size: u64) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
 
-/// 克隆一个文件句柄，用于并发访问同一个文件。
-///
-/// # 概述
-/// 此函数会尝试创建一个新的 [`tokio::fs::File`] 句柄，
-/// 它与原始句柄指向同一个底层文件（操作系统层面的文件描述符被复制）。
-/// 克隆后的句柄可以在不同的异步任务中独立使用，
-/// 特别适合 **分片下载** 这样的场景：
-/// 多个任务需要同时写入同一个文件的不同区域。
-///
-/// # 为什么要克隆而不是共享？
-/// - [`tokio::fs::File`] **不是 `Sync`**，不能安全地在多个异步任务中共享 `&mut File`。
-/// - 如果使用 `Arc<Mutex<File>>`，虽然可以共享，但所有写操作会被串行化，
-///   导致并发性能大幅下降。
-/// - 通过克隆文件句柄，每个任务都能独立 `seek` 到自己的偏移量并写入数据，
-///   避免了锁竞争，同时保持高效的并发写入。
-///
-/// # 错误
-/// 如果底层操作系统在复制文件描述符时失败，
-/// 函数会返回 `Err(String)`，其中包含原始 I/O 错误的描述。
-///
-/// # 示例
-/// ```no_run
-/// use tokio::fs::File;
-///
-/// # async fn example() -> Result<(), String> {
-/// let file = File::create("example.txt").await.map_err(|e| e.to_string())?;
-///
-/// // 克隆文件句柄，供另一个任务独立写入
-/// let cloned = clone_file_handle(&file).await?;
-///
-/// // 此时 `file` 和 `cloned` 都指向同一个物理文件
-/// # Ok(())
-/// # }
-/// ```
-///
-/// # 相关链接
-/// - [`tokio::fs::File::try_clone`]
-/// - [`tokio::io::AsyncSeekExt`] 用于移动文件指针
-/// - [`tokio::io::AsyncWriteExt`] 用于写入数据
-pub async fn clone_file_handle(
-    file: &File,
-) -> Result<File, CloneFileHandleError> {
-    let file = file.try_clone().await?;
+    let Ok(len) = libc::off_t::try_from(total_size) else {
+        return Ok(());
+    };
 
-    Ok(file)
+    // 模式 0 只分配空间，不改变已经写入的内容；
+    // 调用期间 file 一直被借用，文件描述符不会被关闭
+    if unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, len) } == 0 {
+        return Ok(());
+    }
+
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::EOPNOTSUPP) | Some(libc::ENOSYS) => Ok(()),
+        _ => Err(error),
+    }
 }
 
-#[derive(Debug, Error)]
-pub enum GetLocalFileSizeError {
-    #[error("文件句柄克隆失败: {0}")]
-    IoError(#[from] std::io::Error),
+/// Windows 上 `set_len` 已经会分配空间，其它系统只得到稀疏文件
+#[cfg(not(target_os = "linux"))]
+fn allocate(
+    _file: &std::fs::File,
+    _total_size: u64,
+) -> std::io::Result<()> {
+    Ok(())
 }
 
-pub async fn get_local_file_size(
+/// 打开文件并把长度预分配为 `total_size`
+///
+/// 磁盘空间不够时在这里就会失败，而不是下载到一半才发现，
+/// 一次分配完也能减少并发写入不同位置造成的碎片。
+/// 续传时已经写入的内容不受影响。
+///
+/// 返回的是标准库的 [`std::fs::File`]，各分片通过
+/// [`ChunkWriter`](super::chunk_writer::ChunkWriter) 按偏移量共用它。
+pub async fn open_file(
     save_absolute_path: &PathBuf,
     total_size: u64,
-) -> Result<LocalFileDownloadState, GetLocalFileSizeError> {
-    // 检查是否已有部分文件（断点续传）
-    let mut local_size: u64 = 0;
-
-    let file_meta = tokio::fs::metadata(save_absolute_path).await;
-
-    if let Ok(meta) = file_meta {
-        local_size = meta.len();
-
-        if local_size > total_size {
-            // 本地文件比远程大，说明出错，删掉重新下
-            tokio::fs::remove_file(save_absolute_path).await?;
-
-            local_size = 0;
-        } else if local_size == total_size {
-            // 已经下载完成
-            return Ok(LocalFileDownloadState::Downloaded);
-        }
-    }
+) -> Result<std::fs::File, OpenFileError> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(save_absolute_path)
+        .await?
+        .into_std()
+        .await;
 
-    Ok(LocalFileDownloadState::Incomplete(local_size))
+    tokio::task::spawn_blocking(move || {
+        file.set_len(total_size)?;
+        allocate(&file, total_size)?;
+        Ok(file)
+    })
+    .await
+    .map_err(std::io::Error::other)
+    .and_then(|result| result)
+    .map_err(OpenFileError::Preallocate)
 }
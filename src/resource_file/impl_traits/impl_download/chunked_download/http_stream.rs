@@ -1,47 +1,69 @@
-use crate::global_config::global_config::GlobalConfig;
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, idle_timeout, response_timeout,
+};
 use crate::reactive::reactive::ReactivePropertyError;
-use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::impl_traits::impl_download::chunked_download::chunk_writer::ChunkWriter;
+use crate::resource_file::impl_traits::impl_download::chunked_download::range_probe::{
+    RangeResponseError, check_range_response,
+};
+use crate::resource_file::impl_traits::impl_download::retry::{
+    Retryable, backoff_delay, is_retryable_reqwest_error,
+    is_retryable_status,
+};
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
+use crate::resource_file::structs::transfer_progress::TransferState;
+use crate::resource_file::traits::download::TDownloadConfig;
 use bytes::Bytes;
-use futures_util::StreamExt;
-use reqwest::header::RANGE;
-use reqwest::{Client, Response};
-use std::io::SeekFrom;
+use reqwest::header::{ACCEPT_ENCODING, IF_RANGE, RANGE};
+use reqwest::{Client, Response, StatusCode};
+use std::fs::File;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
-use tokio::fs::File;
-use tokio::io;
-use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::time::Instant;
 
 #[derive(Debug, Error)]
 pub enum FetchRangeError {
     #[error("HTTP 请求失败: {0}")]
     Http(#[from] reqwest::Error),
+
+    #[error("请求超时: {0}")]
+    Timeout(#[from] RequestTimeoutError),
 }
 
 pub struct FetchRangeArgs<'a> {
     pub(crate) http_client: &'a Client,
     pub range_header_str: &'a str,
     pub file_url: &'a str,
+    pub if_range: Option<String>, // 文件变化时服务器返回 200 而不是 206
+    pub timeout_secs: Option<u64>,
 }
 
 pub async fn fetch_range_method<'a>(
     args: FetchRangeArgs<'a>,
 ) -> Result<Response, FetchRangeError> {
-    let result = args
+    let mut request = args
         .http_client
         .get(args.file_url)
         .header(RANGE, args.range_header_str)
-        .send()
-        .await?;
+        .header(ACCEPT_ENCODING, "identity"); // 区间按原始字节计算，不能被压缩
+
+    if let Some(if_range) = args.if_range {
+        request = request.header(IF_RANGE, if_range);
+    }
+
+    let result =
+        response_timeout(args.timeout_secs, request.send()).await??;
 
     Ok(result)
 }
 
 #[derive(Debug, Error)]
 pub enum HandleBytesStreamError {
-    #[error("文件Seek写入失败: {0}")]
-    Seek(io::Error),
-
     #[error("文件写入失败: {0}")]
     Write(io::Error),
 
@@ -49,33 +71,25 @@ pub enum HandleBytesStreamError {
     UpdateBytes(#[from] ReactivePropertyError),
 }
 
-pub struct HandleBytesStreamArgs<'a> {
+pub(crate) struct HandleBytesStreamArgs<'a> {
     pub chunk: Bytes,
-    pub current_file_seek_start: u64,
-    pub file: &'a mut File,
+    pub writer: &'a mut ChunkWriter,
     pub inner_state: ResourceFileProperty,
+    pub chunk_index: usize, // 分片序号，用来更新分片进度
 }
 
 async fn handle_bytes_stream<'a>(
     args: HandleBytesStreamArgs<'a>,
 ) -> Result<(), HandleBytesStreamError> {
-    args.file
-        .seek(SeekFrom::Start(args.current_file_seek_start))
-        .await
-        .map_err(|e| HandleBytesStreamError::Seek(e))?;
-
-    args.file
-        .write_all(&args.chunk)
+    args.writer
+        .write(&args.chunk)
         .await
-        .map_err(|e| HandleBytesStreamError::Write(e))?;
-
-    // 读取一次，避免报未使用错误
-    let _ = args.current_file_seek_start;
+        .map_err(HandleBytesStreamError::Write)?;
 
     // 更新大小
-    args.inner_state.download_bytes.update_field(|download_bytes| {
-        *download_bytes += args.chunk.len()
-    })?;
+    args.inner_state
+        .reporter
+        .add(Some(args.chunk_index), args.chunk.len() as u64)?;
 
     Ok(())
 }
@@ -85,78 +99,226 @@ pub enum DownloadRangeFileError {
     #[error("获取分片失败: {0}")]
     Fetch(#[from] FetchRangeError),
 
+    #[error("服务器返回异常状态码: {0}")]
+    UnexpectedStatus(u16),
+
     #[error("下载流出错: {0}")]
     Stream(#[from] reqwest::Error),
 
+    #[error("读取分片超时: {0}")]
+    Timeout(#[from] RequestTimeoutError),
+
     #[error("处理分片失败: {0}")]
     Handle(#[from] HandleBytesStreamError),
+
+    #[error(transparent)]
+    RemoteChanged(#[from] RemoteChangedError),
+
+    #[error(transparent)]
+    RangeNotSupported(#[from] RangeResponseError),
+
+    #[error(
+        "分片数据不完整，应下载到 {expected} 字节，实际只到 {actual} 字节"
+    )]
+    Incomplete { expected: u64, actual: u64 },
+
+    #[error("更新下载进度失败: {0}")]
+    UpdateProgress(#[from] ReactivePropertyError),
+}
+
+impl Retryable for DownloadRangeFileError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            DownloadRangeFileError::Fetch(FetchRangeError::Http(e))
+            | DownloadRangeFileError::Stream(e) => {
+                is_retryable_reqwest_error(e)
+            }
+            DownloadRangeFileError::Fetch(FetchRangeError::Timeout(_))
+            | DownloadRangeFileError::Timeout(_)
+            | DownloadRangeFileError::Incomplete { .. } => true,
+            DownloadRangeFileError::UnexpectedStatus(status) => {
+                StatusCode::from_u16(*status)
+                    .map(is_retryable_status)
+                    .unwrap_or(false)
+            }
+            DownloadRangeFileError::Handle(_)
+            | DownloadRangeFileError::RemoteChanged(_)
+            | DownloadRangeFileError::RangeNotSupported(_)
+            | DownloadRangeFileError::UpdateProgress(_) => false,
+        }
+    }
 }
 
 pub struct DownloadRangeFileArgs<'a> {
     pub(crate) http_client: &'a Client,
-    pub range_header_str: &'a str,
     pub file_url: &'a str,
-    pub file: &'a mut File,
+    pub file: &'a Arc<File>,
     pub start: u64,
+    pub end: u64, // 分片最后一个字节的位置（包含）
+    pub total_size: u64,
+    pub validator: &'a RemoteValidator,
     pub inner_state: ResourceFileProperty,
-    #[allow(dead_code)]
-    pub global_config: GlobalConfig,
-    #[allow(dead_code)]
-    pub inner_config: ResourceConfig,
+    pub download_config: TDownloadConfig,
+    pub chunk_index: usize, // 分片序号，用来更新分片进度
 }
 
-pub async fn download_range_file<'a>(
-    args: DownloadRangeFileArgs<'a>,
-) -> Result<(), DownloadRangeFileError> {
+/// 分片最后一次（成功的那次）请求的测量结果，自适应分片据此调整
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RangeStats {
+    pub bytes: u64,        // 这次请求写入的字节数
+    pub latency: Duration, // 发出请求到收到响应头
+    pub elapsed: Duration, // 发出请求到写完最后一块，不含暂停的时间
+}
+
+/// 从 `current_file_seek_start` 开始下载到分片末尾，每写入一块就向前推进
+///
+/// `writer` 在多次重试之间保留，缓冲区里还没写入文件的数据
+/// 正好接在 `current_file_seek_start` 之前。
+async fn download_range_once(
+    args: &DownloadRangeFileArgs<'_>,
+    writer: &mut ChunkWriter,
+    current_file_seek_start: &mut u64,
+) -> Result<RangeStats, DownloadRangeFileError> {
+    let requested_from = *current_file_seek_start;
+    let mut paused = Duration::ZERO;
+
+    let range_header_str =
+        format!("bytes={}-{}", *current_file_seek_start, args.end);
+
+    // 超时每次请求前重新读取，修改配置后下一个请求立即生效
+    let timeout_secs = args.download_config.timeout_secs();
+
     let fetch_range_args = FetchRangeArgs {
         http_client: args.http_client,
-        range_header_str: args.range_header_str,
+        range_header_str: &range_header_str,
         file_url: args.file_url,
+        if_range: args.validator.if_range(),
+        timeout_secs,
     };
 
+    // 按主机策略等待发出请求的名额，等待时间不算在延迟里
+    args.download_config.wait_request_slot(args.file_url).await;
+    let requested_at = Instant::now();
+
     let resp = fetch_range_method(fetch_range_args).await?;
+    let latency = requested_at.elapsed();
 
-    // 把它转成数据流
-    let mut download_stream = resp.bytes_stream();
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(DownloadRangeFileError::UnexpectedStatus(
+            status.as_u16(),
+        ));
+    }
 
-    let mut current_file_seek_start = args.start;
+    // 远程文件变了，继续写入会得到新旧内容混合的文件
+    args.validator.check_ranged_response(
+        args.file_url,
+        status,
+        resp.headers(),
+    )?;
 
-    let global_config = &args.global_config;
-    let mut global_config_watch = global_config.watch();
+    // 不是请求的那一段就不能按偏移量写入
+    check_range_response(
+        status,
+        resp.headers(),
+        *current_file_seek_start,
+        args.end,
+        args.total_size,
+    )?;
 
-    let inner_config = &args.inner_config;
-    let mut inner_config_watch = inner_config.watch();
+    // 把它转成数据流
+    let mut download_stream = resp.bytes_stream();
 
-    while let Some(downloaded_chunk) = download_stream.next().await {
+    while let Some(downloaded_chunk) =
+        idle_timeout(timeout_secs, &mut download_stream).await?
+    {
         let chunk = downloaded_chunk?;
 
-        while global_config.is_paused() || inner_config.is_paused() {
-            if global_config.is_paused() {
-                println!("[分片下载] 全局暂停");
-                let _ = global_config_watch.changed().await;
-                println!("[分片下载]  全局启动");
-            }
-
-            if inner_config.is_paused() {
-                println!("[分片下载] 内部暂停");
-                let _ = inner_config_watch.changed().await;
-                println!("[分片下载] 内部启动");
-            }
-        }
+        // 资源、账号、全局任意一层暂停都会在这里等待
+        let pause_started = Instant::now();
+        args.inner_state
+            .reporter
+            .wait_if_paused(&args.download_config, Some(args.chunk_index))
+            .await?;
+        paused += pause_started.elapsed();
 
         let chunk_length = chunk.len() as u64;
 
+        // 按资源、账号、全局三层限速等待，同一个文件的分片共用文件级令牌桶
+        args.download_config.throttle(chunk_length).await;
+
         let handle_bytes_stream_args = HandleBytesStreamArgs {
             chunk,
-            current_file_seek_start,
-            file: args.file,
+            writer: &mut *writer,
             inner_state: args.inner_state.clone(), // 如果 ResourceFileProperty 可 Clone
+            chunk_index: args.chunk_index,
         };
 
         handle_bytes_stream(handle_bytes_stream_args).await?;
 
-        current_file_seek_start += chunk_length;
+        *current_file_seek_start += chunk_length;
     }
 
-    Ok(())
+    // 响应体提前结束时不能当作完成，否则分片记录会把没写到的部分标记为完成，
+    // 文件里留下一段全是 0 的空洞
+    if *current_file_seek_start != args.end + 1 {
+        return Err(DownloadRangeFileError::Incomplete {
+            expected: args.end + 1,
+            actual: *current_file_seek_start,
+        });
+    }
+
+    Ok(RangeStats {
+        bytes: *current_file_seek_start - requested_from,
+        latency,
+        elapsed: requested_at.elapsed().saturating_sub(paused),
+    })
+}
+
+pub async fn download_range_file<'a>(
+    args: DownloadRangeFileArgs<'a>,
+) -> Result<RangeStats, DownloadRangeFileError> {
+    let mut current_file_seek_start = args.start;
+    let mut writer = ChunkWriter::new(Arc::clone(args.file), args.start);
+
+    // 累计失败次数，即使某次尝试写入了数据也不清零，
+    // 否则每次只返回几个字节就断开的服务器会被无限重试
+    let mut failures: u32 = 0;
+
+    loop {
+        let result = download_range_once(
+            &args,
+            &mut writer,
+            &mut current_file_seek_start,
+        )
+        .await;
+
+        let error = match result {
+            Ok(stats) => {
+                // 缓冲区里剩下的数据写入并落盘后才算下载完成，
+                // 之后分片记录才能把这一段标记为完成，
+                // 否则断电后记录里完成的区间可能根本没写到磁盘上
+                writer
+                    .sync_data()
+                    .await
+                    .map_err(HandleBytesStreamError::Write)?;
+                return Ok(stats);
+            }
+            Err(e) => e,
+        };
+
+        // 每次都重新读取，运行中修改重试次数会立即生效
+        let max_retries = args.download_config.max_retries().unwrap_or(0);
+
+        if !error.is_retryable() || failures >= max_retries {
+            return Err(error);
+        }
+
+        failures += 1;
+        args.inner_state.reporter.set_state(
+            Some(args.chunk_index),
+            TransferState::Retrying(failures),
+        )?;
+        tokio::time::sleep(backoff_delay(failures)).await;
+    }
 }
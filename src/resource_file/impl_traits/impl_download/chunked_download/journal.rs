@@ -0,0 +1,199 @@
+use crate::resource_file::structs::remote_validator::RemoteValidator;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+// 分片记录文件的后缀，和下载文件放在同一个目录
+const JOURNAL_EXTENSION: &str = "wdpart";
+
+#[derive(Debug, Error)]
+pub enum ChunkJournalError {
+    #[error("分片记录读写失败: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("分片记录序列化失败: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+/// 分片下载记录，保存在 `<文件名>.wdpart` 中
+///
+/// 只有整个分片写完才会记录，分片下载到一半中断时下次会重新下载这个分片。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkJournal {
+    /// 远程文件大小
+    pub remote_size: u64,
+    /// 开始下载时远程文件的版本（ETag、修改时间）
+    #[serde(flatten)]
+    pub validator: RemoteValidator,
+    /// 已完成的区间（包含两端），按起点排序且互不相邻
+    pub completed: Vec<(u64, u64)>,
+}
+
+impl ChunkJournal {
+    pub fn new(remote_size: u64, validator: RemoteValidator) -> Self {
+        Self { remote_size, validator, completed: vec![] }
+    }
+
+    /// 判断记录是否属于同一个远程文件
+    fn matches(
+        &self,
+        remote_size: u64,
+        validator: &RemoteValidator,
+    ) -> bool {
+        self.remote_size == remote_size
+            && self.validator.matches(validator)
+    }
+
+    /// 标记 `[start, end]` 已完成，并与相邻区间合并
+    pub fn mark_completed(&mut self, start: u64, end: u64) {
+        self.completed.push((start, end));
+        self.completed.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = vec![];
+        for (start, end) in self.completed.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+
+        self.completed = merged;
+    }
+
+    /// 已完成的字节数
+    pub fn completed_bytes(&self) -> u64 {
+        self.completed.iter().map(|(start, end)| end - start + 1).sum()
+    }
+
+    /// 还没有下载的区间（包含两端）
+    pub fn missing_ranges(&self) -> Vec<(u64, u64)> {
+        let mut missing = vec![];
+        let mut cursor = 0;
+
+        for &(start, end) in &self.completed {
+            if start > cursor {
+                missing.push((cursor, start - 1));
+            }
+            cursor = cursor.max(end + 1);
+        }
+
+        if cursor < self.remote_size {
+            missing.push((cursor, self.remote_size - 1));
+        }
+
+        missing
+    }
+}
+
+/// 下载文件对应的分片记录路径
+pub fn journal_path(save_absolute_path: &Path) -> PathBuf {
+    let mut path = save_absolute_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(JOURNAL_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// 读取分片记录的结果
+#[derive(Debug)]
+pub enum LoadedJournal {
+    /// 记录不存在或无法解析，无法解析的记录已经被删除
+    Missing,
+    /// 记录属于当前的远程文件
+    Matched(ChunkJournal),
+    /// 记录属于旧版本的远程文件（大小或版本不一致），
+    /// 是否删除由调用方按远程文件变化的处理方式决定
+    Changed(ChunkJournal),
+}
+
+/// 读取分片记录
+pub async fn load_journal(
+    save_absolute_path: &Path,
+    remote_size: u64,
+    validator: &RemoteValidator,
+) -> Result<LoadedJournal, ChunkJournalError> {
+    let path = journal_path(save_absolute_path);
+
+    let json = match fs::read(&path).await {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(LoadedJournal::Missing);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let Ok(journal) = serde_json::from_slice::<ChunkJournal>(&json) else {
+        remove_journal(save_absolute_path).await?;
+        return Ok(LoadedJournal::Missing);
+    };
+
+    if journal.matches(remote_size, validator) {
+        Ok(LoadedJournal::Matched(journal))
+    } else {
+        Ok(LoadedJournal::Changed(journal))
+    }
+}
+
+/// 删除分片记录，记录不存在时什么也不做
+pub async fn remove_journal(
+    save_absolute_path: &Path,
+) -> Result<(), ChunkJournalError> {
+    match fs::remove_file(journal_path(save_absolute_path)).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 多个分片任务共用的分片记录，每完成一个分片就写一次磁盘
+#[derive(Debug)]
+pub struct ChunkJournalFile {
+    path: PathBuf,
+    journal: Mutex<ChunkJournal>,
+}
+
+impl ChunkJournalFile {
+    pub fn new(save_absolute_path: &Path, journal: ChunkJournal) -> Self {
+        Self {
+            path: journal_path(save_absolute_path),
+            journal: Mutex::new(journal),
+        }
+    }
+
+    /// 写入磁盘，先写临时文件再重命名，进程中途退出也不会留下半截记录
+    async fn persist(
+        &self,
+        journal: &ChunkJournal,
+    ) -> Result<(), ChunkJournalError> {
+        let json = serde_json::to_vec(journal)?;
+
+        let mut tmp_path = self.path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        fs::write(&tmp_path, json).await?;
+        fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+
+    /// 立即写入当前记录，开始下载前调用一次，保证中断后能找到记录
+    pub async fn save(&self) -> Result<(), ChunkJournalError> {
+        let journal = self.journal.lock().await;
+        self.persist(&journal).await
+    }
+
+    /// 标记一个分片完成并写入磁盘
+    pub async fn complete(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<(), ChunkJournalError> {
+        let mut journal = self.journal.lock().await;
+        journal.mark_completed(start, end);
+        self.persist(&journal).await
+    }
+}
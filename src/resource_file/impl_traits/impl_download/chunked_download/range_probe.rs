@@ -0,0 +1,192 @@
+use crate::resource_file::impl_traits::impl_download::chunked_download::http_stream::{
+    FetchRangeArgs, FetchRangeError, fetch_range_method,
+};
+use crate::reactive::reactive::ReactivePropertyError;
+use crate::resource_file::impl_traits::impl_download::retry::{
+    Retryable, backoff_delay, is_retryable_reqwest_error,
+    is_retryable_status,
+};
+use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
+use crate::resource_file::structs::transfer_progress::TransferState;
+use crate::resource_file::traits::download::TDownloadConfig;
+use reqwest::header::{CONTENT_RANGE, HeaderMap};
+use reqwest::{Client, StatusCode};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RangeResponseError {
+    #[error("服务器没有按 Range 返回部分内容，状态码: {0}")]
+    NotPartialContent(u16),
+
+    #[error(
+        "Content-Range 与请求的区间不一致，请求 {expected}，返回 {actual}"
+    )]
+    ContentRangeMismatch { expected: String, actual: String },
+}
+
+/// 解析 `bytes start-end/total`，总大小未知（`*`）时为 `None`
+fn parse_content_range(value: &str) -> Option<(u64, u64, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (range, total) = range.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?, total))
+}
+
+/// 检查分片请求的响应是不是请求的那一段
+///
+/// 不支持 Range 的服务器会返回 200 和完整内容，
+/// 直接按分片的偏移量写入会把整个文件写进每个分片的位置。
+pub(crate) fn check_range_response(
+    status: StatusCode,
+    headers: &HeaderMap,
+    start: u64,
+    end: u64,
+    total_size: u64,
+) -> Result<(), RangeResponseError> {
+    if status != StatusCode::PARTIAL_CONTENT {
+        return Err(RangeResponseError::NotPartialContent(
+            status.as_u16(),
+        ));
+    }
+
+    let actual = headers
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let matched = match parse_content_range(actual) {
+        Some((s, e, total)) => {
+            s == start && e == end && total.is_none_or(|t| t == total_size)
+        }
+        None => false,
+    };
+
+    if !matched {
+        return Err(RangeResponseError::ContentRangeMismatch {
+            expected: format!("bytes {}-{}/{}", start, end, total_size),
+            actual: actual.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum ProbeRangeError {
+    #[error("探测 Range 支持失败: {0}")]
+    Fetch(#[from] FetchRangeError),
+
+    #[error("探测 Range 支持时服务器返回异常状态码: {0}")]
+    UnexpectedStatus(u16),
+
+    #[error("更新下载进度失败: {0}")]
+    UpdateProgress(#[from] ReactivePropertyError),
+}
+
+impl Retryable for ProbeRangeError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            ProbeRangeError::Fetch(FetchRangeError::Http(e)) => {
+                is_retryable_reqwest_error(e)
+            }
+            ProbeRangeError::Fetch(FetchRangeError::Timeout(_)) => true,
+            ProbeRangeError::UnexpectedStatus(status) => {
+                StatusCode::from_u16(*status)
+                    .map(is_retryable_status)
+                    .unwrap_or(false)
+            }
+            ProbeRangeError::UpdateProgress(_) => false,
+        }
+    }
+}
+
+pub(crate) struct ProbeRangeSupportArgs<'a> {
+    pub(crate) http_client: &'a Client,
+    pub(crate) file_url: &'a str,
+    pub(crate) total_size: u64,
+    pub(crate) inner_state: &'a ResourceFileProperty,
+    pub(crate) download_config: &'a TDownloadConfig,
+}
+
+/// 发出一次探测请求
+///
+/// 服务器返回 200（忽略了 Range）或者 206 但 `Content-Range` 不对时才算不支持，
+/// 其它状态码说明请求本身失败了，交给调用方决定是否重试。
+/// 服务器返回 200 时只读取了响应头，响应体随响应一起丢弃，不会下载整个文件。
+async fn probe_range_once(
+    args: &ProbeRangeSupportArgs<'_>,
+) -> Result<bool, ProbeRangeError> {
+    args.download_config.wait_request_slot(args.file_url).await;
+
+    let fetch_range_args = FetchRangeArgs {
+        http_client: args.http_client,
+        range_header_str: "bytes=0-0",
+        file_url: args.file_url,
+        if_range: None,
+        timeout_secs: args.download_config.timeout_secs(),
+    };
+
+    let resp = fetch_range_method(fetch_range_args).await?;
+    let status = resp.status();
+
+    match check_range_response(
+        status,
+        resp.headers(),
+        0,
+        0,
+        args.total_size,
+    ) {
+        Ok(()) => Ok(true),
+        Err(RangeResponseError::ContentRangeMismatch { .. }) => Ok(false),
+        Err(RangeResponseError::NotPartialContent(_))
+            if status == StatusCode::OK =>
+        {
+            Ok(false)
+        }
+        Err(RangeResponseError::NotPartialContent(status)) => {
+            Err(ProbeRangeError::UnexpectedStatus(status))
+        }
+    }
+}
+
+/// 请求第一个字节，确认服务器支持 Range 之后再分片下载
+///
+/// 只有返回 206 且 `Content-Range` 正确才算支持。
+/// 网络错误、超时和可以重试的状态码按退避时间重试，
+/// 重试次数用完或者遇到不能重试的错误时直接返回错误，不再当成不支持 Range。
+pub(crate) async fn probe_range_support(
+    args: ProbeRangeSupportArgs<'_>,
+) -> Result<bool, ProbeRangeError> {
+    // 空文件没有可以请求的字节
+    if args.total_size == 0 {
+        return Ok(false);
+    }
+
+    let mut failures: u32 = 0;
+
+    loop {
+        let error = match probe_range_once(&args).await {
+            Ok(supported) => return Ok(supported),
+            Err(e) => e,
+        };
+
+        // 每次都重新读取，运行中修改重试次数会立即生效
+        let max_retries = args.download_config.max_retries().unwrap_or(0);
+
+        if !error.is_retryable() || failures >= max_retries {
+            return Err(error);
+        }
+
+        failures += 1;
+        args.inner_state
+            .reporter
+            .set_state(None, TransferState::Retrying(failures))?;
+        tokio::time::sleep(backoff_delay(failures)).await;
+    }
+}
@@ -1,123 +1,183 @@
-use crate::global_config::global_config::GlobalConfig;
-use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
+use crate::reactive::reactive::ReactivePropertyError;
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
-use crate::resource_file::impl_traits::impl_download::chunked_download::file::{clone_file_handle, CloneFileHandleError};
-use crate::resource_file::impl_traits::impl_download::chunked_download::http_stream::{download_range_file, DownloadRangeFileArgs, DownloadRangeFileError};
+use crate::resource_file::structs::transfer_progress::TransferState;
+use crate::resource_file::traits::download::TDownloadConfig;
+use crate::resource_file::impl_traits::impl_download::chunked_download::journal::{ChunkJournalError, ChunkJournalFile};
+use crate::resource_file::impl_traits::impl_download::chunked_download::http_stream::{download_range_file, DownloadRangeFileArgs, DownloadRangeFileError, RangeStats};
+use crate::resource_file::impl_traits::impl_download::chunked_download::range_probe::RangeResponseError;
 use crate::resource_file::impl_traits::impl_download::chunked_download::CHUNK_SIZE;
-use futures_util::future::join_all;
 use reqwest::Client;
 use std::cmp::min;
+use std::fs::File;
 use std::sync::Arc;
 use thiserror::Error;
-use tokio::fs::File;
 use tokio::sync::{AcquireError, Semaphore};
-use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
 
-pub fn computed_range_header(total_size: u64, start: u64) -> String {
-    let end = min(start + CHUNK_SIZE - 1, total_size - 1);
-    let range_header_str = format!("bytes={}-{}", start, end);
-
-    range_header_str
+/// 计算分片最后一个字节的位置（包含）
+pub fn computed_range_end(total_size: u64, start: u64) -> u64 {
+    min(start + CHUNK_SIZE - 1, total_size - 1)
 }
 
 pub struct DownloadTaskArgs<'a> {
     pub(crate) http_client: &'a Client,
     pub file_url: &'a str,
     pub semaphore: Arc<Semaphore>,
-    pub start: u64,
+    pub ranges: Vec<(u64, u64)>, // 需要下载的区间（包含两端）
     pub total_size: u64,
-    pub file: File,
+    pub file: Arc<File>, // 所有分片共用，按偏移量写入
+    pub journal: Arc<ChunkJournalFile>,
+    pub validator: Arc<RemoteValidator>,
     pub inner_state: &'a ResourceFileProperty,
-    pub global_config: GlobalConfig,
-    pub inner_config: ResourceConfig,
+    pub download_config: TDownloadConfig,
 }
 
-pub type DownloadTasks = Vec<JoinHandle<Result<(), BuildDownloadTasksError>>>;
+/// 分片任务集合，drop 时还没结束的分片会被全部取消，
+/// 下载被中途取消时不会留下继续写文件的分片
+pub type DownloadTasks = JoinSet<Result<(), BuildDownloadTasksError>>;
 
-struct DownloadTaskContext {
+pub(crate) struct DownloadTaskContext {
     pub http_client: Client,
     pub file_url: String,
-    pub range_header_str: String,
-    pub file: File,
+    pub end: u64,
+    pub total_size: u64,
+    pub file: Arc<File>,
     pub start: u64,
+    pub chunk_index: usize,
+    pub validator: Arc<RemoteValidator>,
     pub inner_state: ResourceFileProperty,
-    pub global_config: GlobalConfig,
-    pub inner_config: ResourceConfig,
+    pub download_config: TDownloadConfig,
 }
 
 impl DownloadTaskContext {
-    fn into_range_file_args<'a>(
-        &'a mut self,
-    ) -> DownloadRangeFileArgs<'a> {
+    /// 为分片任务准备上下文
+    pub(crate) fn new(
+        args: &DownloadTaskArgs<'_>,
+        start: u64,
+        end: u64,
+        chunk_index: usize,
+    ) -> Self {
+        Self {
+            http_client: args.http_client.clone(),
+            file_url: args.file_url.to_string(),
+            end,
+            total_size: args.total_size,
+            file: Arc::clone(&args.file),
+            start,
+            chunk_index,
+            validator: Arc::clone(&args.validator),
+            inner_state: args.inner_state.clone(),
+            download_config: args.download_config.clone(),
+        }
+    }
+
+    /// 下载 `[start, end]` 并写入分片记录，分片状态跟着更新
+    pub(crate) async fn download_piece(
+        &mut self,
+        journal: &ChunkJournalFile,
+    ) -> Result<RangeStats, BuildDownloadTasksError> {
+        let _connection =
+            self.download_config.acquire_connection(&self.file_url).await?;
+
+        let chunk_index = self.chunk_index;
+        let (start, end) = (self.start, self.end);
+
+        let reporter = self.inner_state.reporter.clone();
+        reporter.set_chunk_state(chunk_index, TransferState::Running)?;
+
+        let result = async {
+            let stats =
+                download_range_file(self.range_file_args()).await?;
+
+            // 整个分片写完才记录，中途失败的分片下次重新下载
+            journal.complete(start, end).await?;
+
+            Ok(stats)
+        }
+        .await;
+
+        let chunk_state = match result {
+            Ok(_) => TransferState::Done,
+            Err(_) => TransferState::Failed,
+        };
+        reporter.set_chunk_state(chunk_index, chunk_state)?;
+
+        result
+    }
+
+    fn range_file_args(&self) -> DownloadRangeFileArgs<'_> {
         DownloadRangeFileArgs {
             http_client: &self.http_client,
-            range_header_str: &self.range_header_str,
             file_url: &self.file_url,
-            file: &mut self.file,
+            file: &self.file,
             start: self.start,
+            end: self.end,
+            total_size: self.total_size,
+            validator: &self.validator,
             inner_state: self.inner_state.clone(),
-            global_config: self.global_config.clone(),
-            inner_config: self.inner_config.clone(),
+            download_config: self.download_config.clone(),
+            chunk_index: self.chunk_index,
         }
     }
 }
 
 #[derive(Debug, Error)]
 pub enum BuildDownloadTasksError {
-    #[error("clone_file_handle 出错: {0}")]
-    CloneFileHandleError(#[from] CloneFileHandleError),
-
     #[error("无法获取并发许可: {0}")]
     AcquirePermitError(#[from] AcquireError),
 
     #[error("download_range_file 出错: {0}")]
     DownloadRangeFileError(#[from] DownloadRangeFileError),
+
+    #[error("更新分片记录出错: {0}")]
+    ChunkJournalError(#[from] ChunkJournalError),
+
+    #[error("更新下载进度出错: {0}")]
+    UpdateProgressError(#[from] ReactivePropertyError),
 }
 
 /// 构建下载任务
-pub async fn build_download_tasks<'a>(
-    mut args: DownloadTaskArgs<'a>,
-) -> Result<(DownloadTasks, DownloadTaskArgs<'a>), BuildDownloadTasksError>
-{
-    let mut tasks = vec![];
+pub fn build_download_tasks(
+    args: DownloadTaskArgs<'_>,
+) -> Result<DownloadTasks, BuildDownloadTasksError> {
+    let mut tasks = JoinSet::new();
+
+    // 把每个缺失的区间再切成分片，分配到并发线程
+    let mut pieces = vec![];
+    for &(range_start, range_end) in &args.ranges {
+        let mut start = range_start;
+
+        while start <= range_end {
+            let end = computed_range_end(range_end + 1, start);
+            pieces.push((start, end));
+            start = end + 1;
+        }
+    }
 
-    // 将下载任务分配到并发线程
-    while args.start < args.total_size {
-        let range_header_str =
-            computed_range_header(args.total_size, args.start);
+    // 先登记全部分片，排队中的分片也能在进度里看到
+    args.inner_state.reporter.set_chunks(&pieces)?;
 
-        let cloned_file_handle = clone_file_handle(&args.file).await?;
-
-        let mut context = DownloadTaskContext {
-            http_client: args.http_client.clone(),
-            file_url: args.file_url.to_string(),
-            range_header_str,
-            file: cloned_file_handle,
-            start: args.start,
-            inner_state: args.inner_state.clone(),
-            global_config: args.global_config.clone(),
-            inner_config: args.inner_config.clone(),
-        };
+    for (chunk_index, &(start, end)) in pieces.iter().enumerate() {
+        let mut context =
+            DownloadTaskContext::new(&args, start, end, chunk_index);
 
         let semaphore = Arc::clone(&args.semaphore);
+        let journal = Arc::clone(&args.journal);
 
-        let task = tokio::task::spawn(async move {
+        tasks.spawn(async move {
             // 使用Semaphore来限制并发数量
-            let _permit =
-                semaphore.acquire_owned().await?;
-
-            let download_range_file_args = context.into_range_file_args();
+            let _permit = semaphore.acquire_owned().await?;
 
-            download_range_file(download_range_file_args).await?;
+            context.download_piece(&journal).await?;
 
             Ok(())
         });
-
-        tasks.push(task);
-        args.start += CHUNK_SIZE;
     }
 
-    Ok((tasks, args))
+    Ok(tasks)
 }
 
 #[derive(Debug, Error)]
@@ -127,23 +187,38 @@ pub enum JoinAllAndHandleResultError {
 
     #[error("任务执行出错: {0}")]
     TaskError(String),
+
+    #[error(transparent)]
+    RemoteChanged(RemoteChangedError),
+
+    #[error(transparent)]
+    RangeNotSupported(RangeResponseError),
 }
 
 pub async fn join_all_and_handle_result(
-    tasks: DownloadTasks,
+    mut tasks: DownloadTasks,
 ) -> Result<(), JoinAllAndHandleResultError> {
-    // 等待所有任务完成
-    let results = join_all(tasks).await;
-
-    // 检查任务的结果
-    for result in results {
-        // JoinError
-        let inner = result?;
-
-        // 任务返回的 Err(String)
-        inner.map_err(|e| {
-            JoinAllAndHandleResultError::TaskError(e.to_string())
-        })?;
+    // 按完成顺序检查结果，分片内部已经重试过，这里出错说明已经无法挽回
+    while let Some(result) = tasks.join_next().await {
+        let outcome = match result {
+            Ok(Err(BuildDownloadTasksError::DownloadRangeFileError(
+                DownloadRangeFileError::RemoteChanged(e),
+            ))) => Err(JoinAllAndHandleResultError::RemoteChanged(e)),
+            Ok(Err(BuildDownloadTasksError::DownloadRangeFileError(
+                DownloadRangeFileError::RangeNotSupported(e),
+            ))) => Err(JoinAllAndHandleResultError::RangeNotSupported(e)),
+            Ok(inner) => inner.map_err(|e| {
+                JoinAllAndHandleResultError::TaskError(e.to_string())
+            }),
+            Err(e) => Err(JoinAllAndHandleResultError::JoinError(e)),
+        };
+
+        if let Err(e) = outcome {
+            // 取消其它还在排队或下载中的分片，避免继续浪费流量
+            tasks.abort_all();
+
+            return Err(e);
+        }
     }
 
     Ok(())
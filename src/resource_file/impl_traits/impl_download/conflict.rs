@@ -0,0 +1,143 @@
+use crate::resource_file::enums::conflict_policy::ConflictPolicy;
+use crate::resource_file::impl_traits::impl_download::chunked_download::journal::journal_path;
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+
+#[derive(Debug, Error)]
+pub enum ResolveConflictError {
+    #[error("本地文件[{0}] 已存在")]
+    LocalFileExists(PathBuf),
+
+    #[error("读取本地文件[{0}] 的修改时间失败: {1}")]
+    ReadModified(PathBuf, std::io::Error),
+
+    #[error("删除本地文件[{0}] 失败: {1}")]
+    RemoveFile(PathBuf, std::io::Error),
+}
+
+/// 冲突处理的结果
+pub(crate) enum ConflictResolution {
+    /// 下载到 `path`，`renamed` 表示它已经不是原来的目标路径
+    Download { path: PathBuf, renamed: bool },
+    /// 保留本地文件，不下载
+    Skip,
+}
+
+async fn exists(path: &Path) -> bool {
+    fs::metadata(path).await.is_ok()
+}
+
+/// 有分片记录说明是上次没下载完的文件
+async fn has_journal(path: &Path) -> bool {
+    exists(&journal_path(path)).await
+}
+
+/// 在文件名和扩展名之间插入编号：`name.ext` → `name (n).ext`
+fn numbered_path(path: &Path, n: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let name = match path.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+
+    path.with_file_name(name)
+}
+
+/// 找到第一个可用的编号路径
+///
+/// 上次改名后没下载完的文件也算可用，这样中断后再下载会续传到同一个文件。
+async fn rename_target(target: &Path) -> PathBuf {
+    let mut n = 1;
+
+    loop {
+        let candidate = numbered_path(target, n);
+        if !exists(&candidate).await || has_journal(&candidate).await {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// 远程文件是否比本地文件新，只比较到秒，HTTP 日期没有更高的精度
+async fn is_remote_newer(
+    target: &Path,
+    resource_file_data: &ResourceFileData,
+) -> Result<bool, ResolveConflictError> {
+    let Some(remote) = resource_file_data.last_modified else {
+        return Ok(false);
+    };
+
+    let local: DateTime<Utc> = fs::metadata(target)
+        .await
+        .and_then(|meta| meta.modified())
+        .map_err(|e| {
+            ResolveConflictError::ReadModified(target.to_path_buf(), e)
+        })?
+        .into();
+
+    Ok(remote.timestamp() > local.timestamp())
+}
+
+/// 覆盖目标文件
+///
+/// 非原子下载时先删掉旧文件，分片下载不会把大小相同的旧文件当成已经下载完成；
+/// 原子下载最后的重命名会替换它，这里保持原样，下载失败时旧文件依然可用。
+async fn overwrite(
+    target: &Path,
+    atomic_write: bool,
+) -> Result<ConflictResolution, ResolveConflictError> {
+    if !atomic_write {
+        fs::remove_file(target).await.map_err(|e| {
+            ResolveConflictError::RemoveFile(target.to_path_buf(), e)
+        })?;
+    }
+
+    Ok(ConflictResolution::Download {
+        path: target.to_path_buf(),
+        renamed: false,
+    })
+}
+
+/// 按冲突策略决定下载到哪里，或者是否跳过
+///
+/// 分片和不分片下载都在这之后才开始，两者遇到已有文件时的行为保持一致。
+pub(crate) async fn resolve_conflict(
+    target: &Path,
+    resource_file_data: &ResourceFileData,
+    policy: ConflictPolicy,
+    atomic_write: bool,
+) -> Result<ConflictResolution, ResolveConflictError> {
+    // 目标不存在，或者是上次没下载完的文件，直接下载或续传
+    if !exists(target).await || has_journal(target).await {
+        return Ok(ConflictResolution::Download {
+            path: target.to_path_buf(),
+            renamed: false,
+        });
+    }
+
+    match policy {
+        ConflictPolicy::Overwrite => overwrite(target, atomic_write).await,
+        ConflictPolicy::Skip => Ok(ConflictResolution::Skip),
+        ConflictPolicy::Rename => Ok(ConflictResolution::Download {
+            path: rename_target(target).await,
+            renamed: true,
+        }),
+        ConflictPolicy::KeepNewer => {
+            if is_remote_newer(target, resource_file_data).await? {
+                overwrite(target, atomic_write).await
+            } else {
+                Ok(ConflictResolution::Skip)
+            }
+        }
+        ConflictPolicy::Fail => Err(
+            ResolveConflictError::LocalFileExists(target.to_path_buf()),
+        ),
+    }
+}
@@ -1,9 +1,24 @@
-use crate::global_config::global_config::GlobalConfig;
-use crate::resource_file::structs::resource_config::ResourceConfig;
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
 use crate::resource_file::structs::resource_file_data::ResourceFileData;
 use crate::resource_file::traits::download::TDownloadConfig;
-use crate::resource_file::impl_traits::impl_download::chunked_download::black_list::is_chunked_download_blacklisted;
+use crate::resource_file::enums::download_mode::DownloadMode;
+use crate::resource_file::impl_traits::impl_download::atomic_write::{
+    FinalizeAtomicDownloadError, finalize_atomic_download, temp_path,
+};
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use crate::resource_file::enums::download_outcome::DownloadOutcome;
+use crate::resource_file::impl_traits::impl_download::conflict::{
+    ConflictResolution, ResolveConflictError, resolve_conflict,
+};
+use crate::resource_file::impl_traits::impl_download::preserve_metadata::{
+    PreserveMetadataError, preserve_metadata,
+};
+use crate::reactive::reactive::ReactivePropertyError;
+use crate::resource_file::impl_traits::impl_download::integrity::{
+    DownloadDigest, VerifyDownloadArgs, VerifyDownloadError, verify_download,
+};
+use crate::resource_file::structs::checksum::IntegrityError;
+use crate::resource_file::structs::remote_validator::RemoteChangedError;
 use crate::resource_file::impl_traits::impl_download::chunked_download::{chunked_download, ChunkedDownloadArgs, ChunkedDownloadError};
 use crate::resource_file::impl_traits::impl_download::not_chunked_download::{not_chunked_download, NotChunkedDownloadArgs, NotChunkedDownloadError};
 use reqwest::Client;
@@ -21,7 +36,7 @@ pub enum GetLargeFileThresholdError {
 fn get_large_file_threshold(
     config: &TDownloadConfig,
 ) -> Result<u64, GetLargeFileThresholdError> {
-    config.get_current().map(|c| c.large_file_threshold).ok_or_else(|| {
+    config.large_file_threshold().ok_or_else(|| {
         GetLargeFileThresholdError::GlobalConfigUninitialized
     })
 }
@@ -35,19 +50,16 @@ pub enum DownloadWithoutChunkingError {
 /// 统一处理非分片下载
 async fn download_without_chunking(
     args: HandleDownloadArgs,
-) -> Result<(), DownloadWithoutChunkingError> {
+) -> Result<DownloadDigest, DownloadWithoutChunkingError> {
     let not_chunked_download_args = NotChunkedDownloadArgs {
         http_client: args.http_client,
         resource_file_data: args.resource_file_data,
         save_absolute_path: args.save_absolute_path,
-        global_config: args.global_config,
         inner_state: args.inner_state,
-        inner_config: args.inner_config,
+        download_config: args.download_config,
     };
 
-    not_chunked_download(not_chunked_download_args).await?;
-
-    Ok(())
+    Ok(not_chunked_download(not_chunked_download_args).await?)
 }
 
 #[derive(Debug, Error)]
@@ -60,51 +72,233 @@ pub enum HandleDownloadError {
 
     #[error("chunked_download 出错: {0}")]
     ChunkedDownloadError(#[from] ChunkedDownloadError),
+
+    #[error(transparent)]
+    FinalizeAtomicDownloadError(#[from] FinalizeAtomicDownloadError),
+
+    #[error("校验下载结果失败: {0}")]
+    VerifyDownloadError(#[from] VerifyDownloadError),
+
+    #[error(transparent)]
+    ResolveConflictError(#[from] ResolveConflictError),
+
+    #[error("更新下载结果或进度失败: {0}")]
+    UpdateOutcomeError(#[from] ReactivePropertyError),
+
+    #[error(transparent)]
+    PreserveMetadataError(#[from] PreserveMetadataError),
+}
+
+impl HandleDownloadError {
+    /// 把远程文件变化的错误单独取出来，方便使用者按类型处理
+    pub(crate) fn into_remote_changed(
+        self,
+    ) -> Result<RemoteChangedError, Self> {
+        match self {
+            HandleDownloadError::ChunkedDownloadError(
+                ChunkedDownloadError::RemoteChanged(e),
+            )
+            | HandleDownloadError::DownloadWithoutChunkingError(
+                DownloadWithoutChunkingError::NotChunkedDownloadError(
+                    NotChunkedDownloadError::RemoteChanged(e),
+                ),
+            ) => Ok(e),
+            e => Err(e),
+        }
+    }
+
+    /// 把校验失败的错误单独取出来，方便使用者按类型处理
+    pub(crate) fn into_integrity(self) -> Result<IntegrityError, Self> {
+        match self {
+            HandleDownloadError::VerifyDownloadError(
+                VerifyDownloadError::Integrity(e),
+            ) => Ok(e),
+            e => Err(e),
+        }
+    }
+
+    /// 把本地文件已存在的错误单独取出来，方便使用者按类型处理
+    pub(crate) fn into_local_file_exists(self) -> Result<PathBuf, Self> {
+        match self {
+            HandleDownloadError::ResolveConflictError(
+                ResolveConflictError::LocalFileExists(path),
+            ) => Ok(path),
+            e => Err(e),
+        }
+    }
 }
 
 pub struct HandleDownloadArgs {
     pub(crate) resource_file_data: Arc<ResourceFileData>,
     pub(crate) save_absolute_path: PathBuf,
     pub(crate) http_client: Client,
-    pub(crate) global_config: GlobalConfig,
     pub(crate) inner_state: ResourceFileProperty,
-    pub(crate) inner_config: ResourceConfig,
+    pub(crate) download_config: TDownloadConfig,
 }
 
 pub async fn handle_download(
     args: HandleDownloadArgs,
 ) -> Result<(), HandleDownloadError> {
+    let inner_state = args.inner_state.clone();
+    inner_state.get_outcome().update(None)?;
+    inner_state.reporter.start(args.resource_file_data.size)?;
+
+    let result = download_with_conflict_policy(args).await;
+    inner_state.reporter.finish(result.is_ok())?;
+
+    inner_state.get_outcome().update(Some(result?))?;
+
+    Ok(())
+}
+
+/// 先按冲突策略确定保存路径，再下载、校验
+async fn download_with_conflict_policy(
+    args: HandleDownloadArgs,
+) -> Result<DownloadOutcome, HandleDownloadError> {
     // 这里不再处理任何文件夹的递归逻辑，交由库的使用者来处理递归情况
     if args.resource_file_data.is_dir {
-        return Ok(());
+        return Ok(DownloadOutcome::Skipped(args.save_absolute_path));
+    }
+
+    let atomic_write = args.download_config.atomic_write();
+
+    // 分片和不分片下载共用同一个冲突处理，已有文件的行为保持一致
+    let (save_absolute_path, renamed) = match resolve_conflict(
+        &args.save_absolute_path,
+        &args.resource_file_data,
+        args.download_config.conflict_policy(),
+        atomic_write,
+    )
+    .await?
+    {
+        ConflictResolution::Download { path, renamed } => (path, renamed),
+        ConflictResolution::Skip => {
+            return Ok(DownloadOutcome::Skipped(args.save_absolute_path));
+        }
+    };
+
+    // 原子下载：所有写入都落在临时文件上，分片记录也跟着临时文件走，
+    // 失败时临时文件留着下次续传，目标文件始终保持原样
+    let download_path = if atomic_write {
+        temp_path(&save_absolute_path)
+    } else {
+        save_absolute_path.clone()
+    };
+
+    let http_client = args.http_client.clone();
+    let resource_file_data = args.resource_file_data.clone();
+    let inner_state = args.inner_state.clone();
+    let download_config = args.download_config.clone();
+
+    // 从这里开始写入本地文件，取消下载时按它清理
+    inner_state.partial_path.update(Some(download_path.clone()))?;
+
+    let digest = route_download(HandleDownloadArgs {
+        save_absolute_path: download_path.clone(),
+        ..args
+    })
+    .await?;
+
+    // 校验放在重命名之前，校验失败时目标文件保持原样
+    verify_download(VerifyDownloadArgs {
+        http_client: &http_client,
+        path: &download_path,
+        resource_file_data: &resource_file_data,
+        digest,
+        inner_state: &inner_state,
+        download_config: &download_config,
+    })
+    .await?;
+
+    if atomic_write {
+        finalize_atomic_download(&download_path, &save_absolute_path)
+            .await?;
     }
 
-    // 黑名单检查
-    if is_chunked_download_blacklisted(
-        &args.resource_file_data.base_url.to_string(),
-    ) {
-        return Ok(download_without_chunking(args).await?);
+    // 文件已经完整落地，之后再取消也不会删除它
+    inner_state.partial_path.update(None)?;
+
+    preserve_metadata(
+        &save_absolute_path,
+        &resource_file_data,
+        &download_config,
+    )
+    .await?;
+
+    if renamed {
+        Ok(DownloadOutcome::Renamed(save_absolute_path))
+    } else {
+        Ok(DownloadOutcome::Downloaded(save_absolute_path))
+    }
+}
+
+/// 按下载方式、主机策略和文件大小选择分片或不分片下载
+///
+/// 非分片下载会返回边下载边计算的摘要，分片下载返回 `None`。
+async fn route_download(
+    args: HandleDownloadArgs,
+) -> Result<Option<DownloadDigest>, HandleDownloadError> {
+    match args.download_config.get_resource_config().download_mode() {
+        DownloadMode::NotChunked => {
+            return Ok(Some(download_without_chunking(args).await?));
+        }
+        // 强制分片时跳过主机策略和阈值检查
+        DownloadMode::Chunked => return download_with_chunking(args).await,
+        DownloadMode::Auto => {}
+    }
+
+    // 主机策略检查，不允许分片的服务器连探测请求都不发
+    if !args
+        .download_config
+        .get_global_config()
+        .get_host_policies()
+        .allows_chunked(&args.resource_file_data.absolute_path)
+    {
+        return Ok(Some(download_without_chunking(args).await?));
     }
 
     // 文件大小阈值检查
     if let Some(size) = args.resource_file_data.size {
-        let threshold = get_large_file_threshold(&args.global_config)?;
+        let threshold = get_large_file_threshold(&args.download_config)?;
         if size < threshold {
-            return Ok(download_without_chunking(args).await?);
+            return Ok(Some(download_without_chunking(args).await?));
         }
     }
 
     // 默认使用分片下载
+    download_with_chunking(args).await
+}
+
+async fn download_with_chunking(
+    args: HandleDownloadArgs,
+) -> Result<Option<DownloadDigest>, HandleDownloadError> {
     let chunked_download_args = ChunkedDownloadArgs {
-        resource_file_data: args.resource_file_data,
-        http_client: args.http_client,
-        save_absolute_path: args.save_absolute_path,
-        global_config: args.global_config,
-        inner_state: args.inner_state,
-        inner_config: args.inner_config,
+        resource_file_data: args.resource_file_data.clone(),
+        http_client: args.http_client.clone(),
+        save_absolute_path: args.save_absolute_path.clone(),
+        inner_state: args.inner_state.clone(),
+        download_config: args.download_config.clone(),
     };
 
-    chunked_download(chunked_download_args).await?;
+    match chunked_download(chunked_download_args).await {
+        Err(ChunkedDownloadError::RemoteChanged(e)) => {
+            if args.download_config.remote_changed_policy()
+                == RemoteChangedPolicy::Fail
+            {
+                return Err(ChunkedDownloadError::RemoteChanged(e).into());
+            }
 
-    Ok(())
+            // 远程文件的大小可能也变了，不能再按原来的大小分片，
+            // 改为不分片从头下载，非分片下载会清空文件并删除分片记录
+            Ok(Some(download_without_chunking(args).await?))
+        }
+        // 服务器不支持 Range，已经写入的分片不可信，同样改为不分片从头下载
+        Err(ChunkedDownloadError::RangeNotSupported(_)) => {
+            Ok(Some(download_without_chunking(args).await?))
+        }
+        result => {
+            result?;
+            Ok(None)
+        }
+    }
 }
@@ -0,0 +1,274 @@
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, response_timeout,
+};
+use crate::reactive::reactive::ReactivePropertyError;
+use crate::resource_file::enums::checksum_algorithm::ChecksumAlgorithm;
+use crate::resource_file::structs::checksum::{
+    Checksum, FileHash, IntegrityError,
+};
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
+use crate::resource_file::traits::download::TDownloadConfig;
+use md5::Md5;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use thiserror::Error;
+use tokio::io::AsyncReadExt;
+
+// 下载完成后重新读取文件时的缓冲区大小
+const HASH_BUFFER_SIZE: usize = 256 * 1024;
+
+/// 边读边计算摘要
+///
+/// SHA-256 总是计算，对外公开的摘要就是它；MD5 和 SHA-1 只在服务器提供了
+/// 对应的校验值时才计算，避免每次下载都多算两遍。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StreamHasher {
+    md5: Option<Md5>,
+    sha1: Option<Sha1>,
+    sha256: Sha256,
+}
+
+impl StreamHasher {
+    /// 除了 SHA-256 之外，再计算 `checksums` 里出现的算法
+    pub fn for_checksums(checksums: &[Checksum]) -> Self {
+        let mut hasher = Self::default();
+        hasher.enable(checksums);
+        hasher
+    }
+
+    /// 开始计算 `checksums` 里出现的算法，只能在还没有输入数据时调用
+    fn enable(&mut self, checksums: &[Checksum]) {
+        for checksum in checksums {
+            match checksum.algorithm {
+                ChecksumAlgorithm::Md5 => {
+                    self.md5.get_or_insert_with(Md5::new);
+                }
+                ChecksumAlgorithm::Sha1 => {
+                    self.sha1.get_or_insert_with(Sha1::new);
+                }
+                ChecksumAlgorithm::Sha256 => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        if let Some(md5) = &mut self.md5 {
+            md5.update(data);
+        }
+        if let Some(sha1) = &mut self.sha1 {
+            sha1.update(data);
+        }
+        self.sha256.update(data);
+    }
+
+    pub fn finalize(self) -> FileHash {
+        FileHash {
+            md5: self.md5.map(|md5| format!("{:x}", md5.finalize())),
+            sha1: self.sha1.map(|sha1| format!("{:x}", sha1.finalize())),
+            sha256: format!("{:x}", self.sha256.finalize()),
+        }
+    }
+}
+
+/// 边下载边计算的摘要，以及完整响应头里带的校验值
+#[derive(Debug, Clone)]
+pub(crate) struct DownloadDigest {
+    pub hash: FileHash,
+    pub checksums: Vec<Checksum>,
+}
+
+/// 非分片下载过程中的摘要状态，从头重新下载时需要 [`reset`](Self::reset)
+#[derive(Debug)]
+pub(crate) struct StreamDigest {
+    hasher: StreamHasher,
+    known: Vec<Checksum>, // 列目录时得到的校验值，重新下载时据此选择算法
+    checksums: Vec<Checksum>,
+}
+
+impl StreamDigest {
+    /// `known` 是下载前已经知道的校验值，决定除 SHA-256 外还要计算哪些算法
+    pub fn new(known: &[Checksum]) -> Self {
+        Self {
+            hasher: StreamHasher::for_checksums(known),
+            known: known.to_vec(),
+            checksums: vec![],
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// 记录完整文件响应里的校验值，并开始计算对应的算法
+    ///
+    /// 完整文件的响应总是从第一个字节开始写，此时还没有输入过数据。
+    pub fn record_headers(&mut self, headers: &HeaderMap) {
+        self.checksums = Checksum::from_headers(headers);
+        self.hasher.enable(&self.checksums);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(&self.known);
+    }
+
+    pub fn finish(self) -> DownloadDigest {
+        DownloadDigest {
+            hash: self.hasher.finalize(),
+            checksums: self.checksums,
+        }
+    }
+}
+
+/// 读取整个文件计算摘要，`checksums` 决定除 SHA-256 外还要计算哪些算法
+///
+/// 分片下载是乱序写入的，无法边下载边计算，只能在全部写完后再读一遍。
+async fn hash_file(
+    path: &Path,
+    checksums: &[Checksum],
+) -> Result<FileHash, std::io::Error> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = StreamHasher::for_checksums(checksums);
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[derive(Debug, Error)]
+pub enum VerifyDownloadError {
+    #[error("读取文件[{0}] 失败: {1}")]
+    ReadFile(String, std::io::Error),
+
+    #[error("获取校验文件失败: {0}")]
+    Sidecar(#[from] reqwest::Error),
+
+    #[error("获取校验文件超时: {0}")]
+    SidecarTimeout(#[from] RequestTimeoutError),
+
+    #[error("更新摘要失败: {0}")]
+    UpdateHash(#[from] ReactivePropertyError),
+
+    #[error(transparent)]
+    Integrity(#[from] IntegrityError),
+}
+
+/// 读取同名 `.sha256` 文件，服务器没有这个文件时返回 `None`
+async fn fetch_sidecar_checksum(
+    http_client: &Client,
+    file_url: &str,
+    timeout_secs: Option<u64>,
+) -> Result<Option<Checksum>, VerifyDownloadError> {
+    let request = http_client.get(format!("{}.sha256", file_url)).send();
+    let resp = response_timeout(timeout_secs, request).await??;
+
+    // 服务器没有这个文件（404）或者不允许读取时跳过
+    if !resp.status().is_success() {
+        return Ok(None);
+    }
+
+    let text = response_timeout(timeout_secs, resp.text()).await??;
+
+    Ok(Checksum::parse_sidecar(&text))
+}
+
+pub(crate) struct VerifyDownloadArgs<'a> {
+    pub(crate) http_client: &'a Client,
+    pub(crate) path: &'a Path,
+    pub(crate) resource_file_data: &'a ResourceFileData,
+    pub(crate) digest: Option<DownloadDigest>, // 分片下载时为空，需要重新读取文件
+    pub(crate) inner_state: &'a ResourceFileProperty,
+    pub(crate) download_config: &'a TDownloadConfig,
+}
+
+/// 校验下载结果：先比较大小，再把摘要和服务器提供的所有校验值逐一比较
+///
+/// 计算出的摘要会写入 `inner_state.hash`，校验失败时也会保留。
+pub(crate) async fn verify_download(
+    args: VerifyDownloadArgs<'_>,
+) -> Result<(), VerifyDownloadError> {
+    let path_str = args.path.to_string_lossy().to_string();
+    let read_error =
+        |e| VerifyDownloadError::ReadFile(path_str.clone(), e);
+
+    let actual =
+        tokio::fs::metadata(args.path).await.map_err(read_error)?.len();
+
+    if let Some(expected) = args.resource_file_data.size
+        && expected != actual
+    {
+        return Err(IntegrityError::SizeMismatch {
+            path: args.path.to_path_buf(),
+            expected,
+            actual,
+        }
+        .into());
+    }
+
+    let (hash, mut checksums) = match args.digest {
+        Some(DownloadDigest { hash, checksums }) => {
+            (Some(hash), checksums)
+        }
+        None => (None, vec![]),
+    };
+
+    checksums.extend(args.resource_file_data.checksums.iter().cloned());
+
+    if args.download_config.sidecar_checksum() {
+        // 校验文件和被下载的文件在同一个服务器上，同样受请求频率限制
+        args.download_config
+            .wait_request_slot(&args.resource_file_data.absolute_path)
+            .await;
+
+        let sidecar = fetch_sidecar_checksum(
+            args.http_client,
+            &args.resource_file_data.absolute_path,
+            args.download_config.timeout_secs(),
+        )
+        .await?;
+        checksums.extend(sidecar);
+    }
+
+    // 边下载边计算的摘要已经包含服务器提供的所有算法，
+    // 分片下载只能重新读一遍文件，同样只计算需要的算法；
+    // 没有任何校验值时不再读文件，大文件的磁盘读写不会翻倍
+    let hash = match hash {
+        Some(hash) => hash,
+        None if checksums.is_empty() => {
+            // 不留下上一次下载的摘要
+            args.inner_state.get_hash().update(None)?;
+            return Ok(());
+        }
+        None => {
+            hash_file(args.path, &checksums).await.map_err(read_error)?
+        }
+    };
+
+    args.inner_state.get_hash().update(Some(hash.clone()))?;
+
+    // 校验文件只提供 SHA-256，上面的摘要一定包含所有需要的算法
+    for checksum in checksums {
+        let actual = hash.get(checksum.algorithm).unwrap_or_default();
+        if actual != checksum.value {
+            return Err(IntegrityError::ChecksumMismatch {
+                path: args.path.to_path_buf(),
+                algorithm: checksum.algorithm,
+                expected: checksum.value,
+                actual: actual.to_string(),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
@@ -1,21 +1,46 @@
-use crate::global_config::global_config::GlobalConfig;
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, idle_timeout, response_timeout,
+};
 use crate::reactive::reactive::ReactivePropertyError;
-use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::impl_traits::impl_download::chunked_download::journal::{
+    ChunkJournalError, remove_journal,
+};
+use crate::resource_file::impl_traits::impl_download::integrity::{
+    DownloadDigest, StreamDigest,
+};
+use crate::resource_file::impl_traits::impl_download::retry::{
+    Retryable, backoff_delay, is_retryable_reqwest_error,
+    is_retryable_status,
+};
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
 use crate::resource_file::structs::resource_file_data::ResourceFileData;
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
-use futures_util::StreamExt;
-use reqwest::Client;
+use crate::resource_file::structs::transfer_progress::TransferState;
+use crate::resource_file::traits::download::TDownloadConfig;
+use reqwest::header::{ACCEPT_ENCODING, IF_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use std::io::SeekFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::AcquireError;
 
 #[derive(Debug, Error)]
 pub enum NotChunkedDownloadError {
     #[error("HTTP 请求失败: {0}")]
     HttpClientError(#[from] reqwest::Error),
 
+    #[error("请求超时: {0}")]
+    Timeout(#[from] RequestTimeoutError),
+
+    #[error("服务器返回异常状态码: {0}")]
+    UnexpectedStatus(u16),
+
     #[error("创建文件失败: {0}")]
     CreateFileError(std::io::Error),
 
@@ -25,69 +50,253 @@ pub enum NotChunkedDownloadError {
     #[error("写入文件失败: {0}")]
     WriteFileError(tokio::io::Error),
 
+    #[error("清空文件失败: {0}")]
+    TruncateFileError(tokio::io::Error),
+
     #[error("更新下载字节数失败: {0}")]
     UpdateBytesError(#[from] ReactivePropertyError), // 或者你自己定义的错误类型
+
+    #[error("删除分片记录失败: {0}")]
+    ChunkJournalError(#[from] ChunkJournalError),
+
+    #[error(transparent)]
+    RemoteChanged(#[from] RemoteChangedError),
+
+    #[error("无法获取连接许可: {0}")]
+    AcquireConnectionError(#[from] AcquireError),
+}
+
+impl Retryable for NotChunkedDownloadError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            NotChunkedDownloadError::HttpClientError(e)
+            | NotChunkedDownloadError::DownloadStreamError(e) => {
+                is_retryable_reqwest_error(e)
+            }
+            NotChunkedDownloadError::Timeout(_) => true,
+            NotChunkedDownloadError::UnexpectedStatus(status) => {
+                StatusCode::from_u16(*status)
+                    .map(is_retryable_status)
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
 }
 
 pub struct NotChunkedDownloadArgs {
     pub(crate) http_client: Client,
     pub(crate) resource_file_data: Arc<ResourceFileData>,
     pub(crate) save_absolute_path: PathBuf,
-    pub(crate) global_config: GlobalConfig,
     pub(crate) inner_state: ResourceFileProperty,
-    pub(crate) inner_config: ResourceConfig,
+    pub(crate) download_config: TDownloadConfig,
 }
 
-pub async fn not_chunked_download(
-    args: NotChunkedDownloadArgs,
+/// 清空文件，把已经写入的字节从进度里退回去，摘要也重新计算
+async fn restart_from_beginning(
+    args: &NotChunkedDownloadArgs,
+    file: &mut File,
+    written: &mut u64,
+    digest: &mut StreamDigest,
 ) -> Result<(), NotChunkedDownloadError> {
-    let resp = args
+    file.set_len(0)
+        .await
+        .map_err(NotChunkedDownloadError::TruncateFileError)?;
+    file.seek(SeekFrom::Start(0))
+        .await
+        .map_err(NotChunkedDownloadError::TruncateFileError)?;
+
+    args.inner_state.reporter.discard(*written)?;
+    *written = 0;
+    digest.reset();
+
+    Ok(())
+}
+
+/// 下载一次，`written` 是已经写入文件的字节数，重试时从这里继续
+///
+/// `validator` 是第一次请求时记录的远程文件版本，续传时用来确认文件没有变化。
+async fn download_stream_once(
+    args: &NotChunkedDownloadArgs,
+    file: &mut File,
+    written: &mut u64,
+    validator: &mut Option<RemoteValidator>,
+    digest: &mut StreamDigest,
+) -> Result<(), NotChunkedDownloadError> {
+    // 不让服务器压缩：保存的必须是原始字节，
+    // Digest / Content-MD5 也是按传输的实体计算的，解压后就对不上了
+    let mut request = args
         .http_client
         .get(&args.resource_file_data.absolute_path)
-        .send()
-        .await?;
+        .header(ACCEPT_ENCODING, "identity");
 
-    let mut download_stream = resp.bytes_stream();
+    if *written > 0 {
+        request = request.header(RANGE, format!("bytes={}-", *written));
 
-    let mut file = File::create(&args.save_absolute_path)
-        .await
-        .map_err(|e| NotChunkedDownloadError::CreateFileError(e))?;
+        if let Some(if_range) =
+            validator.as_ref().and_then(RemoteValidator::if_range)
+        {
+            request = request.header(IF_RANGE, if_range);
+        }
+    }
 
-    let reactive_downloaded_bytes = args.inner_state.get_download_bytes();
+    // 超时每次请求前重新读取，修改配置后下一个请求立即生效
+    let timeout_secs = args.download_config.timeout_secs();
 
-    let global_config = &args.global_config;
-    let mut global_config_watch = global_config.watch();
+    args.download_config
+        .wait_request_slot(&args.resource_file_data.absolute_path)
+        .await;
 
-    let inner_config = &args.inner_config;
-    let mut inner_config_watch = inner_config.watch();
+    let resp = response_timeout(timeout_secs, request.send()).await??;
 
-    while let Some(memory_chunk) = download_stream.next().await {
-        while global_config.is_paused() || inner_config.is_paused() {
-            if global_config.is_paused() {
-                println!("全局暂停");
-                let _ = global_config_watch.changed().await;
-                println!("全局启动");
-            }
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(NotChunkedDownloadError::UnexpectedStatus(
+            status.as_u16(),
+        ));
+    }
 
-            if inner_config.is_paused() {
-                println!("内部暂停");
-                let _ = inner_config_watch.changed().await;
-                println!("内部启动");
-            }
+    match validator {
+        // 续传请求，先确认还是同一个版本
+        Some(validator) if *written > 0 => {
+            validator.check_ranged_response(
+                &args.resource_file_data.absolute_path,
+                status,
+                resp.headers(),
+            )?;
         }
+        // 从头下载，记录这次拿到的版本，响应头里没有时用列目录时的数据
+        _ => {
+            let current = RemoteValidator::from_headers(resp.headers());
+            *validator = Some(if current == RemoteValidator::default() {
+                RemoteValidator::from_data(&args.resource_file_data)
+            } else {
+                current
+            });
+        }
+    }
 
-        let chunk = memory_chunk.map_err(|e| {
-            NotChunkedDownloadError::DownloadStreamError(e)
-        })?;
+    // 服务器忽略了 Range，返回的是完整文件，只能从头开始写
+    if *written > 0 && status != StatusCode::PARTIAL_CONTENT {
+        restart_from_beginning(args, file, written, digest).await?;
+    }
+
+    // 完整文件的响应头里可能带着校验值，206 里的只对应其中一段
+    if status != StatusCode::PARTIAL_CONTENT {
+        digest.record_headers(resp.headers());
+    }
+
+    let mut download_stream = resp.bytes_stream();
+
+    while let Some(memory_chunk) =
+        idle_timeout(timeout_secs, &mut download_stream).await?
+    {
+        // 资源、账号、全局任意一层暂停都会在这里等待
+        args.inner_state
+            .reporter
+            .wait_if_paused(&args.download_config, None)
+            .await?;
+
+        let chunk = memory_chunk
+            .map_err(NotChunkedDownloadError::DownloadStreamError)?;
+
+        // 按资源、账号、全局三层限速等待
+        args.download_config.throttle(chunk.len() as u64).await;
 
         file.write_all(&chunk)
             .await
-            .map_err(|e| NotChunkedDownloadError::WriteFileError(e))?;
+            .map_err(NotChunkedDownloadError::WriteFileError)?;
+
+        digest.update(&chunk);
 
-        reactive_downloaded_bytes.update_field(|download_bytes| {
-            *download_bytes += chunk.len()
-        })?;
+        *written += chunk.len() as u64;
+
+        args.inner_state.reporter.add(None, chunk.len() as u64)?;
     }
 
     Ok(())
 }
+
+/// 下载整个文件，返回边下载边计算出的摘要
+pub async fn not_chunked_download(
+    args: NotChunkedDownloadArgs,
+) -> Result<DownloadDigest, NotChunkedDownloadError> {
+    // 整个下载只用一个连接，从头到尾占着名额
+    let _connection = args
+        .download_config
+        .acquire_connection(&args.resource_file_data.absolute_path)
+        .await?;
+
+    let mut file = File::create(&args.save_absolute_path)
+        .await
+        .map_err(NotChunkedDownloadError::CreateFileError)?;
+
+    // 文件已经被清空，之前分片下载留下的记录不再有效，进度也从 0 开始
+    remove_journal(&args.save_absolute_path).await?;
+    args.inner_state.reporter.reset(0)?;
+
+    let mut written: u64 = 0;
+    let mut validator: Option<RemoteValidator> = None;
+    let mut digest =
+        StreamDigest::new(&args.resource_file_data.checksums);
+
+    // 累计失败次数，即使某次尝试写入了数据也不清零，
+    // 否则每次只返回几个字节就断开的服务器会被无限重试
+    let mut failures: u32 = 0;
+
+    loop {
+        let result = download_stream_once(
+            &args,
+            &mut file,
+            &mut written,
+            &mut validator,
+            &mut digest,
+        )
+        .await;
+
+        let error = match result {
+            Ok(()) => {
+                // tokio 的 File 写入是在后台完成的，返回之前必须 flush
+                file.flush()
+                    .await
+                    .map_err(NotChunkedDownloadError::WriteFileError)?;
+                return Ok(digest.finish());
+            }
+            Err(e) => e,
+        };
+
+        // 每次都重新读取，运行中修改重试次数会立即生效
+        let max_retries = args.download_config.max_retries().unwrap_or(0);
+
+        if let NotChunkedDownloadError::RemoteChanged(_) = error {
+            let policy = args.download_config.remote_changed_policy();
+            if policy == RemoteChangedPolicy::Fail
+                || failures >= max_retries
+            {
+                return Err(error);
+            }
+
+            // 丢掉旧版本的内容，下一次请求不带 Range，直接下载新版本
+            restart_from_beginning(
+                &args,
+                &mut file,
+                &mut written,
+                &mut digest,
+            )
+            .await?;
+            validator = None;
+            failures += 1;
+            continue;
+        }
+
+        if !error.is_retryable() || failures >= max_retries {
+            return Err(error);
+        }
+
+        failures += 1;
+        args.inner_state
+            .reporter
+            .set_state(None, TransferState::Retrying(failures))?;
+        tokio::time::sleep(backoff_delay(failures)).await;
+    }
+}
@@ -0,0 +1,94 @@
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use crate::resource_file::traits::download::TDownloadConfig;
+use chrono::{DateTime, FixedOffset};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PreserveMetadataError {
+    #[error("设置[{0}] 的修改时间失败: {1}")]
+    SetModified(PathBuf, std::io::Error),
+
+    #[error("设置[{0}] 为只读失败: {1}")]
+    SetReadonly(PathBuf, std::io::Error),
+}
+
+/// 以只修改属性的方式打开文件或文件夹
+#[cfg(windows)]
+fn open_for_times(path: &Path) -> std::io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // FILE_WRITE_ATTRIBUTES，FILE_FLAG_BACKUP_SEMANTICS 用来打开文件夹
+    OpenOptions::new()
+        .access_mode(0x0100)
+        .custom_flags(0x0200_0000)
+        .open(path)
+}
+
+/// 以只修改属性的方式打开文件或文件夹
+///
+/// 修改时间只要求是文件的所有者，只读打开就够了，文件夹也能这样打开。
+#[cfg(not(windows))]
+fn open_for_times(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().read(true).open(path)
+}
+
+/// 把文件或文件夹的修改时间设为 `modified`
+pub(crate) async fn set_modified(
+    path: &Path,
+    modified: DateTime<FixedOffset>,
+) -> Result<(), PreserveMetadataError> {
+    let owned_path = path.to_path_buf();
+    let modified = SystemTime::from(modified);
+
+    tokio::task::spawn_blocking(move || {
+        open_for_times(&owned_path)?.set_modified(modified)
+    })
+    .await
+    .map_err(std::io::Error::other)
+    .and_then(|result| result)
+    .map_err(|e| PreserveMetadataError::SetModified(path.to_path_buf(), e))
+}
+
+/// 服务器返回了权限信息，且当前账号既没有 `write` 也没有 `all`
+fn is_readonly(privileges: &[String]) -> bool {
+    !privileges.is_empty()
+        && !privileges.iter().any(|p| p == "write" || p == "all")
+}
+
+/// 下载完成后按配置同步远程文件的修改时间和只读属性
+///
+/// 必须在最终文件落地之后调用，原子下载的重命名、校验时的读取都不会再改动它。
+pub(crate) async fn preserve_metadata(
+    path: &Path,
+    resource_file_data: &ResourceFileData,
+    download_config: &TDownloadConfig,
+) -> Result<(), PreserveMetadataError> {
+    if download_config.preserve_mtime()
+        && let Some(modified) = resource_file_data.last_modified
+    {
+        set_modified(path, modified).await?;
+    }
+
+    // 只读放在最后，先把其它属性改完
+    if download_config.preserve_readonly()
+        && is_readonly(&resource_file_data.privileges)
+    {
+        let set_readonly_error =
+            |e| PreserveMetadataError::SetReadonly(path.to_path_buf(), e);
+
+        let mut permissions = tokio::fs::metadata(path)
+            .await
+            .map_err(set_readonly_error)?
+            .permissions();
+        permissions.set_readonly(true);
+
+        tokio::fs::set_permissions(path, permissions)
+            .await
+            .map_err(set_readonly_error)?;
+    }
+
+    Ok(())
+}
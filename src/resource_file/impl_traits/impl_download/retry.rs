@@ -0,0 +1,70 @@
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 第一次重试前的等待时间
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+// 单次等待时间的上限
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// 判断一个下载错误是否值得重试
+pub(crate) trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+/// 判断 HTTP 状态码是否值得重试
+///
+/// 只重试请求超时（408）、限流（429）和服务器的临时故障（500、502、503、504），
+/// 其它状态码（鉴权失败、资源不存在、501 不支持等）重试也不会成功，直接放弃。
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// 判断 reqwest 错误是否值得重试
+///
+/// 带状态码的错误按状态码判断，连接断开、超时、读取中断等网络错误都可以重试。
+pub(crate) fn is_retryable_reqwest_error(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => is_retryable_status(status),
+        None => !error.is_builder(),
+    }
+}
+
+/// 计算第 `attempt` 次重试前的等待时间（从 1 开始）
+///
+/// 指数退避加全抖动：在 `[0, min(上限, 基数 * 2^(attempt-1))]` 之间随机取值，
+/// 避免多个分片在同一时刻一起重试。
+pub(crate) fn backoff_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let ceiling = RETRY_BASE_DELAY
+        .saturating_mul(1 << exponent)
+        .min(RETRY_MAX_DELAY);
+
+    let ceiling_millis = ceiling.as_millis() as u64;
+    if ceiling_millis == 0 {
+        return ceiling;
+    }
+
+    Duration::from_millis(random_u64() % (ceiling_millis + 1))
+}
+
+/// 不引入随机数依赖，借用 `RandomState` 每次实例化都会随机生成的种子
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    hasher.write_u64(nanos);
+    hasher.finish()
+}
@@ -0,0 +1,341 @@
+use crate::client::enums::depth::Depth;
+use crate::client::webdav_request::get_folders_public_impl::{
+    GetFoldersError, get_folders_with_client,
+};
+use crate::resource_file::enums::download_outcome::DownloadOutcome;
+use crate::resource_file::impl_traits::impl_download::preserve_metadata::set_modified;
+use crate::resource_file::structs::recursive_download::{
+    RecursiveDownloadFailure, RecursiveDownloadOptions,
+    RecursiveDownloadOutcome, RecursiveDownloadReport,
+    RecursiveDownloadSkipReason,
+};
+use crate::resource_file::structs::resources_file::ResourcesFile;
+use crate::resource_file::traits::download::{Download, DownloadError};
+use crate::resource_file::traits::download_recursive::{
+    DownloadRecursive, DownloadRecursiveError,
+};
+use crate::resource_file::traits::to_resource_file_data::ToResourceFileData;
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::task::{Id, JoinError, JoinSet};
+
+/// 名字会被直接拼到本地路径上，不能包含分隔符，也不能是 `.`、`..`
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+}
+
+/// 比较两个 href 是否指向同一个资源，忽略末尾斜杠和 URL 编码的差异
+fn is_same_href(a: &str, b: &str) -> bool {
+    let decode = |href: &str| {
+        percent_encoding::percent_decode_str(href.trim_end_matches('/'))
+            .decode_utf8_lossy()
+            .to_string()
+    };
+
+    decode(a) == decode(b)
+}
+
+/// 列出文件夹的直接子项
+///
+/// 空文件夹的响应里只有它自己，不会被当成脏数据丢掉，这里按路径再过滤一次。
+pub(crate) async fn list_children(
+    dir: &ResourcesFile,
+) -> Result<Vec<ResourcesFile>, GetFoldersError> {
+    let data = dir.get_data();
+
+    let url = if data.absolute_path.ends_with('/') {
+        data.absolute_path.clone()
+    } else {
+        format!("{}/", data.absolute_path)
+    };
+
+    let multi_status = get_folders_with_client(
+        dir.get_http_client(),
+        &url,
+        &Depth::One,
+        dir.get_download_config().timeout_secs(),
+    )
+    .await?;
+
+    let children = multi_status
+        .to_resource_file_data(&data.base_url)?
+        .into_iter()
+        .filter(|child| {
+            !is_same_href(
+                &child.relative_root_path,
+                &data.relative_root_path,
+            )
+        })
+        .map(|child| {
+            child.to_resources_file(
+                dir.get_http_client_arc(),
+                dir.get_global_config(),
+            )
+        })
+        .collect();
+
+    Ok(children)
+}
+
+/// 下载中的文件，任务异常退出时用来找回它对应的路径
+type TRunningFiles = HashMap<Id, (String, PathBuf)>;
+
+/// 需要保留修改时间的文件夹：报告中的序号、本地路径、远程修改时间
+type TCreatedDirs = Vec<(usize, PathBuf, DateTime<FixedOffset>)>;
+
+type TFileResult =
+    (String, PathBuf, Result<Option<DownloadOutcome>, DownloadError>);
+
+/// 把一个下载任务的结果写进报告
+fn record_file_result(
+    report: &mut RecursiveDownloadReport,
+    running: &mut TRunningFiles,
+    result: Result<(Id, TFileResult), JoinError>,
+) {
+    let (id, entry) = match result {
+        Ok((id, (remote_path, local_path, result))) => {
+            let (local_path, outcome) = match result {
+                // 按冲突策略改名后，报告里记录实际保存的路径
+                Ok(Some(DownloadOutcome::Renamed(path))) => {
+                    (path, RecursiveDownloadOutcome::Downloaded)
+                }
+                Ok(Some(DownloadOutcome::Skipped(_))) => (
+                    local_path,
+                    RecursiveDownloadOutcome::Skipped(
+                        RecursiveDownloadSkipReason::LocalFileExists,
+                    ),
+                ),
+                Ok(_) => {
+                    (local_path, RecursiveDownloadOutcome::Downloaded)
+                }
+                Err(e) => (
+                    local_path,
+                    RecursiveDownloadOutcome::Failed(e.into()),
+                ),
+            };
+            (id, Some((remote_path, local_path, outcome)))
+        }
+        Err(e) => {
+            let id = e.id();
+            let entry = running.get(&id).map(|(remote, local)| {
+                (
+                    remote.clone(),
+                    local.clone(),
+                    RecursiveDownloadOutcome::Failed(e.into()),
+                )
+            });
+            (id, entry)
+        }
+    };
+
+    running.remove(&id);
+
+    if let Some((remote_path, local_path, outcome)) = entry {
+        report.push(&remote_path, local_path, false, outcome);
+    }
+}
+
+/// 处理一个子文件夹：列出内容并创建本地文件夹，返回需要继续处理的子项
+async fn prepare_dir(
+    dir: &ResourcesFile,
+    local_path: &Path,
+    options: &RecursiveDownloadOptions,
+) -> Result<Vec<ResourcesFile>, RecursiveDownloadOutcome> {
+    let children = list_children(dir)
+        .await
+        .map_err(|e| RecursiveDownloadOutcome::Failed(e.into()))?;
+
+    if children.is_empty() && !options.include_empty_dirs {
+        return Err(RecursiveDownloadOutcome::Skipped(
+            RecursiveDownloadSkipReason::EmptyDir,
+        ));
+    }
+
+    tokio::fs::create_dir_all(local_path).await.map_err(|e| {
+        RecursiveDownloadOutcome::Failed(
+            RecursiveDownloadFailure::CreateDir(e),
+        )
+    })?;
+
+    Ok(children)
+}
+
+/// 删掉整棵子树里没有任何文件的文件夹
+///
+/// 列目录时只能看到直接子项，只有空文件夹的文件夹也会被创建，
+/// 所以等全部处理完再按报告里的文件找出真正用到的文件夹。
+async fn prune_empty_dirs(report: &mut RecursiveDownloadReport) {
+    let used: HashSet<PathBuf> = report
+        .entries
+        .iter()
+        .filter(|entry| {
+            !entry.is_dir
+                && !matches!(
+                    entry.outcome,
+                    RecursiveDownloadOutcome::Skipped(
+                        RecursiveDownloadSkipReason::UnsafeName
+                    )
+                )
+        })
+        .flat_map(|entry| entry.local_path.ancestors().skip(1))
+        .map(Path::to_path_buf)
+        .collect();
+
+    // 文件夹按层加入报告，倒序处理时子文件夹先于父文件夹被删掉
+    for entry in report.entries.iter_mut().rev() {
+        if !entry.is_dir
+            || used.contains(&entry.local_path)
+            || !matches!(
+                entry.outcome,
+                RecursiveDownloadOutcome::Downloaded
+            )
+        {
+            continue;
+        }
+
+        // 只删除空文件夹，本地原本就有内容的文件夹保留
+        if tokio::fs::remove_dir(&entry.local_path).await.is_ok() {
+            entry.outcome = RecursiveDownloadOutcome::Skipped(
+                RecursiveDownloadSkipReason::EmptyDir,
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl DownloadRecursive for ResourcesFile {
+    async fn download_recursive(
+        &self,
+        save_absolute_path: &str,
+        options: RecursiveDownloadOptions,
+    ) -> Result<RecursiveDownloadReport, DownloadRecursiveError> {
+        let root = PathBuf::from(save_absolute_path);
+        let data = self.get_data();
+        let mut report = RecursiveDownloadReport::default();
+
+        // 文件夹先列出内容，失败时不在本地留下空文件夹
+        let root_children = if data.is_dir {
+            list_children(self).await?
+        } else {
+            vec![self.clone()]
+        };
+
+        tokio::fs::create_dir_all(&root).await.map_err(|e| {
+            DownloadRecursiveError::CreateDir(root.clone(), e)
+        })?;
+
+        let max_concurrent_files = options.max_concurrent_files.max(1);
+        let mut tasks: JoinSet<TFileResult> = JoinSet::new();
+        let mut running = TRunningFiles::new();
+        let mut created_dirs = TCreatedDirs::new();
+
+        // 按层处理，不使用递归，文件夹很深时也不会撑爆调用栈
+        let mut queue = VecDeque::from([(root_children, root)]);
+
+        while let Some((children, local_dir)) = queue.pop_front() {
+            for child in children {
+                let child_data = child.get_data();
+                let remote_path = child_data.relative_root_path.clone();
+                let local_path = local_dir.join(&child_data.name);
+
+                if !is_safe_name(&child_data.name) {
+                    report.push(
+                        &remote_path,
+                        local_path,
+                        child_data.is_dir,
+                        RecursiveDownloadOutcome::Skipped(
+                            RecursiveDownloadSkipReason::UnsafeName,
+                        ),
+                    );
+                    continue;
+                }
+
+                if child_data.is_dir {
+                    let outcome =
+                        match prepare_dir(&child, &local_path, &options)
+                            .await
+                        {
+                            Ok(grandchildren) => {
+                                queue.push_back((
+                                    grandchildren,
+                                    local_path.clone(),
+                                ));
+                                RecursiveDownloadOutcome::Downloaded
+                            }
+                            Err(outcome) => outcome,
+                        };
+
+                    if matches!(
+                        outcome,
+                        RecursiveDownloadOutcome::Downloaded
+                    ) && child.get_download_config().preserve_mtime()
+                        && let Some(modified) = child_data.last_modified
+                    {
+                        created_dirs.push((
+                            report.entries.len(),
+                            local_path.clone(),
+                            modified,
+                        ));
+                    }
+
+                    report.push(&remote_path, local_path, true, outcome);
+                    continue;
+                }
+
+                // 达到并发上限时先等一个文件下载完
+                if tasks.len() >= max_concurrent_files
+                    && let Some(result) = tasks.join_next_with_id().await
+                {
+                    record_file_result(&mut report, &mut running, result);
+                }
+
+                let save_dir = local_dir.to_string_lossy().to_string();
+                let task_remote_path = remote_path.clone();
+                let task_local_path = local_path.clone();
+
+                let handle = tasks.spawn(async move {
+                    let result =
+                        child.download(&save_dir).await.map(|file| {
+                            file.get_outcome()
+                                .get_current_borrow()
+                                .clone()
+                                .flatten()
+                        });
+                    (task_remote_path, task_local_path, result)
+                });
+                running.insert(handle.id(), (remote_path, local_path));
+            }
+        }
+
+        while let Some(result) = tasks.join_next_with_id().await {
+            record_file_result(&mut report, &mut running, result);
+        }
+
+        if !options.include_empty_dirs {
+            prune_empty_dirs(&mut report).await;
+        }
+
+        // 文件夹里每写入一个文件修改时间都会变，所以等全部下载完再设置；
+        // 倒序处理，先子文件夹后父文件夹
+        for (index, path, modified) in created_dirs.into_iter().rev() {
+            if !matches!(
+                report.entries[index].outcome,
+                RecursiveDownloadOutcome::Downloaded
+            ) {
+                continue;
+            }
+
+            if let Err(e) = set_modified(&path, modified).await {
+                report.entries[index].outcome =
+                    RecursiveDownloadOutcome::Failed(e.into());
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,119 @@
+pub(crate) mod sequential_stream;
+
+use crate::resource_file::impl_traits::impl_download_stream::sequential_stream::{
+    SequentialStreamArgs, open_sequential_stream,
+};
+use crate::resource_file::structs::resource_byte_stream::ResourceByteStream;
+use crate::resource_file::structs::resources_file::ResourcesFile;
+use crate::resource_file::traits::download_stream::{
+    DownloadStream, DownloadStreamError,
+};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// 把字节流依次写入 `writer`
+async fn copy_to_writer<W>(
+    resources_file: &ResourcesFile,
+    writer: &mut W,
+) -> Result<u64, DownloadStreamError>
+where
+    W: AsyncWrite + Unpin + Send + ?Sized,
+{
+    let mut stream = resources_file.open_stream()?;
+    let mut written: u64 = 0;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(DownloadStreamError::Write)?;
+        written += chunk.len() as u64;
+    }
+
+    writer.flush().await.map_err(DownloadStreamError::Write)?;
+
+    Ok(written)
+}
+
+/// 把字节流读到内存里，超过 `max_size` 时立即停止
+async fn collect_bytes(
+    resources_file: &ResourcesFile,
+    max_size: u64,
+) -> Result<Vec<u8>, DownloadStreamError> {
+    let size = resources_file.get_data().size.unwrap_or(0);
+    let mut buffer = Vec::with_capacity(size.min(max_size) as usize);
+
+    let mut stream = resources_file.open_stream()?;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if buffer.len() as u64 + chunk.len() as u64 > max_size {
+            return Err(DownloadStreamError::TooLarge { max_size });
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(buffer)
+}
+
+#[async_trait]
+impl DownloadStream for ResourcesFile {
+    async fn download_to_writer<W>(
+        &self,
+        writer: &mut W,
+    ) -> Result<u64, DownloadStreamError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        // 与下载到文件一样先锁定，避免同时下载时进度互相覆盖
+        self.lock_file(false).await?;
+
+        let result = copy_to_writer(self, writer).await;
+
+        self.unlock_file(false).await?;
+
+        result
+    }
+
+    async fn download_to_bytes(
+        &self,
+        max_size: u64,
+    ) -> Result<Vec<u8>, DownloadStreamError> {
+        // 已知大小超过上限时不发请求
+        if let Some(size) = self.get_data().size
+            && size > max_size
+        {
+            return Err(DownloadStreamError::TooLarge { max_size });
+        }
+
+        self.lock_file(false).await?;
+
+        let result = collect_bytes(self, max_size).await;
+
+        self.unlock_file(false).await?;
+
+        result
+    }
+
+    fn open_stream(
+        &self,
+    ) -> Result<ResourceByteStream, DownloadStreamError> {
+        let data = self.get_data();
+        if data.is_dir {
+            return Err(DownloadStreamError::IsDir(
+                data.absolute_path.clone(),
+            ));
+        }
+
+        let args = SequentialStreamArgs {
+            http_client: self.get_http_client(),
+            resource_file_data: data,
+            inner_state: self.get_reactive_state(),
+            download_config: self.get_download_config(),
+        };
+
+        Ok(open_sequential_stream(args)?)
+    }
+}
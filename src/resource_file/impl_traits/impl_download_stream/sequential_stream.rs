@@ -0,0 +1,307 @@
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, idle_timeout, response_timeout,
+};
+use crate::reactive::reactive::ReactivePropertyError;
+use crate::resource_file::impl_traits::impl_download::retry::{
+    Retryable, backoff_delay, is_retryable_reqwest_error,
+    is_retryable_status,
+};
+use crate::resource_file::structs::download_config::ConnectionPermit;
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
+use crate::resource_file::structs::resource_byte_stream::ResourceByteStream;
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
+use crate::resource_file::structs::transfer_progress::TransferState;
+use crate::resource_file::traits::download::TDownloadConfig;
+use bytes::Bytes;
+use futures_util::{Stream, stream};
+use reqwest::header::{ACCEPT_ENCODING, IF_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::AcquireError;
+
+#[derive(Debug, Error)]
+pub enum SequentialStreamError {
+    #[error("HTTP 请求失败: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("请求超时: {0}")]
+    Timeout(#[from] RequestTimeoutError),
+
+    #[error("服务器返回异常状态码: {0}")]
+    UnexpectedStatus(u16),
+
+    #[error("更新下载进度失败: {0}")]
+    UpdateBytes(#[from] ReactivePropertyError),
+
+    #[error(transparent)]
+    RemoteChanged(#[from] RemoteChangedError),
+
+    #[error(
+        "响应提前结束，应读取 {expected} 字节，实际只有 {actual} 字节"
+    )]
+    Incomplete { expected: u64, actual: u64 },
+
+    #[error("无法获取连接许可: {0}")]
+    AcquireConnectionError(#[from] AcquireError),
+}
+
+impl Retryable for SequentialStreamError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            SequentialStreamError::Http(e) => {
+                is_retryable_reqwest_error(e)
+            }
+            SequentialStreamError::Timeout(_)
+            | SequentialStreamError::Incomplete { .. } => true,
+            SequentialStreamError::UnexpectedStatus(status) => {
+                StatusCode::from_u16(*status)
+                    .map(is_retryable_status)
+                    .unwrap_or(false)
+            }
+            SequentialStreamError::UpdateBytes(_)
+            | SequentialStreamError::RemoteChanged(_)
+            | SequentialStreamError::AcquireConnectionError(_) => false,
+        }
+    }
+}
+
+type TBodyStream =
+    Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>;
+
+pub(crate) struct SequentialStreamArgs {
+    pub(crate) http_client: Client,
+    pub(crate) resource_file_data: Arc<ResourceFileData>,
+    pub(crate) inner_state: ResourceFileProperty,
+    pub(crate) download_config: TDownloadConfig,
+}
+
+/// 顺序读取的状态，在 [`stream::unfold`] 的每一步之间传递
+struct SequentialStreamState {
+    args: SequentialStreamArgs,
+    body: Option<TBodyStream>,
+    connection: Option<ConnectionPermit>, // 和 body 一起持有，响应丢弃时归还
+    offset: u64,                        // 已经交给使用者的字节数
+    skip: u64,     // 服务器忽略 Range 时，响应开头需要丢掉的字节数
+    failures: u32, // 累计失败次数，读到新数据也不清零
+    validator: Option<RemoteValidator>, // 第一次请求时记录的远程文件版本
+    finished: bool,
+}
+
+impl SequentialStreamState {
+    /// 发出请求，从 `offset` 开始读取
+    async fn connect(
+        &mut self,
+    ) -> Result<TBodyStream, SequentialStreamError> {
+        let url = &self.args.resource_file_data.absolute_path;
+        // 断点续传按原始字节的偏移计算，不能被压缩
+        let mut request = self
+            .args
+            .http_client
+            .get(url)
+            .header(ACCEPT_ENCODING, "identity");
+
+        let if_range =
+            self.validator.as_ref().and_then(RemoteValidator::if_range);
+
+        if self.offset > 0 {
+            request =
+                request.header(RANGE, format!("bytes={}-", self.offset));
+
+            if let Some(if_range) = &if_range {
+                request = request.header(IF_RANGE, if_range);
+            }
+        }
+
+        // 按主机策略占用连接名额，连接成功后和响应体一起保存
+        let connection =
+            self.args.download_config.acquire_connection(url).await?;
+        self.args.download_config.wait_request_slot(url).await;
+
+        let timeout_secs = self.args.download_config.timeout_secs();
+        let resp =
+            response_timeout(timeout_secs, request.send()).await??;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(SequentialStreamError::UnexpectedStatus(
+                status.as_u16(),
+            ));
+        }
+
+        match &self.validator {
+            Some(validator) if self.offset > 0 => {
+                validator.check_ranged_response(
+                    url,
+                    status,
+                    resp.headers(),
+                )?;
+            }
+            _ => {
+                let current =
+                    RemoteValidator::from_headers(resp.headers());
+                self.validator =
+                    Some(if current == RemoteValidator::default() {
+                        RemoteValidator::from_data(
+                            &self.args.resource_file_data,
+                        )
+                    } else {
+                        current
+                    });
+            }
+        }
+
+        // 服务器忽略了 Range 但版本没变，跳过已经交出去的部分
+        self.skip = if status == StatusCode::PARTIAL_CONTENT {
+            0
+        } else {
+            self.offset
+        };
+
+        self.connection = Some(connection);
+
+        Ok(Box::pin(resp.bytes_stream()))
+    }
+
+    /// 响应体结束时检查是否读到了文件末尾
+    ///
+    /// 连接被正常关闭时响应体也会结束，大小已知的文件没读完就当作可以重试的错误，
+    /// 否则使用者会把截断的数据当成完整的文件。
+    fn check_complete(&self) -> Result<(), SequentialStreamError> {
+        match self.args.resource_file_data.size {
+            Some(size) if self.offset < size => {
+                Err(SequentialStreamError::Incomplete {
+                    expected: size,
+                    actual: self.offset,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 读取下一块交给使用者的数据，`None` 表示读完了
+    async fn next_chunk(
+        &mut self,
+    ) -> Result<Option<Bytes>, SequentialStreamError> {
+        loop {
+            // 出错时响应随之丢弃，下一次重新连接
+            let mut body = match self.body.take() {
+                Some(body) => body,
+                None => self.connect().await?,
+            };
+
+            let timeout_secs = self.args.download_config.timeout_secs();
+            let Some(chunk) = idle_timeout(timeout_secs, &mut body).await?
+            else {
+                self.check_complete()?;
+                return Ok(None);
+            };
+            let mut chunk = chunk?;
+            self.body = Some(body);
+
+            if self.skip > 0 {
+                let skipped = self.skip.min(chunk.len() as u64);
+                self.skip -= skipped;
+                chunk = chunk.slice(skipped as usize..);
+            }
+
+            if chunk.is_empty() {
+                continue;
+            }
+
+            // 资源、账号、全局任意一层暂停都会在这里等待
+            self.args
+                .inner_state
+                .reporter
+                .wait_if_paused(&self.args.download_config, None)
+                .await?;
+
+            // 按资源、账号、全局三层限速等待
+            self.args.download_config.throttle(chunk.len() as u64).await;
+
+            self.args.inner_state.reporter.add(None, chunk.len() as u64)?;
+
+            self.offset += chunk.len() as u64;
+
+            return Ok(Some(chunk));
+        }
+    }
+
+    /// 读取下一块数据，可以重试的错误在这里带着 Range 重连
+    async fn next_with_retry(
+        &mut self,
+    ) -> Result<Option<Bytes>, SequentialStreamError> {
+        loop {
+            let error = match self.next_chunk().await {
+                Ok(Some(chunk)) => return Ok(Some(chunk)),
+                Ok(None) => {
+                    self.connection = None;
+                    self.args.inner_state.reporter.finish(true)?;
+                    return Ok(None);
+                }
+                Err(e) => e,
+            };
+
+            // 带着 Range 重新连接，等待重试期间不占用连接名额
+            self.body = None;
+            self.connection = None;
+
+            // 每次都重新读取，运行中修改重试次数会立即生效
+            let max_retries =
+                self.args.download_config.max_retries().unwrap_or(0);
+
+            if !error.is_retryable() || self.failures >= max_retries {
+                self.args.inner_state.reporter.finish(false)?;
+                return Err(error);
+            }
+
+            self.failures += 1;
+            self.args.inner_state.reporter.set_state(
+                None,
+                TransferState::Retrying(self.failures),
+            )?;
+            tokio::time::sleep(backoff_delay(self.failures)).await;
+        }
+    }
+}
+
+/// 打开顺序读取的字节流，进度从 0 开始计算
+///
+/// 请求在第一次读取时才发出，出错后字节流结束。
+pub(crate) fn open_sequential_stream(
+    args: SequentialStreamArgs,
+) -> Result<ResourceByteStream, SequentialStreamError> {
+    args.inner_state.reporter.start(args.resource_file_data.size)?;
+
+    let state = SequentialStreamState {
+        args,
+        body: None,
+        connection: None,
+        offset: 0,
+        skip: 0,
+        failures: 0,
+        validator: None,
+        finished: false,
+    };
+
+    let stream = stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+
+        match state.next_with_retry().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), state)),
+            Ok(None) => None,
+            Err(e) => {
+                state.finished = true;
+                Some((Err(e.into()), state))
+            }
+        }
+    });
+
+    Ok(ResourceByteStream::new(stream))
+}
@@ -0,0 +1,38 @@
+pub(crate) mod fetch_block;
+
+use crate::resource_file::structs::remote_reader::{
+    RemoteReader, RemoteReaderOptions,
+};
+use crate::resource_file::structs::remote_validator::RemoteValidator;
+use crate::resource_file::structs::resources_file::ResourcesFile;
+use crate::resource_file::traits::open_reader::{
+    OpenReader, OpenReaderError,
+};
+
+impl OpenReader for ResourcesFile {
+    fn open_reader_with_options(
+        &self,
+        options: RemoteReaderOptions,
+    ) -> Result<RemoteReader, OpenReaderError> {
+        let data = self.get_data();
+        if data.is_dir {
+            return Err(OpenReaderError::IsDir(
+                data.absolute_path.clone(),
+            ));
+        }
+
+        let total_size = data.size.ok_or_else(|| {
+            OpenReaderError::UnknownFileSize(data.absolute_path.clone())
+        })?;
+
+        // 按列目录时的版本读取，每个 Range 请求都带上 If-Range
+        Ok(RemoteReader::new(
+            self.get_http_client(),
+            &data.absolute_path,
+            total_size,
+            RemoteValidator::from_data(&data),
+            self.get_download_config(),
+            options,
+        ))
+    }
+}
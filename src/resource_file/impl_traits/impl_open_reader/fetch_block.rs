@@ -0,0 +1,178 @@
+use crate::client::webdav_request::timeout::{
+    RequestTimeoutError, idle_timeout,
+};
+use crate::resource_file::impl_traits::impl_download::chunked_download::http_stream::{
+    FetchRangeArgs, FetchRangeError, fetch_range_method,
+};
+use crate::resource_file::impl_traits::impl_download::chunked_download::range_probe::{
+    RangeResponseError, check_range_response,
+};
+use crate::resource_file::impl_traits::impl_download::retry::{
+    Retryable, backoff_delay, is_retryable_reqwest_error,
+    is_retryable_status,
+};
+use crate::resource_file::structs::remote_validator::{
+    RemoteChangedError, RemoteValidator,
+};
+use crate::resource_file::traits::download::TDownloadConfig;
+use bytes::{Bytes, BytesMut};
+use reqwest::{Client, StatusCode};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::AcquireError;
+
+#[derive(Debug, Error)]
+pub enum FetchBlockError {
+    #[error("获取区间失败: {0}")]
+    Fetch(#[from] FetchRangeError),
+
+    #[error("服务器返回异常状态码: {0}")]
+    UnexpectedStatus(u16),
+
+    #[error("读取数据流出错: {0}")]
+    Stream(#[from] reqwest::Error),
+
+    #[error("读取区间超时: {0}")]
+    Timeout(#[from] RequestTimeoutError),
+
+    #[error(transparent)]
+    RemoteChanged(#[from] RemoteChangedError),
+
+    #[error(transparent)]
+    RangeNotSupported(#[from] RangeResponseError),
+
+    #[error("区间数据不完整，应为 {expected} 字节，实际为 {actual} 字节")]
+    Incomplete { expected: u64, actual: u64 },
+
+    #[error("无法获取连接许可: {0}")]
+    AcquireConnectionError(#[from] AcquireError),
+}
+
+impl Retryable for FetchBlockError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            FetchBlockError::Fetch(FetchRangeError::Http(e))
+            | FetchBlockError::Stream(e) => is_retryable_reqwest_error(e),
+            FetchBlockError::Fetch(FetchRangeError::Timeout(_))
+            | FetchBlockError::Timeout(_)
+            | FetchBlockError::Incomplete { .. } => true,
+            FetchBlockError::UnexpectedStatus(status) => {
+                StatusCode::from_u16(*status)
+                    .map(is_retryable_status)
+                    .unwrap_or(false)
+            }
+            FetchBlockError::RemoteChanged(_)
+            | FetchBlockError::RangeNotSupported(_)
+            | FetchBlockError::AcquireConnectionError(_) => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FetchBlockArgs {
+    pub(crate) http_client: Client,
+    pub(crate) file_url: Arc<str>,
+    pub(crate) start: u64,
+    pub(crate) end: u64, // 最后一个字节的位置（包含）
+    pub(crate) total_size: u64,
+    pub(crate) validator: Arc<RemoteValidator>,
+    pub(crate) download_config: TDownloadConfig,
+}
+
+/// 请求一次区间，把响应体完整读到内存里
+async fn fetch_block_once(
+    args: &FetchBlockArgs,
+) -> Result<Bytes, FetchBlockError> {
+    let range_header_str = format!("bytes={}-{}", args.start, args.end);
+
+    // 超时每次请求前重新读取，修改配置后下一个请求立即生效
+    let timeout_secs = args.download_config.timeout_secs();
+
+    let fetch_range_args = FetchRangeArgs {
+        http_client: &args.http_client,
+        range_header_str: &range_header_str,
+        file_url: &args.file_url,
+        if_range: args.validator.if_range(),
+        timeout_secs,
+    };
+
+    // 按主机策略占用连接名额，整个响应体读完才归还
+    let _connection =
+        args.download_config.acquire_connection(&args.file_url).await?;
+    args.download_config.wait_request_slot(&args.file_url).await;
+
+    let resp = fetch_range_method(fetch_range_args).await?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(FetchBlockError::UnexpectedStatus(status.as_u16()));
+    }
+
+    // 缓存里可能还有旧版本的块，远程文件变了只能报错
+    args.validator.check_ranged_response(
+        &args.file_url,
+        status,
+        resp.headers(),
+    )?;
+
+    check_range_response(
+        status,
+        resp.headers(),
+        args.start,
+        args.end,
+        args.total_size,
+    )?;
+
+    let mut buffer =
+        BytesMut::with_capacity((args.end - args.start + 1) as usize);
+    let mut body = resp.bytes_stream();
+
+    while let Some(chunk) = idle_timeout(timeout_secs, &mut body).await?
+    {
+        let chunk = chunk?;
+
+        // 资源、账号、全局任意一层暂停都会在这里等待
+        args.download_config.wait_if_paused().await;
+
+        // 按资源、账号、全局三层限速等待
+        args.download_config.throttle(chunk.len() as u64).await;
+
+        buffer.extend_from_slice(&chunk);
+    }
+
+    let expected = args.end - args.start + 1;
+    if buffer.len() as u64 != expected {
+        return Err(FetchBlockError::Incomplete {
+            expected,
+            actual: buffer.len() as u64,
+        });
+    }
+
+    Ok(buffer.freeze())
+}
+
+/// 读取 `start..=end` 区间，可以重试的错误按配置重新请求整个区间
+///
+/// 区间只有几个块大小，失败后不再按已读到的位置续传。
+pub(crate) async fn fetch_block(
+    args: FetchBlockArgs,
+) -> Result<Bytes, FetchBlockError> {
+    let mut failures: u32 = 0;
+
+    loop {
+        let error = match fetch_block_once(&args).await {
+            Ok(bytes) => return Ok(bytes),
+            Err(e) => e,
+        };
+
+        // 每次都重新读取，运行中修改重试次数会立即生效
+        let max_retries = args.download_config.max_retries().unwrap_or(0);
+
+        if !error.is_retryable() || failures >= max_retries {
+            return Err(error);
+        }
+
+        failures += 1;
+        tokio::time::sleep(backoff_delay(failures)).await;
+    }
+}
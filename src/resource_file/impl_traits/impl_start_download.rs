@@ -0,0 +1,18 @@
+use crate::resource_file::structs::download_handle::DownloadHandle;
+use crate::resource_file::structs::resources_file::ResourcesFile;
+use crate::resource_file::traits::download::Download;
+use crate::resource_file::traits::start_download::StartDownload;
+
+impl StartDownload for ResourcesFile {
+    fn start_download(self, output_absolute_path: &str) -> DownloadHandle {
+        // 句柄持有一份克隆，和后台任务共享进度、文件锁和配置
+        let resources_file = self.clone();
+        let output_absolute_path = output_absolute_path.to_string();
+
+        let task = tokio::spawn(async move {
+            self.download(&output_absolute_path).await
+        });
+
+        DownloadHandle::new(resources_file, task)
+    }
+}
@@ -4,3 +4,24 @@ pub mod resources_file;
 pub mod resource_file_property;
 
 pub mod resource_config;
+
+pub mod download_config;
+
+
+pub mod remote_validator;
+
+pub mod checksum;
+
+pub mod resource_byte_stream;
+
+pub mod remote_reader;
+
+pub mod recursive_download;
+
+pub mod transfer_progress;
+
+pub(crate) mod progress_reporter;
+
+pub mod download_handle;
+
+pub mod adaptive_chunking;
\ No newline at end of file
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// # 自适应分片参数
+///
+/// 下载过程中测量每个连接的吞吐量和延迟：
+/// 分片大小按吞吐量换算，让每个请求持续大约 `target_request_ms` 毫秒，
+/// 并且建立连接的延迟只占请求时间的一小部分；
+/// 并发连接数从 `min_connections` 开始逐个增加，
+/// 总吞吐量不再明显提升时退回一个，吞吐量明显下降时继续减少。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveChunking {
+    pub min_chunk_size: u64, // 分片大小下限，第一批分片使用这个大小
+    pub max_chunk_size: u64, // 分片大小上限
+    pub min_connections: u32, // 并发连接数下限，也是开始时的连接数
+    pub max_connections: u32, // 并发连接数上限
+    pub target_request_ms: u64, // 期望每个分片请求持续的时间
+}
+
+impl Default for AdaptiveChunking {
+    fn default() -> Self {
+        Self {
+            min_chunk_size: 1024 * 1024,
+            max_chunk_size: 64 * 1024 * 1024,
+            min_connections: 1,
+            max_connections: 8,
+            target_request_ms: 5000,
+        }
+    }
+}
+
+impl AdaptiveChunking {
+    /// 修正不合理的参数：下限至少为 1，上限不小于下限
+    pub fn normalized(&self) -> Self {
+        let min_chunk_size = self.min_chunk_size.max(1);
+        let min_connections = self.min_connections.max(1);
+
+        Self {
+            min_chunk_size,
+            max_chunk_size: self.max_chunk_size.max(min_chunk_size),
+            min_connections,
+            max_connections: self.max_connections.max(min_connections),
+            target_request_ms: self.target_request_ms,
+        }
+    }
+}
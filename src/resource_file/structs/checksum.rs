@@ -0,0 +1,151 @@
+use crate::resource_file::enums::checksum_algorithm::ChecksumAlgorithm;
+use base64::Engine;
+use reqwest::header::HeaderMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// 服务器提供的校验值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String, // 小写十六进制
+}
+
+/// 下载过程中计算出的摘要，均为小写十六进制
+///
+/// SHA-256 总是会计算；MD5 和 SHA-1 只在服务器提供了对应的校验值时才计算，
+/// 没有计算时为 `None`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHash {
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: String,
+}
+
+impl FileHash {
+    /// 指定算法的摘要，没有计算过这种算法时返回 `None`
+    pub fn get(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => self.md5.as_deref(),
+            ChecksumAlgorithm::Sha1 => self.sha1.as_deref(),
+            ChecksumAlgorithm::Sha256 => Some(&self.sha256),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum IntegrityError {
+    /// 下载得到的文件大小与服务器记录的不一致
+    #[error(
+        "文件[{path}] 大小不正确，应为 {expected} 字节，实际为 {actual} 字节"
+    )]
+    SizeMismatch { path: PathBuf, expected: u64, actual: u64 },
+
+    /// 计算出的摘要与服务器提供的校验值不一致
+    #[error(
+        "文件[{path}] {} 校验失败，应为 {expected}，实际为 {actual}",
+        algorithm.as_str()
+    )]
+    ChecksumMismatch {
+        path: PathBuf,
+        algorithm: ChecksumAlgorithm,
+        expected: String,
+        actual: String,
+    },
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 十六进制原样转成小写，否则按 base64 解码后再转成十六进制
+fn normalize_value(value: &str, hex_len: usize) -> Option<String> {
+    let value = value.trim();
+
+    if value.len() == hex_len
+        && value.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Some(value.to_ascii_lowercase());
+    }
+
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .ok()
+        .filter(|bytes| bytes.len() * 2 == hex_len)
+        .map(|bytes| to_hex(&bytes))
+}
+
+impl Checksum {
+    fn new(algorithm: ChecksumAlgorithm, value: &str) -> Option<Self> {
+        let hex_len = match algorithm {
+            ChecksumAlgorithm::Md5 => 32,
+            ChecksumAlgorithm::Sha1 => 40,
+            ChecksumAlgorithm::Sha256 => 64,
+        };
+
+        normalize_value(value, hex_len)
+            .map(|value| Self { algorithm, value })
+    }
+
+    /// 解析 `oc:checksums` 和 `OC-Checksum` 使用的 `SHA1:abc MD5:def` 格式，
+    /// 不支持的算法（如 ADLER32）会被忽略
+    pub fn parse_list(raw: &str) -> Vec<Checksum> {
+        raw.split_whitespace()
+            .filter_map(|item| item.split_once(':'))
+            .filter_map(|(name, value)| {
+                Self::new(ChecksumAlgorithm::from_name(name)?, value)
+            })
+            .collect()
+    }
+
+    /// 从响应头里读取校验值：`OC-Checksum`、`Digest`（RFC 3230）、`Content-MD5`
+    ///
+    /// 只应该对完整文件的响应调用，206 响应里的校验值只对应其中一段。
+    ///
+    /// `Digest` 和 `Content-MD5` 是按传输的实体计算的，
+    /// 响应带有 `Content-Encoding` 时与解码后的文件内容对不上，会被忽略；
+    /// `OC-Checksum` 对应的是文件本身，不受影响。
+    pub fn from_headers(headers: &HeaderMap) -> Vec<Checksum> {
+        let header = |name: &str| {
+            headers.get(name).and_then(|value| value.to_str().ok())
+        };
+
+        let mut checksums = vec![];
+
+        if let Some(raw) = header("oc-checksum") {
+            checksums.extend(Self::parse_list(raw));
+        }
+
+        let encoded = header("content-encoding").is_some_and(|encoding| {
+            !encoding.trim().eq_ignore_ascii_case("identity")
+        });
+        if encoded {
+            return checksums;
+        }
+
+        if let Some(raw) = header("digest") {
+            checksums.extend(
+                raw.split(',')
+                    .filter_map(|item| item.split_once('='))
+                    .filter_map(|(name, value)| {
+                        Self::new(
+                            ChecksumAlgorithm::from_name(name)?,
+                            value,
+                        )
+                    }),
+            );
+        }
+
+        if let Some(raw) = header("content-md5") {
+            checksums.extend(Self::new(ChecksumAlgorithm::Md5, raw));
+        }
+
+        checksums
+    }
+
+    /// 解析同名 `.sha256` 文件，格式与 `sha256sum` 的输出一致：`<摘要>  <文件名>`
+    pub fn parse_sidecar(text: &str) -> Option<Checksum> {
+        let value = text.split_whitespace().next()?;
+        Self::new(ChecksumAlgorithm::Sha256, value)
+    }
+}
@@ -0,0 +1,297 @@
+use crate::client::structs::account_config::{
+    AccountConfig, AccountConfigData,
+};
+use crate::global_config::global_config::{ConfigData, GlobalConfig};
+use crate::resource_file::enums::chunk_strategy::ChunkStrategy;
+use crate::resource_file::enums::conflict_policy::ConflictPolicy;
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use crate::resource_file::structs::resource_config::{
+    ResourceConfig, ResourceConfigData,
+};
+use std::sync::Arc;
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+/// 一个连接占用的名额，drop 时全部归还
+#[derive(Debug)]
+pub(crate) struct ConnectionPermit {
+    _host: Option<OwnedSemaphorePermit>,
+    _manager: Option<OwnedSemaphorePermit>,
+}
+
+/// # 下载配置
+///
+/// 把资源、账号、全局三层配置组合在一起，
+/// 每次读取时按 资源 → 账号 → 全局 的顺序取第一个有值的配置。
+///
+/// 内部只保存三层配置的共享引用，不缓存任何值，
+/// 所以下载过程中修改任意一层配置都会在下一次读取时生效。
+#[derive(Debug, Clone)]
+pub struct DownloadConfig {
+    global_config: GlobalConfig,
+    account_config: AccountConfig,
+    resource_config: ResourceConfig,
+    connection_limiter: Option<Arc<Semaphore>>, // 传输管理器的总连接数限制
+}
+
+impl DownloadConfig {
+    pub fn new(
+        global_config: GlobalConfig,
+        account_config: AccountConfig,
+        resource_config: ResourceConfig,
+    ) -> Self {
+        Self {
+            global_config,
+            account_config,
+            resource_config,
+            connection_limiter: None,
+        }
+    }
+
+    /// 与其它下载共用同一个连接数限制，由传输管理器设置
+    pub(crate) fn with_connection_limiter(
+        mut self,
+        connection_limiter: Option<Arc<Semaphore>>,
+    ) -> Self {
+        self.connection_limiter = connection_limiter;
+        self
+    }
+
+    pub fn get_global_config(&self) -> &GlobalConfig {
+        &self.global_config
+    }
+
+    pub fn get_account_config(&self) -> &AccountConfig {
+        &self.account_config
+    }
+
+    pub fn get_resource_config(&self) -> &ResourceConfig {
+        &self.resource_config
+    }
+
+    /// 按 资源 → 账号 → 全局 的顺序解析某个配置项
+    fn resolve<R>(
+        &self,
+        resource: impl FnOnce(&ResourceConfigData) -> Option<R>,
+        account: impl FnOnce(&AccountConfigData) -> Option<R>,
+        global: impl FnOnce(&ConfigData) -> Option<R>,
+    ) -> Option<R> {
+        if let Some(value) = self
+            .resource_config
+            .get_current_borrow()
+            .as_ref()
+            .and_then(resource)
+        {
+            return Some(value);
+        }
+
+        if let Some(value) = self
+            .account_config
+            .get_current_borrow()
+            .as_ref()
+            .and_then(account)
+        {
+            return Some(value);
+        }
+
+        self.global_config.get_current_borrow().as_ref().and_then(global)
+    }
+
+    /// 当前生效的最大下载速度，`None` 表示不限速
+    ///
+    /// 限速不走覆盖逻辑：资源、账号、全局三层同时生效，取其中最小的一个。
+    pub fn max_speed(&self) -> Option<u64> {
+        [
+            self.resource_config.max_speed(),
+            self.account_config.max_speed(),
+            self.global_config.max_speed(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// 按三层限速依次消耗令牌，读到一块数据后、写入之前调用
+    ///
+    /// 每一层都有自己的令牌桶：全局桶被所有下载共用，账号桶被该账号的下载共用，
+    /// 文件桶被该文件的所有分片共用。
+    pub async fn throttle(&self, bytes: u64) {
+        self.global_config
+            .get_speed_limiter()
+            .acquire(bytes, || self.global_config.max_speed())
+            .await;
+
+        self.account_config
+            .get_speed_limiter()
+            .acquire(bytes, || self.account_config.max_speed())
+            .await;
+
+        self.resource_config
+            .get_speed_limiter()
+            .acquire(bytes, || self.resource_config.max_speed())
+            .await;
+    }
+
+    /// 超时时间（秒），`None` 表示全局配置未初始化
+    pub fn timeout_secs(&self) -> Option<u64> {
+        self.resolve(
+            |r| r.timeout_secs,
+            |a| a.timeout_secs,
+            |g| Some(g.timeout_secs),
+        )
+    }
+
+    /// 最大重试次数，`None` 表示全局配置未初始化
+    pub fn max_retries(&self) -> Option<u32> {
+        self.resolve(
+            |r| r.max_retries,
+            |a| a.max_retries,
+            |g| Some(g.max_retries),
+        )
+    }
+
+    /// 大文件阈值，`None` 表示全局配置未初始化
+    pub fn large_file_threshold(&self) -> Option<u64> {
+        self.resolve(
+            |r| r.large_file_threshold,
+            |a| a.large_file_threshold,
+            |g| Some(g.large_file_threshold),
+        )
+    }
+
+    /// 最大线程数，全局没有这一项，`None` 表示按文件大小自动计算
+    pub fn max_thread_count(&self) -> Option<u32> {
+        self.resolve(
+            |r| r.max_thread_count,
+            |a| a.max_thread_count,
+            |_| None,
+        )
+    }
+
+    /// 分片大小和并发数的调整方式
+    pub fn chunk_strategy(&self) -> ChunkStrategy {
+        self.resolve(
+            |r| r.chunk_strategy,
+            |a| a.chunk_strategy,
+            |g| Some(g.chunk_strategy),
+        )
+        .unwrap_or_default()
+    }
+
+    /// 续传时发现远程文件变化的处理方式
+    pub fn remote_changed_policy(&self) -> RemoteChangedPolicy {
+        self.resolve(
+            |r| r.remote_changed_policy,
+            |a| a.remote_changed_policy,
+            |g| Some(g.remote_changed_policy),
+        )
+        .unwrap_or_default()
+    }
+
+    /// 是否先下载到临时文件，完成后再替换目标文件
+    pub fn atomic_write(&self) -> bool {
+        self.resolve(
+            |r| r.atomic_write,
+            |a| a.atomic_write,
+            |g| Some(g.atomic_write),
+        )
+        .unwrap_or(false)
+    }
+
+    /// 是否读取同名 `.sha256` 文件校验下载结果
+    pub fn sidecar_checksum(&self) -> bool {
+        self.resolve(
+            |r| r.sidecar_checksum,
+            |a| a.sidecar_checksum,
+            |g| Some(g.sidecar_checksum),
+        )
+        .unwrap_or(false)
+    }
+
+    /// 本地目标文件已经存在时的处理方式
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        self.resolve(
+            |r| r.conflict_policy,
+            |a| a.conflict_policy,
+            |g| Some(g.conflict_policy),
+        )
+        .unwrap_or_default()
+    }
+
+    /// 是否把本地修改时间设为远程文件的修改时间
+    pub fn preserve_mtime(&self) -> bool {
+        self.resolve(
+            |r| r.preserve_mtime,
+            |a| a.preserve_mtime,
+            |g| Some(g.preserve_mtime),
+        )
+        .unwrap_or(false)
+    }
+
+    /// 是否把没有写权限的远程文件在本地设为只读
+    pub fn preserve_readonly(&self) -> bool {
+        self.resolve(
+            |r| r.preserve_readonly,
+            |a| a.preserve_readonly,
+            |g| Some(g.preserve_readonly),
+        )
+        .unwrap_or(false)
+    }
+
+    /// 任意一层处于暂停状态都算暂停
+    pub fn is_paused(&self) -> bool {
+        self.global_config.is_paused()
+            || self.account_config.is_paused()
+            || self.resource_config.is_paused()
+    }
+
+    /// 占用一个连接名额，返回的许可 drop 时归还
+    ///
+    /// 先占用 `url` 所属主机策略的名额，再占用传输管理器的总名额，
+    /// 等待某个主机时不会占着其它主机也要用的总名额。
+    /// 没有限制的一层不占用名额。
+    pub(crate) async fn acquire_connection(
+        &self,
+        url: &str,
+    ) -> Result<ConnectionPermit, AcquireError> {
+        let host = self
+            .global_config
+            .get_host_policies()
+            .acquire_connection(url)
+            .await?;
+
+        let manager = match &self.connection_limiter {
+            Some(limiter) => Some(limiter.clone().acquire_owned().await?),
+            None => None,
+        };
+
+        Ok(ConnectionPermit { _host: host, _manager: manager })
+    }
+
+    /// 每次向 `url` 发出请求前调用，按主机策略限制请求频率
+    pub(crate) async fn wait_request_slot(&self, url: &str) {
+        self.global_config
+            .get_host_policies()
+            .wait_request_slot(url)
+            .await;
+    }
+
+    /// 如果处于暂停状态，则一直等待到三层都恢复为止
+    pub async fn wait_if_paused(&self) {
+        loop {
+            // 先订阅再检查状态，避免检查之后、等待之前的恢复通知被漏掉
+            let mut global_watch = self.global_config.watch();
+            let mut account_watch = self.account_config.watch();
+            let mut resource_watch = self.resource_config.watch();
+
+            if !self.is_paused() {
+                return;
+            }
+
+            tokio::select! {
+                _ = global_watch.changed() => {}
+                _ = account_watch.changed() => {}
+                _ = resource_watch.changed() => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::reactive::reactive::{PropertyWatcher, ReactivePropertyError};
+use crate::resource_file::impl_traits::impl_download::atomic_write::temp_path;
+use crate::resource_file::impl_traits::impl_download::chunked_download::journal::journal_path;
+use crate::resource_file::structs::resource_config::ResourceConfigError;
+use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
+use crate::resource_file::structs::resources_file::{
+    ResourcesFile, UnlockFileError,
+};
+use crate::resource_file::structs::transfer_progress::TransferProgress;
+use crate::resource_file::traits::download::DownloadError;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use thiserror::Error;
+use tokio::task::{JoinError, JoinHandle};
+
+type TDownloadTask = JoinHandle<Result<Arc<ResourcesFile>, DownloadError>>;
+
+#[derive(Debug, Error)]
+pub enum DownloadHandleError {
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+
+    #[error("下载已被取消")]
+    Cancelled,
+
+    #[error("下载任务异常退出: {0}")]
+    Join(JoinError),
+}
+
+#[derive(Debug, Error)]
+pub enum CancelDownloadError {
+    #[error("取消后解锁文件失败: {0}")]
+    UnlockFileError(#[from] UnlockFileError),
+
+    #[error("删除未完成的文件[{0}] 失败: {1}")]
+    RemovePartialFile(PathBuf, std::io::Error),
+
+    #[error("更新下载进度失败: {0}")]
+    UpdateProgress(#[from] ReactivePropertyError),
+}
+
+/// 删除文件，文件不存在时什么也不做
+pub(crate) async fn remove_if_exists(
+    path: &Path,
+) -> Result<(), CancelDownloadError> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(
+            CancelDownloadError::RemovePartialFile(path.to_path_buf(), e),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// 删除最近一次下载留下的未完成文件和分片记录，已经完整落地的文件不会被删除
+pub(crate) async fn remove_partial_files(
+    inner_state: &ResourceFileProperty,
+) -> Result<(), CancelDownloadError> {
+    let partial_path =
+        inner_state.partial_path.get_current().and_then(|p| (*p).clone());
+
+    if let Some(path) = partial_path {
+        remove_if_exists(&path).await?;
+        remove_if_exists(&journal_path(&path)).await?;
+        inner_state.partial_path.update(None)?;
+    }
+
+    Ok(())
+}
+
+/// 删除下载到 `target` 时留下的未完成文件
+///
+/// 只删除旁边有分片记录的文件（临时文件或目标文件本身），
+/// 没有记录的文件无法确定是不是未完成的下载。
+pub(crate) async fn remove_journaled_files(
+    target: &Path,
+) -> Result<(), CancelDownloadError> {
+    for path in [temp_path(target), target.to_path_buf()] {
+        let journal = journal_path(&path);
+        if tokio::fs::try_exists(&journal).await.unwrap_or(false) {
+            remove_if_exists(&path).await?;
+            remove_if_exists(&journal).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// # 下载句柄
+///
+/// 由 [`StartDownload::start_download`](crate::resource_file::traits::start_download::StartDownload::start_download)
+/// 返回，下载在后台任务里进行。直接 `.await` 句柄即可拿到下载结果。
+///
+/// 丢弃句柄不会停止下载，需要停止时调用 [`cancel`](Self::cancel)。
+#[derive(Debug)]
+pub struct DownloadHandle {
+    resources_file: ResourcesFile, // 与后台任务共享状态和配置
+    task: TDownloadTask,
+}
+
+impl DownloadHandle {
+    pub(crate) fn new(
+        resources_file: ResourcesFile,
+        task: TDownloadTask,
+    ) -> Self {
+        Self { resources_file, task }
+    }
+
+    /// 暂停这次下载，等同于暂停该文件的 `ResourceConfig`
+    pub fn pause(&self) -> Result<(), ResourceConfigError> {
+        self.resources_file.get_reactive_config().pause()?;
+        Ok(())
+    }
+
+    /// 恢复这次下载，账号或全局仍处于暂停状态时不会继续
+    pub fn resume(&self) -> Result<(), ResourceConfigError> {
+        self.resources_file.get_reactive_config().resume()?;
+        Ok(())
+    }
+
+    /// 监听传输进度
+    pub fn progress(&self) -> PropertyWatcher<TransferProgress> {
+        self.resources_file.get_progress().watch()
+    }
+
+    /// 后台任务是否已经结束（成功、失败或被取消）
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// 取消下载
+    ///
+    /// 后台任务和它启动的所有分片任务都会被中止，文件锁强制解除。
+    /// `keep_partial` 为 `true` 时保留已经写入的文件和分片记录，下次下载可以续传；
+    /// 为 `false` 时一起删除。已经完整落地的文件不会被删除。
+    pub async fn cancel(
+        self,
+        keep_partial: bool,
+    ) -> Result<(), CancelDownloadError> {
+        self.task.abort();
+
+        // 等后台任务真正退出，分片任务跟着它一起被取消；
+        // 已经结束的任务会返回原来的结果，这里不再关心
+        let _ = self.task.await;
+
+        let inner_state = self.resources_file.get_reactive_state();
+
+        // 中止时没有机会走正常的解锁流程
+        self.resources_file.unlock_file(true).await?;
+
+        let finished = inner_state
+            .get_progress()
+            .get_current()
+            .is_some_and(|progress| progress.state.is_finished());
+        if !finished {
+            inner_state.reporter.cancel()?;
+        }
+
+        if !keep_partial {
+            remove_partial_files(&inner_state).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Future for DownloadHandle {
+    type Output = Result<Arc<ResourcesFile>, DownloadHandleError>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        Pin::new(&mut self.task).poll(cx).map(|result| match result {
+            Ok(result) => result.map_err(DownloadHandleError::Download),
+            Err(e) if e.is_cancelled() => {
+                Err(DownloadHandleError::Cancelled)
+            }
+            Err(e) => Err(DownloadHandleError::Join(e)),
+        })
+    }
+}
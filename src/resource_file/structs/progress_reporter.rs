@@ -0,0 +1,379 @@
+use crate::reactive::reactive::{ReactiveProperty, ReactivePropertyError};
+use crate::resource_file::structs::download_config::DownloadConfig;
+use crate::resource_file::structs::transfer_progress::{
+    ChunkProgress, TransferProgress, TransferState,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+// 两次采样之间的最短间隔，间隔太短时瞬时速度抖动很大，
+// 收到数据时也按这个间隔发布快照
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+// 新采样在瞬时速度里的权重，越大越灵敏，越小越平滑
+const SPEED_SMOOTHING: f64 = 0.3;
+
+#[derive(Debug)]
+struct SpeedMeter {
+    started_at: Instant,
+    started_bytes: u64, // 本次开始时已有的字节数，平均速度不算这部分
+    sample_at: Instant,
+    sample_bytes: u64,
+}
+
+impl SpeedMeter {
+    fn new(done_bytes: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            started_at: now,
+            started_bytes: done_bytes,
+            sample_at: now,
+            sample_bytes: done_bytes,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ProgressState {
+    progress: TransferProgress,
+    meter: SpeedMeter,
+    published_at: Option<Instant>, // 上一次发布快照的时间
+}
+
+impl ProgressState {
+    /// 收到数据时是否需要发布快照，距离上一次发布不到采样间隔时跳过
+    fn should_publish(&self) -> bool {
+        self.published_at
+            .is_none_or(|at| at.elapsed() >= SAMPLE_INTERVAL)
+    }
+
+    /// 按已完成的字节数重新计算速度和剩余时间
+    fn refresh_speed(&mut self) {
+        let now = Instant::now();
+        let done = self.progress.done_bytes;
+
+        let elapsed = now.duration_since(self.meter.sample_at);
+        if elapsed >= SAMPLE_INTERVAL {
+            let sampled = done.saturating_sub(self.meter.sample_bytes);
+            let rate = sampled as f64 / elapsed.as_secs_f64();
+
+            self.progress.speed = if self.progress.speed > 0.0 {
+                SPEED_SMOOTHING * rate
+                    + (1.0 - SPEED_SMOOTHING) * self.progress.speed
+            } else {
+                rate
+            };
+            self.meter.sample_at = now;
+            self.meter.sample_bytes = done;
+        }
+
+        let total_elapsed =
+            now.duration_since(self.meter.started_at).as_secs_f64();
+        if total_elapsed > 0.0 {
+            let transferred =
+                done.saturating_sub(self.meter.started_bytes);
+            self.progress.average_speed =
+                transferred as f64 / total_elapsed;
+        }
+
+        // 第一次采样之前用平均速度估算
+        let speed = if self.progress.speed > 0.0 {
+            self.progress.speed
+        } else {
+            self.progress.average_speed
+        };
+
+        self.progress.eta = match self.progress.remaining_bytes() {
+            Some(remaining) if speed > 0.0 => {
+                Some(Duration::from_secs_f64(remaining as f64 / speed))
+            }
+            _ => None,
+        };
+    }
+}
+
+/// # 进度上报
+///
+/// 下载的各个环节都通过它更新进度，内部持有完整状态，
+/// 状态变化时把快照发布到 `progress`，同时同步 `download_bytes`。
+/// 分片任务并发调用也不会互相覆盖。
+///
+/// 收到数据只累加计数，快照（包括整个分片列表）最多每个采样间隔发布一次，
+/// `download_bytes` 仍然每次都同步。
+#[derive(Debug, Clone)]
+pub(crate) struct ProgressReporter {
+    state: Arc<Mutex<ProgressState>>,
+    progress: ReactiveProperty<TransferProgress>,
+    download_bytes: ReactiveProperty<usize>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(
+        progress: ReactiveProperty<TransferProgress>,
+        download_bytes: ReactiveProperty<usize>,
+    ) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ProgressState {
+                progress: TransferProgress::default(),
+                meter: SpeedMeter::new(0),
+                published_at: None,
+            })),
+            progress,
+            download_bytes,
+        }
+    }
+
+    /// 修改状态并发布，发布放在锁里，保证监听者看到的快照不会倒退
+    ///
+    /// `updater` 返回 `false` 时只同步 `download_bytes`，不发布快照。
+    fn modify_with(
+        &self,
+        updater: impl FnOnce(&mut ProgressState) -> bool,
+    ) -> Result<(), ReactivePropertyError> {
+        let mut state =
+            self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let publish = updater(&mut state);
+
+        self.download_bytes.update(state.progress.done_bytes as usize)?;
+
+        if publish {
+            state.published_at = Some(Instant::now());
+            self.progress.update(state.progress.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// 修改状态并立即发布快照
+    fn modify(
+        &self,
+        updater: impl FnOnce(&mut ProgressState),
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify_with(|state| {
+            updater(state);
+            true
+        })
+    }
+
+    /// 开始一次新的传输，之前的进度全部清空
+    pub(crate) fn start(
+        &self,
+        total_bytes: Option<u64>,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            state.progress = TransferProgress {
+                total_bytes,
+                state: TransferState::Running,
+                ..TransferProgress::default()
+            };
+            state.meter = SpeedMeter::new(0);
+        })
+    }
+
+    /// 从 `done_bytes` 重新开始计算，续传或者从头重下时调用
+    pub(crate) fn reset(
+        &self,
+        done_bytes: u64,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            state.progress.done_bytes = done_bytes;
+            state.progress.speed = 0.0;
+            state.progress.average_speed = 0.0;
+            state.progress.eta = None;
+            state.progress.chunks.clear();
+            state.meter = SpeedMeter::new(done_bytes);
+        })
+    }
+
+    /// 登记本次需要下载的分片，顺序就是分片的序号
+    pub(crate) fn set_chunks(
+        &self,
+        ranges: &[(u64, u64)],
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            state.progress.chunks = ranges
+                .iter()
+                .map(|&(start, end)| ChunkProgress {
+                    start,
+                    end,
+                    done_bytes: 0,
+                    state: TransferState::Queued,
+                })
+                .collect();
+        })
+    }
+
+    /// 追加一个分片，返回它的序号，分片大小在下载过程中才确定时使用
+    pub(crate) fn add_chunk(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<usize, ReactivePropertyError> {
+        let mut index = 0;
+
+        self.modify(|state| {
+            index = state.progress.chunks.len();
+            state.progress.chunks.push(ChunkProgress {
+                start,
+                end,
+                done_bytes: 0,
+                state: TransferState::Queued,
+            });
+        })?;
+
+        Ok(index)
+    }
+
+    /// 写入了 `bytes` 字节，`chunk` 为分片序号，不分片时为 `None`
+    ///
+    /// 状态没有变化时按采样间隔发布，不会每个网络包都复制一次分片列表。
+    pub(crate) fn add(
+        &self,
+        chunk: Option<usize>,
+        bytes: u64,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify_with(|state| {
+            let mut changed =
+                state.progress.state != TransferState::Running;
+
+            state.progress.done_bytes += bytes;
+            state.progress.state = TransferState::Running;
+
+            if let Some(chunk) =
+                chunk.and_then(|i| state.progress.chunks.get_mut(i))
+            {
+                changed |= chunk.state != TransferState::Running;
+                chunk.done_bytes += bytes;
+                chunk.state = TransferState::Running;
+            }
+
+            state.refresh_speed();
+
+            changed || state.should_publish()
+        })
+    }
+
+    /// 丢掉已经写入的 `bytes` 字节，服务器忽略 Range 只能从头写时调用
+    pub(crate) fn discard(
+        &self,
+        bytes: u64,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            let done = state.progress.done_bytes.saturating_sub(bytes);
+            state.progress.done_bytes = done;
+
+            // 采样基准跟着退回，避免算出负的速度
+            state.meter.sample_bytes = state.meter.sample_bytes.min(done);
+            state.meter.started_bytes =
+                state.meter.started_bytes.min(done);
+        })
+    }
+
+    /// 修改整体状态，`chunk` 不为空时同时修改该分片的状态
+    ///
+    /// 单个分片重试时其它分片可能还在正常下载，
+    /// 只有进行中的分片全部在重试时，整个文件才标记为重试。
+    pub(crate) fn set_state(
+        &self,
+        chunk: Option<usize>,
+        transfer_state: TransferState,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            let Some(chunk) =
+                chunk.and_then(|i| state.progress.chunks.get_mut(i))
+            else {
+                state.progress.state = transfer_state;
+                return;
+            };
+
+            chunk.state = transfer_state;
+
+            state.progress.state = match transfer_state {
+                TransferState::Retrying(_) => {
+                    let all_retrying = state
+                        .progress
+                        .chunks
+                        .iter()
+                        .filter(|chunk| {
+                            matches!(
+                                chunk.state,
+                                TransferState::Running
+                                    | TransferState::Retrying(_)
+                            )
+                        })
+                        .all(|chunk| {
+                            matches!(chunk.state, TransferState::Retrying(_))
+                        });
+
+                    if all_retrying {
+                        transfer_state
+                    } else {
+                        TransferState::Running
+                    }
+                }
+                _ => transfer_state,
+            };
+        })
+    }
+
+    /// 只修改分片的状态，分片完成或失败不代表整个文件完成或失败
+    pub(crate) fn set_chunk_state(
+        &self,
+        chunk: usize,
+        transfer_state: TransferState,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            if let Some(chunk) = state.progress.chunks.get_mut(chunk) {
+                chunk.state = transfer_state;
+            }
+        })
+    }
+
+    /// 传输结束
+    pub(crate) fn finish(
+        &self,
+        success: bool,
+    ) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            state.progress.speed = 0.0;
+            state.progress.state = if success {
+                TransferState::Done
+            } else {
+                TransferState::Failed
+            };
+            state.progress.eta = success.then_some(Duration::ZERO);
+        })
+    }
+
+    /// 传输被取消，没有完成的分片一起标记为取消
+    pub(crate) fn cancel(&self) -> Result<(), ReactivePropertyError> {
+        self.modify(|state| {
+            state.progress.speed = 0.0;
+            state.progress.eta = None;
+            state.progress.state = TransferState::Cancelled;
+
+            for chunk in &mut state.progress.chunks {
+                if !chunk.state.is_finished() {
+                    chunk.state = TransferState::Cancelled;
+                }
+            }
+        })
+    }
+
+    /// 处于暂停状态时等待恢复，等待期间状态为 `Paused`
+    pub(crate) async fn wait_if_paused(
+        &self,
+        download_config: &DownloadConfig,
+        chunk: Option<usize>,
+    ) -> Result<(), ReactivePropertyError> {
+        if !download_config.is_paused() {
+            return Ok(());
+        }
+
+        self.set_state(chunk, TransferState::Paused)?;
+        download_config.wait_if_paused().await;
+        self.set_state(chunk, TransferState::Running)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::client::webdav_request::get_folders_public_impl::GetFoldersError;
+use crate::resource_file::impl_traits::impl_download::preserve_metadata::PreserveMetadataError;
+use crate::resource_file::traits::download::DownloadError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// # 递归下载的参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecursiveDownloadOptions {
+    pub max_concurrent_files: usize, // 同时下载的文件数，每个文件内部仍按下载配置分片
+    pub include_empty_dirs: bool,    // 是否保留子树里没有文件的文件夹
+}
+
+impl Default for RecursiveDownloadOptions {
+    fn default() -> Self {
+        Self { max_concurrent_files: 4, include_empty_dirs: true }
+    }
+}
+
+/// 跳过某个条目的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecursiveDownloadSkipReason {
+    /// 名字为空、是 `.`/`..` 或者包含路径分隔符，写到本地会跳出目标目录
+    UnsafeName,
+    /// 整棵子树里都没有文件的文件夹，且没有开启 `include_empty_dirs`
+    EmptyDir,
+    /// 本地文件已存在，按冲突策略保留了本地文件
+    LocalFileExists,
+}
+
+impl RecursiveDownloadSkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecursiveDownloadSkipReason::UnsafeName => "unsafe_name",
+            RecursiveDownloadSkipReason::EmptyDir => "empty_dir",
+            RecursiveDownloadSkipReason::LocalFileExists => {
+                "local_file_exists"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecursiveDownloadFailure {
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+
+    #[error("获取文件夹内容失败: {0}")]
+    ListFolder(#[from] GetFoldersError),
+
+    #[error("创建本地文件夹失败: {0}")]
+    CreateDir(std::io::Error),
+
+    #[error("下载任务异常退出: {0}")]
+    Join(#[from] tokio::task::JoinError),
+
+    #[error(transparent)]
+    PreserveMetadata(#[from] PreserveMetadataError),
+}
+
+/// 单个条目的处理结果
+#[derive(Debug)]
+pub enum RecursiveDownloadOutcome {
+    /// 文件已下载，或者文件夹已创建
+    Downloaded,
+    Skipped(RecursiveDownloadSkipReason),
+    Failed(RecursiveDownloadFailure),
+}
+
+/// 报告里的一个条目
+#[derive(Debug)]
+pub struct RecursiveDownloadEntry {
+    pub remote_path: String, // 远程文件的相对路径（相对根目录）
+    pub local_path: PathBuf, // 本地文件的完整路径
+    pub is_dir: bool,
+    pub outcome: RecursiveDownloadOutcome,
+}
+
+/// # 递归下载的报告
+///
+/// 单个文件失败不会中断整个下载，所有结果都记录在这里，由使用者决定是否重试。
+#[derive(Debug, Default)]
+pub struct RecursiveDownloadReport {
+    pub entries: Vec<RecursiveDownloadEntry>,
+}
+
+impl RecursiveDownloadReport {
+    pub(crate) fn push(
+        &mut self,
+        remote_path: &str,
+        local_path: PathBuf,
+        is_dir: bool,
+        outcome: RecursiveDownloadOutcome,
+    ) {
+        self.entries.push(RecursiveDownloadEntry {
+            remote_path: remote_path.to_string(),
+            local_path,
+            is_dir,
+            outcome,
+        });
+    }
+
+    /// 下载成功的条目
+    pub fn downloaded(
+        &self,
+    ) -> impl Iterator<Item = &RecursiveDownloadEntry> {
+        self.entries.iter().filter(|entry| {
+            matches!(entry.outcome, RecursiveDownloadOutcome::Downloaded)
+        })
+    }
+
+    /// 跳过的条目
+    pub fn skipped(
+        &self,
+    ) -> impl Iterator<Item = &RecursiveDownloadEntry> {
+        self.entries.iter().filter(|entry| {
+            matches!(entry.outcome, RecursiveDownloadOutcome::Skipped(_))
+        })
+    }
+
+    /// 失败的条目
+    pub fn failed(&self) -> impl Iterator<Item = &RecursiveDownloadEntry> {
+        self.entries.iter().filter(|entry| {
+            matches!(entry.outcome, RecursiveDownloadOutcome::Failed(_))
+        })
+    }
+
+    /// 没有任何条目失败
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
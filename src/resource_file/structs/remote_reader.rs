@@ -0,0 +1,298 @@
+use crate::resource_file::impl_traits::impl_open_reader::fetch_block::{
+    FetchBlockArgs, FetchBlockError, fetch_block,
+};
+use crate::resource_file::structs::remote_validator::RemoteValidator;
+use crate::resource_file::traits::download::TDownloadConfig;
+use bytes::Bytes;
+use reqwest::Client;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+/// # 远程读取器的参数
+///
+/// 读取按块进行，缺少某个块时一次请求 `read_ahead` 字节，
+/// 后面的块先放进缓存，顺序读取时不用每块都发一次请求。
+/// 预读的块数不会超过 `cache_blocks`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteReaderOptions {
+    pub block_size: u64,     // 每块的字节数
+    pub read_ahead: u64,     // 每次请求的字节数，不足一块时按一块计算
+    pub cache_blocks: usize, // 最多缓存的块数，超出时淘汰最久没用过的块
+}
+
+impl Default for RemoteReaderOptions {
+    fn default() -> Self {
+        Self {
+            block_size: 256 * 1024,
+            read_ahead: 1024 * 1024,
+            cache_blocks: 16,
+        }
+    }
+}
+
+/// 按最近使用顺序淘汰的块缓存
+#[derive(Debug)]
+struct BlockCache {
+    capacity: usize,
+    blocks: HashMap<u64, Bytes>,
+    order: VecDeque<u64>, // 队尾是最近用过的块
+}
+
+impl BlockCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            blocks: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn touch(&mut self, index: u64) {
+        if let Some(position) = self.order.iter().position(|i| *i == index)
+        {
+            self.order.remove(position);
+        }
+        self.order.push_back(index);
+    }
+
+    fn get(&mut self, index: u64) -> Option<Bytes> {
+        let block = self.blocks.get(&index).cloned()?;
+        self.touch(index);
+        Some(block)
+    }
+
+    fn insert(&mut self, index: u64, block: Bytes) {
+        self.blocks.insert(index, block);
+        self.touch(index);
+
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.blocks.remove(&evicted);
+            }
+        }
+    }
+}
+
+type TFetchFuture =
+    Pin<Box<dyn Future<Output = Result<Bytes, FetchBlockError>> + Send>>;
+
+/// 正在进行的请求，覆盖 `first..=last` 这些块
+struct PendingFetch {
+    first: u64,
+    last: u64,
+    future: TFetchFuture,
+}
+
+/// # 随机读取远程文件
+///
+/// 实现了 [`AsyncRead`] 和 [`AsyncSeek`]，内部用 Range 请求按块读取，
+/// 适合只需要读取大文件一小部分的场景，例如压缩包目录、媒体文件头。
+///
+/// Seek 本身不发请求，跳回已经缓存的位置也不会重新下载。
+/// 读取时远程文件发生变化会返回错误，缓存里的旧数据不会和新数据混在一起。
+pub struct RemoteReader {
+    http_client: Client,
+    file_url: Arc<str>,
+    total_size: u64,
+    validator: Arc<RemoteValidator>,
+    download_config: TDownloadConfig,
+    options: RemoteReaderOptions,
+    position: u64,
+    cache: BlockCache,
+    pending: Option<PendingFetch>,
+}
+
+impl RemoteReader {
+    pub(crate) fn new(
+        http_client: Client,
+        file_url: &str,
+        total_size: u64,
+        validator: RemoteValidator,
+        download_config: TDownloadConfig,
+        options: RemoteReaderOptions,
+    ) -> Self {
+        let options = RemoteReaderOptions {
+            block_size: options.block_size.max(1),
+            ..options
+        };
+
+        Self {
+            http_client,
+            file_url: Arc::from(file_url),
+            total_size,
+            validator: Arc::new(validator),
+            download_config,
+            options,
+            position: 0,
+            cache: BlockCache::new(options.cache_blocks),
+            pending: None,
+        }
+    }
+
+    /// 远程文件的大小
+    pub fn len(&self) -> u64 {
+        self.total_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_size == 0
+    }
+
+    /// 当前读取位置
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// 文件里一共有多少块
+    fn block_count(&self) -> u64 {
+        self.total_size.div_ceil(self.options.block_size)
+    }
+
+    /// 从 `first` 开始请求一个预读窗口，遇到已经缓存的块就停下
+    ///
+    /// 窗口不超过缓存容量，否则存进缓存时会把刚请求的 `first` 淘汰掉，
+    /// 读取方拿不到这一块，只能一直重新请求。
+    fn start_fetch(&self, first: u64) -> PendingFetch {
+        let window = self
+            .options
+            .read_ahead
+            .div_ceil(self.options.block_size)
+            .min(self.cache.capacity as u64);
+        let mut last = first;
+        while last + 1 < first + window.max(1)
+            && last + 1 < self.block_count()
+            && !self.cache.blocks.contains_key(&(last + 1))
+        {
+            last += 1;
+        }
+
+        let block_size = self.options.block_size;
+        let args = FetchBlockArgs {
+            http_client: self.http_client.clone(),
+            file_url: self.file_url.clone(),
+            start: first * block_size,
+            end: ((last + 1) * block_size).min(self.total_size) - 1,
+            total_size: self.total_size,
+            validator: self.validator.clone(),
+            download_config: self.download_config.clone(),
+        };
+
+        PendingFetch { first, last, future: Box::pin(fetch_block(args)) }
+    }
+
+    /// 把一次请求拿到的数据切成块放进缓存
+    fn store_blocks(&mut self, first: u64, data: Bytes) {
+        let block_size = self.options.block_size as usize;
+        let mut index = first;
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let end = (offset + block_size).min(data.len());
+            self.cache.insert(index, data.slice(offset..end));
+            offset = end;
+            index += 1;
+        }
+    }
+
+    /// 等待包含 `index` 的块就绪
+    fn poll_block(
+        &mut self,
+        cx: &mut Context<'_>,
+        index: u64,
+    ) -> Poll<io::Result<Bytes>> {
+        loop {
+            if let Some(block) = self.cache.get(index) {
+                return Poll::Ready(Ok(block));
+            }
+
+            // Seek 到了别处，正在进行的请求用不上，直接放弃
+            let pending = match self.pending.take() {
+                Some(p) if (p.first..=p.last).contains(&index) => p,
+                _ => self.start_fetch(index),
+            };
+            let pending = self.pending.insert(pending);
+
+            let result = ready!(pending.future.as_mut().poll(cx));
+            let first = pending.first;
+            self.pending = None;
+
+            match result {
+                Ok(data) => self.store_blocks(first, data),
+                Err(e) => return Poll::Ready(Err(io::Error::other(e))),
+            }
+        }
+    }
+}
+
+impl AsyncRead for RemoteReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.position >= self.total_size || buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let block_size = self.options.block_size;
+        let index = self.position / block_size;
+        let block = ready!(self.poll_block(cx, index))?;
+
+        let offset = (self.position - index * block_size) as usize;
+        let len = buf.remaining().min(block.len().saturating_sub(offset));
+        buf.put_slice(&block[offset..offset + len]);
+        self.position += len as u64;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for RemoteReader {
+    fn start_seek(
+        mut self: Pin<&mut Self>,
+        position: SeekFrom,
+    ) -> io::Result<()> {
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => {
+                self.total_size.checked_add_signed(delta)
+            }
+            SeekFrom::Current(delta) => {
+                self.position.checked_add_signed(delta)
+            }
+        };
+
+        self.position = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "不能 Seek 到文件开头之前",
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl fmt::Debug for RemoteReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteReader")
+            .field("file_url", &self.file_url)
+            .field("total_size", &self.total_size)
+            .field("position", &self.position)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
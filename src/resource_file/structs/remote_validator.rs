@@ -0,0 +1,112 @@
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use chrono::{DateTime, FixedOffset, Utc};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, HeaderMap, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 远程文件在下载过程中发生了变化
+///
+/// 续传得到的内容会是新旧文件的混合，只有处理方式为
+/// [`Fail`](crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy::Fail)
+/// 时才会返回给使用者。
+#[derive(Debug, Clone, Error)]
+#[error("远程文件[{url}] 已经变化，已下载的内容不能继续使用")]
+pub struct RemoteChangedError {
+    pub url: String,
+    /// 开始下载时记录的版本
+    pub expected: RemoteValidator,
+    /// 服务器当前返回的版本
+    pub current: RemoteValidator,
+}
+
+/// 远程文件的版本标识，开始下载时记录，续传时用来判断文件有没有变化
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteValidator {
+    pub etag: Option<String>, // 清理后的 ETag
+    pub last_modified: Option<DateTime<FixedOffset>>, // 最后修改时间
+}
+
+/// 去掉弱 ETag 前缀、首尾引号和空格，只比较真正的标识部分
+fn normalize_etag(etag: &str) -> &str {
+    etag.trim().trim_start_matches("W/").trim_matches('"')
+}
+
+impl RemoteValidator {
+    pub fn from_data(data: &ResourceFileData) -> Self {
+        Self { etag: data.etag.clone(), last_modified: data.last_modified }
+    }
+
+    /// 从响应头里读取服务器当前的版本
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header =
+            |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+        Self {
+            etag: header(ETAG)
+                .map(|etag| normalize_etag(etag).to_string()),
+            last_modified: header(LAST_MODIFIED)
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok()),
+        }
+    }
+
+    /// 判断是否属于同一个版本，双方都有 ETag 时比较 ETag，否则比较修改时间，
+    /// 什么都没有时无法判断，视为同一个版本
+    pub fn matches(&self, other: &RemoteValidator) -> bool {
+        if let (Some(a), Some(b)) = (&self.etag, &other.etag) {
+            return normalize_etag(a) == normalize_etag(b);
+        }
+
+        if let (Some(a), Some(b)) =
+            (&self.last_modified, &other.last_modified)
+        {
+            return a == b;
+        }
+
+        true
+    }
+
+    /// 生成 `If-Range` 请求头
+    ///
+    /// 弱 ETag 不能用于 `If-Range`，这时退回到修改时间；两者都没有时不发送。
+    pub fn if_range(&self) -> Option<String> {
+        if let Some(etag) = &self.etag
+            && !etag.trim().starts_with("W/")
+        {
+            return Some(format!("\"{}\"", normalize_etag(etag)));
+        }
+
+        self.last_modified.map(|date| {
+            date.with_timezone(&Utc)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string()
+        })
+    }
+
+    /// 检查 Range 请求的响应是否来自同一个版本
+    ///
+    /// 响应头里的版本与记录不一致，或者带了 `If-Range` 却收到 200，
+    /// 都说明远程文件已经变化。
+    pub(crate) fn check_ranged_response(
+        &self,
+        url: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Result<(), RemoteChangedError> {
+        let current = Self::from_headers(headers);
+
+        let changed = !self.matches(&current)
+            || (status == StatusCode::OK && self.if_range().is_some());
+
+        if changed {
+            return Err(RemoteChangedError {
+                url: url.to_string(),
+                expected: self.clone(),
+                current,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::resource_file::traits::download_stream::DownloadStreamError;
+use bytes::Bytes;
+use futures_util::Stream;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type TInnerStream =
+    Pin<Box<dyn Stream<Item = Result<Bytes, DownloadStreamError>> + Send>>;
+
+/// # 远程文件的字节流
+///
+/// 由 [`DownloadStream::open_stream`](crate::resource_file::traits::download_stream::DownloadStream::open_stream)
+/// 返回，按顺序产出文件内容。网络中断时会在内部带着 Range 重连，
+/// 使用者只会在重试用尽或遇到无法恢复的错误时收到一个 `Err`，之后流结束。
+pub struct ResourceByteStream {
+    inner: TInnerStream,
+}
+
+impl ResourceByteStream {
+    pub(crate) fn new(
+        inner: impl Stream<Item = Result<Bytes, DownloadStreamError>>
+        + Send
+        + 'static,
+    ) -> Self {
+        Self { inner: Box::pin(inner) }
+    }
+}
+
+impl Stream for ResourceByteStream {
+    type Item = Result<Bytes, DownloadStreamError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for ResourceByteStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceByteStream").finish_non_exhaustive()
+    }
+}
@@ -1,27 +1,241 @@
 use std::ops::Deref;
-use crate::reactive::reactive::ReactiveProperty;
+use std::sync::Arc;
+use crate::global_config::global_config::GlobalFunctionSymbol;
+use crate::global_config::speed_limiter::SpeedLimiter;
+use crate::reactive::reactive::{ReactiveProperty, ReactivePropertyError};
+use crate::resource_file::enums::chunk_strategy::ChunkStrategy;
+use crate::resource_file::enums::conflict_policy::ConflictPolicy;
+use crate::resource_file::enums::download_mode::DownloadMode;
+use crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Error)]
+pub enum ResourceConfigError {
+    /// ReactiveProperty 内部更新失败
+    #[error("资源配置更新失败: {fun_symbol}")]
+    UpdateFailed {
+        fun_symbol: GlobalFunctionSymbol,
+        #[source]
+        source: ReactivePropertyError,
+    },
+}
+
+/// 单个资源文件的配置覆盖
+///
+/// 字段为 `None` 时沿用账号配置，账号也没有设置时再沿用全局配置。
+/// `max_speed` 例外：三层限速同时生效，实际速度取其中最小的一个。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ResourceConfigData {
-    pub max_speed: Option<u64>,    // 限速
-    pub timeout_secs: u64,         // 超时
-    pub max_retries: u32,          // 最大重试次数
-    pub large_file_threshold: u64, // 如果文件大于该值，则自动分片下载
-    pub max_thread_count: u32,     // 最大线程数
-    pub pause: bool,               // 暂停标志
+    pub max_speed: Option<u64>,            // 限速
+    pub timeout_secs: Option<u64>,         // 超时
+    pub max_retries: Option<u32>,          // 最大重试次数
+    pub large_file_threshold: Option<u64>, // 如果文件大于该值，则自动分片下载
+    pub max_thread_count: Option<u32>,     // 最大线程数
+    pub chunk_strategy: Option<ChunkStrategy>, // 分片大小和并发数的调整方式
+    pub download_mode: DownloadMode,       // 下载方式
+    pub remote_changed_policy: Option<RemoteChangedPolicy>, // 远程文件变化的处理方式
+    pub atomic_write: Option<bool>,        // 原子下载
+    pub sidecar_checksum: Option<bool>,    // 读取同名 .sha256 文件校验
+    pub conflict_policy: Option<ConflictPolicy>, // 本地文件已存在时的处理方式
+    pub preserve_mtime: Option<bool>,      // 保留远程修改时间
+    pub preserve_readonly: Option<bool>,   // 没有写权限的文件设为只读
+    pub pause: bool,                       // 暂停标志
 }
 
 type TResourceConfigData = ReactiveProperty<ResourceConfigData>;
 
+/// # 资源文件配置
+///
+/// 每个 `ResourcesFile` 各自持有一份，所有 setter 都返回 `&Self`，可以链式调用。
+/// 下载过程中修改会在下一次读取配置时生效。
 #[derive(Debug, Clone)]
 pub struct ResourceConfig {
     inner: TResourceConfigData,
+    speed_limiter: Arc<SpeedLimiter>, // 单个文件的限速器，所有分片共用
 }
 
 impl ResourceConfig {
+    pub fn new(config: ResourceConfigData) -> Self {
+        Self {
+            inner: ReactiveProperty::new(config),
+            speed_limiter: Arc::new(SpeedLimiter::new()),
+        }
+    }
+
+    /// 文件限速器，按文件的 `max_speed` 限制该文件所有分片的总速度
+    pub(crate) fn get_speed_limiter(&self) -> &SpeedLimiter {
+        &self.speed_limiter
+    }
+
+    /// 当前文件限速，`None` 表示该文件不单独限速
+    pub fn max_speed(&self) -> Option<u64> {
+        self.get_current_borrow().as_ref().and_then(|cfg| cfg.max_speed)
+    }
+
+    /// 当前下载方式
+    pub fn download_mode(&self) -> DownloadMode {
+        self.get_current_borrow()
+            .as_ref()
+            .map(|cfg| cfg.download_mode)
+            .unwrap_or_default()
+    }
+
     pub fn is_paused(&self) -> bool {
         self.get_current().map(|cfg| cfg.pause).unwrap_or(false)
     }
+
+    fn update_config(
+        &self,
+        fun_symbol: &str,
+        updater: impl FnOnce(&mut ResourceConfigData),
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_field(updater).map_err(|e| {
+            ResourceConfigError::UpdateFailed {
+                fun_symbol: fun_symbol.into(),
+                source: e,
+            }
+        })?;
+
+        Ok(self)
+    }
+
+    /// 暂停该文件的下载
+    pub fn pause(&self) -> Result<&Self, ResourceConfigError> {
+        self.update_config("pause", |cfg| cfg.pause = true)
+    }
+
+    /// 恢复该文件的下载
+    pub fn resume(&self) -> Result<&Self, ResourceConfigError> {
+        self.update_config("resume", |cfg| cfg.pause = false)
+    }
+
+    /// 设置该文件的速度上限，`None` 表示该文件不单独限速
+    pub fn set_max_speed(
+        &self,
+        speed: Option<u64>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_max_speed", |cfg| cfg.max_speed = speed)
+    }
+
+    /// 设置超时时间，`None` 表示沿用账号或全局配置
+    pub fn set_timeout(
+        &self,
+        seconds: Option<u64>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_timeout", |cfg| cfg.timeout_secs = seconds)
+    }
+
+    /// 设置最大重试次数，`None` 表示沿用账号或全局配置
+    pub fn set_max_retries(
+        &self,
+        retries: Option<u32>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_max_retries", |cfg| {
+            cfg.max_retries = retries
+        })
+    }
+
+    /// 设置大文件阈值，`None` 表示沿用账号或全局配置
+    pub fn set_large_file_threshold(
+        &self,
+        threshold: Option<u64>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_large_file_threshold", |cfg| {
+            cfg.large_file_threshold = threshold
+        })
+    }
+
+    /// 设置分片下载的最大线程数，`None` 表示沿用账号配置或按文件大小自动计算
+    pub fn set_max_thread_count(
+        &self,
+        count: Option<u32>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_max_thread_count", |cfg| {
+            cfg.max_thread_count = count
+        })
+    }
+
+    /// 设置分片策略，`None` 表示沿用账号或全局配置
+    pub fn set_chunk_strategy(
+        &self,
+        strategy: Option<ChunkStrategy>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_chunk_strategy", |cfg| {
+            cfg.chunk_strategy = strategy
+        })
+    }
+
+    /// 设置下载方式，见 [`DownloadMode`]
+    pub fn set_download_mode(
+        &self,
+        mode: DownloadMode,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_download_mode", |cfg| {
+            cfg.download_mode = mode
+        })
+    }
+
+    /// 设置续传时发现远程文件变化的处理方式，`None` 表示沿用账号或全局配置
+    pub fn set_remote_changed_policy(
+        &self,
+        policy: Option<RemoteChangedPolicy>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_remote_changed_policy", |cfg| {
+            cfg.remote_changed_policy = policy
+        })
+    }
+
+    /// 设置是否使用原子下载，`None` 表示沿用账号或全局配置
+    pub fn set_atomic_write(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_atomic_write", |cfg| {
+            cfg.atomic_write = enabled
+        })
+    }
+
+    /// 设置是否读取同名 `.sha256` 文件校验，`None` 表示沿用账号或全局配置
+    pub fn set_sidecar_checksum(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_sidecar_checksum", |cfg| {
+            cfg.sidecar_checksum = enabled
+        })
+    }
+
+    /// 设置本地文件已存在时的处理方式，`None` 表示沿用账号或全局配置
+    pub fn set_conflict_policy(
+        &self,
+        policy: Option<ConflictPolicy>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_conflict_policy", |cfg| {
+            cfg.conflict_policy = policy
+        })
+    }
+
+    /// 设置是否保留远程修改时间，`None` 表示沿用账号或全局配置
+    pub fn set_preserve_mtime(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_preserve_mtime", |cfg| {
+            cfg.preserve_mtime = enabled
+        })
+    }
+
+    /// 设置是否把没有写权限的文件设为只读，`None` 表示沿用账号或全局配置
+    pub fn set_preserve_readonly(
+        &self,
+        enabled: Option<bool>,
+    ) -> Result<&Self, ResourceConfigError> {
+        self.update_config("set_preserve_readonly", |cfg| {
+            cfg.preserve_readonly = enabled
+        })
+    }
 }
 
 impl Deref for ResourceConfig {
@@ -34,15 +248,6 @@ impl Deref for ResourceConfig {
 
 impl Default for ResourceConfig {
     fn default() -> Self {
-        Self {
-            inner: ReactiveProperty::new(ResourceConfigData {
-                max_speed: None,
-                timeout_secs: 0,
-                max_retries: 0,
-                large_file_threshold: 0,
-                max_thread_count: 0,
-                pause: false,
-            }),
-        }
+        Self::new(ResourceConfigData::default())
     }
 }
@@ -1,7 +1,9 @@
+use crate::client::THttpClientArc;
 use crate::global_config::global_config::GlobalConfig;
+use crate::resource_file::structs::checksum::Checksum;
 use crate::resource_file::structs::resources_file::ResourcesFile;
 use chrono::{DateTime, FixedOffset};
-use reqwest::{Client, Url};
+use reqwest::Url;
 
 #[derive(Debug, Clone)]
 pub struct ResourceFileData {
@@ -16,12 +18,13 @@ pub struct ResourceFileData {
     pub owner: Option<String>,      // 所有者
     pub etag: Option<String>,       // 清理后的 ETag
     pub privileges: Vec<String>,    // 权限列表
+    pub checksums: Vec<Checksum>,   // 服务器保存的校验值
 }
 
 impl ResourceFileData {
     pub fn to_resources_file(
         self,
-        http_client: Client,
+        http_client: THttpClientArc,
         global_config: GlobalConfig,
     ) -> ResourcesFile {
         ResourcesFile::new(self, http_client, global_config)
@@ -1,18 +1,38 @@
 use crate::reactive::reactive::ReactiveProperty;
+use crate::resource_file::enums::download_outcome::DownloadOutcome;
+use crate::resource_file::structs::checksum::FileHash;
+use crate::resource_file::structs::progress_reporter::ProgressReporter;
+use crate::resource_file::structs::transfer_progress::TransferProgress;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct ResourceFileProperty {
     pub name: ReactiveProperty<String>,
     pub download_bytes: ReactiveProperty<usize>,
     pub file_lock: ReactiveProperty<bool>, // 文件锁，主要用于限制下载时用户尝试修改文件名的操作
+    pub hash: ReactiveProperty<Option<FileHash>>, // 下载完成后计算出的摘要，分片下载且没有校验值时不计算
+    pub outcome: ReactiveProperty<Option<DownloadOutcome>>, // 最近一次下载的结果
+    pub progress: ReactiveProperty<TransferProgress>, // 速度、剩余时间、分片状态等完整进度
+    pub(crate) reporter: ProgressReporter, // 更新 progress 和 download_bytes
+    pub(crate) partial_path: ReactiveProperty<Option<PathBuf>>, // 正在写入、还没完成的文件，取消时用来清理
 }
 
 impl ResourceFileProperty {
     pub fn new(name: String) -> Self {
+        let download_bytes = ReactiveProperty::new(0);
+        let progress = ReactiveProperty::new(TransferProgress::default());
+        let reporter =
+            ProgressReporter::new(progress.clone(), download_bytes.clone());
+
         Self {
             name: ReactiveProperty::new(name),
-            download_bytes: ReactiveProperty::new(0),
+            download_bytes,
             file_lock: ReactiveProperty::new(false),
+            hash: ReactiveProperty::new(None),
+            outcome: ReactiveProperty::new(None),
+            progress,
+            reporter,
+            partial_path: ReactiveProperty::new(None),
         }
     }
 
@@ -25,7 +45,24 @@ impl ResourceFileProperty {
         &self.download_bytes
     }
 
+    /// 完整的传输进度，`download_bytes` 与其中的 `done_bytes` 保持一致
+    pub fn get_progress(&self) -> &ReactiveProperty<TransferProgress> {
+        &self.progress
+    }
+
     pub fn get_file_lock(&self) -> &ReactiveProperty<bool> {
         &self.file_lock
     }
+
+    /// 下载完成后才会有值，校验失败时也会保留计算结果
+    pub fn get_hash(&self) -> &ReactiveProperty<Option<FileHash>> {
+        &self.hash
+    }
+
+    /// 下载成功后才会有值，可以从中拿到文件实际保存的路径
+    pub fn get_outcome(
+        &self,
+    ) -> &ReactiveProperty<Option<DownloadOutcome>> {
+        &self.outcome
+    }
 }
@@ -1,6 +1,8 @@
 use crate::client::structs::client_key::TClientKey;
+use crate::client::THttpClientArc;
 use crate::global_config::global_config::GlobalConfig;
 use crate::reactive::reactive::ReactivePropertyError;
+use crate::resource_file::structs::download_config::DownloadConfig;
 use crate::resource_file::structs::resource_config::ResourceConfig;
 use crate::resource_file::structs::resource_file_data::ResourceFileData;
 use crate::resource_file::structs::resource_file_property::ResourceFileProperty;
@@ -9,6 +11,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ResourceFileUniqueKey {
@@ -26,10 +29,12 @@ const FILE_LOCK_RETRY_DELAY: Duration = Duration::from_secs(1);
 pub struct ResourcesFile {
     /// 资源文件原始数据
     data: Arc<ResourceFileData>,
-    http_client: Client,
+    /// 与账号共享同一个实例，账号更新凭据后这里会自动生效
+    http_client: THttpClientArc,
     reactive_state: ResourceFileProperty,
     reactive_config: ResourceConfig,
     global_config: GlobalConfig,
+    connection_limiter: Option<Arc<Semaphore>>, // 由传输管理器设置，限制所有文件的总连接数
 }
 
 impl Deref for ResourcesFile {
@@ -81,7 +86,7 @@ pub enum UnlockFileError {
 impl ResourcesFile {
     pub fn new(
         data: ResourceFileData,
-        http_client: Client,
+        http_client: THttpClientArc,
         global_config: GlobalConfig,
     ) -> Self {
         let reactive_state = ResourceFileProperty::new(data.name.clone());
@@ -92,6 +97,7 @@ impl ResourcesFile {
             reactive_state,
             reactive_config,
             global_config,
+            connection_limiter: None,
         }
     }
 
@@ -109,14 +115,44 @@ impl ResourcesFile {
     }
 
     /// 获取 HTTP 客户端
-    pub fn get_http_client(&self) -> &Client {
-        &self.http_client
+    ///
+    /// 每次都会拿到账号当前的客户端，账号更新凭据后无需重新获取资源文件
+    pub fn get_http_client(&self) -> Client {
+        self.http_client.get_client()
+    }
+
+    /// 获取账号共享的 HTTP 客户端，用来构建同一账号下的其它资源文件
+    pub(crate) fn get_http_client_arc(&self) -> THttpClientArc {
+        self.http_client.clone()
     }
 
     pub fn get_global_config(&self) -> GlobalConfig {
         self.global_config.clone()
     }
 
+    /// 获取下载时实际使用的配置（资源 → 账号 → 全局）
+    pub fn get_download_config(&self) -> DownloadConfig {
+        DownloadConfig::new(
+            self.global_config.clone(),
+            self.http_client.get_account_config(),
+            self.reactive_config.clone(),
+        )
+        .with_connection_limiter(self.connection_limiter.clone())
+    }
+
+    /// 使用已有的资源配置，传输管理器重建资源文件时沿用调用方的设置
+    pub(crate) fn set_reactive_config(&mut self, config: ResourceConfig) {
+        self.reactive_config = config;
+    }
+
+    /// 下载时与其它文件共用同一个连接数限制
+    pub(crate) fn set_connection_limiter(
+        &mut self,
+        connection_limiter: Arc<Semaphore>,
+    ) {
+        self.connection_limiter = Some(connection_limiter);
+    }
+
     /// 锁定文件
     ///
     /// 传入true即可强制执行，但是需要自己承担风险
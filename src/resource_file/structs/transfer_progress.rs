@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+/// 传输状态，整个文件和每个分片共用
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransferState {
+    /// 还没开始，分片在等待并发许可
+    #[default]
+    Queued,
+    Running,
+    /// 资源、账号、全局任意一层处于暂停状态
+    Paused,
+    /// 出错后等待第 n 次重试；分片下载时只有所有进行中的分片都在重试，
+    /// 整个文件才是这个状态
+    Retrying(u32),
+    Done,
+    Failed,
+    /// 通过 `DownloadHandle::cancel` 取消
+    Cancelled,
+}
+
+impl TransferState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferState::Queued => "queued",
+            TransferState::Running => "running",
+            TransferState::Paused => "paused",
+            TransferState::Retrying(_) => "retrying",
+            TransferState::Done => "done",
+            TransferState::Failed => "failed",
+            TransferState::Cancelled => "cancelled",
+        }
+    }
+
+    /// 已经结束，不会再有变化
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TransferState::Done
+                | TransferState::Failed
+                | TransferState::Cancelled
+        )
+    }
+}
+
+/// 单个分片的进度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkProgress {
+    pub start: u64, // 分片第一个字节的位置
+    pub end: u64,   // 分片最后一个字节的位置（包含）
+    pub done_bytes: u64,
+    pub state: TransferState,
+}
+
+impl ChunkProgress {
+    pub fn total_bytes(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// # 传输进度
+///
+/// 通过 `ResourceFileProperty::progress` 发布，界面直接监听它即可，
+/// 不需要自己计算速度和剩余时间。
+///
+/// 状态变化（开始、暂停、重试、完成等）会立即发布；
+/// 只是收到数据时最多每个采样间隔（500 毫秒）发布一次，
+/// 两次发布之间的字节数可以通过 `download_bytes` 实时获取。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferProgress {
+    /// 文件大小，服务器没有返回时为 `None`
+    pub total_bytes: Option<u64>,
+    /// 已完成的字节数，续传时包含之前下载的部分
+    pub done_bytes: u64,
+    /// 平滑后的瞬时速度（字节/秒）
+    pub speed: f64,
+    /// 本次开始以来的平均速度（字节/秒），不含续传前的部分
+    pub average_speed: f64,
+    /// 预计剩余时间，速度为 0 或大小未知时为 `None`
+    pub eta: Option<Duration>,
+    pub state: TransferState,
+    /// 分片下载时本次需要下载的分片，不分片时为空
+    pub chunks: Vec<ChunkProgress>,
+}
+
+impl TransferProgress {
+    /// 完成百分比（0 ~ 100），大小未知时为 `None`
+    pub fn percentage(&self) -> Option<f64> {
+        let total = self.total_bytes?;
+
+        if total == 0 {
+            return Some(if self.state == TransferState::Done {
+                100.0
+            } else {
+                0.0
+            });
+        }
+
+        Some((self.done_bytes as f64 / total as f64 * 100.0).min(100.0))
+    }
+
+    /// 剩余字节数，大小未知时为 `None`
+    pub fn remaining_bytes(&self) -> Option<u64> {
+        self.total_bytes.map(|total| total.saturating_sub(self.done_bytes))
+    }
+}
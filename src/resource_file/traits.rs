@@ -1,2 +1,6 @@
 pub mod download;
+pub mod download_recursive;
+pub mod download_stream;
+pub mod open_reader;
+pub mod start_download;
 pub mod to_resource_file_data;
\ No newline at end of file
@@ -1,13 +1,16 @@
-use crate::global_config::global_config::GlobalConfig;
 use crate::reactive::reactive::ReactivePropertyError;
 use crate::resource_file::impl_traits::impl_download::handle_download::HandleDownloadError;
 use crate::resource_file::impl_traits::impl_download::{
     HandleMountedError, HandleUnmountedError, PreprocessingSavePathError,
 };
+use crate::resource_file::structs::checksum::IntegrityError;
+use crate::resource_file::structs::download_config::DownloadConfig;
+use crate::resource_file::structs::remote_validator::RemoteChangedError;
 use crate::resource_file::structs::resources_file::{
     LockFileError, UnlockFileError,
 };
 use async_trait::async_trait;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 /// 下载过程中可能出现的错误类型。
@@ -39,15 +42,30 @@ pub enum DownloadError {
     /// 文件解锁失败。
     #[error(transparent)]
     UnlockFileError(#[from] UnlockFileError),
+
+    /// 续传时发现远程文件已经变化，且处理方式为
+    /// [`Fail`](crate::resource_file::enums::remote_changed_policy::RemoteChangedPolicy::Fail)。
+    #[error(transparent)]
+    RemoteChanged(RemoteChangedError),
+
+    /// 下载完成后校验失败：大小与 `ResourceFileData::size` 不符，
+    /// 或摘要与服务器提供的校验值不符。
+    #[error(transparent)]
+    Integrity(IntegrityError),
+
+    /// 本地目标文件已经存在，且冲突处理方式为
+    /// [`Fail`](crate::resource_file::enums::conflict_policy::ConflictPolicy::Fail)。
+    #[error("本地文件[{0}] 已存在")]
+    LocalFileExists(PathBuf),
 }
 
 /// 下载配置类型别名。
 ///
-/// 当前直接使用 [`GlobalConfig`] 作为下载配置，
-/// 未来如需扩展可在此处替换为更具体的配置结构。
+/// 使用 [`DownloadConfig`] 作为下载配置，
+/// 它按 资源 → 账号 → 全局 的顺序解析每一个配置项。
 ///
 /// - 别名都以`T`开头命名，如"TDownloadConfig"
-pub type TDownloadConfig = GlobalConfig;
+pub type TDownloadConfig = DownloadConfig;
 
 /// 定义下载行为的异步 trait。
 ///
@@ -77,13 +95,21 @@ pub trait Download {
     ///
     /// # ⚠️注意（一定要看）
     ///
-    /// 1、本方法 **不包含任何重试机制**，出现错误即返回错误。
+    /// 1、网络错误、超时以及 408、429、500、502、503、504 会自动重试，
+    /// 最多重试 `max_retries` 次（按 资源 → 账号 → 全局 解析，全局默认 4 次），
+    /// 次数按整个文件（分片下载时按每个分片）累计，中途有进展也不清零。
+    /// 每次重试前按指数退避加随机抖动等待，单次最多 30 秒。
+    /// 其它状态码（鉴权失败、资源不存在等）直接返回错误。
     ///
-    /// 如果需要重试，请使用者自行实现（例如在外层包裹重试逻辑）。
+    /// 重试时从已经写入的位置带 Range 续传，不会从头下载；
+    /// 远程文件在此期间变化时按 `remote_changed_policy` 处理。
+    /// 分片下载的完成区间记录在同目录的 `.wdpart` 文件里，
+    /// 重试次数用完返回错误后，再次调用本方法只下载还没完成的分片。
     ///
-    /// 2、本方法**不包含递归逻辑**，仅下载一层目录。
+    /// 2、本方法只下载文件，对目录调用时直接跳过。
     ///
-    /// ️ 若需要递归请自行实现，实现请参考WebdavClient中的get_folders方法⚠️。
+    /// 需要下载整个目录树请使用
+    /// [`DownloadRecursive::download_recursive`](crate::resource_file::traits::download_recursive::DownloadRecursive::download_recursive)。
     async fn download(
         self,
         output_absolute_path: &str,
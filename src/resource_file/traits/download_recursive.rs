@@ -0,0 +1,36 @@
+use crate::client::webdav_request::get_folders_public_impl::GetFoldersError;
+use crate::resource_file::structs::recursive_download::{
+    RecursiveDownloadOptions, RecursiveDownloadReport,
+};
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// 递归下载无法开始时的错误类型。
+///
+/// 开始之后单个文件或子文件夹的失败只记录在报告里，不会在这里返回。
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadRecursiveError {
+    /// 创建保存目录失败。
+    #[error("创建本地文件夹[{0}] 失败: {1}")]
+    CreateDir(PathBuf, std::io::Error),
+
+    /// 获取最外层文件夹的内容失败。
+    #[error("获取文件夹内容失败: {0}")]
+    ListFolder(#[from] GetFoldersError),
+}
+
+/// 递归下载整个文件夹，在本地还原远程的目录结构。
+///
+/// 文件夹内容直接保存到 `save_absolute_path` 下，与
+/// [`Download::download`](crate::resource_file::traits::download::Download::download)
+/// 对文件夹的处理一致；对文件调用时等同于下载这一个文件。
+///
+/// 子文件使用各自默认的资源配置，账号和全局配置照常生效。
+#[async_trait]
+pub trait DownloadRecursive {
+    async fn download_recursive(
+        &self,
+        save_absolute_path: &str,
+        options: RecursiveDownloadOptions,
+    ) -> Result<RecursiveDownloadReport, DownloadRecursiveError>;
+}
@@ -0,0 +1,77 @@
+use crate::resource_file::impl_traits::impl_download_stream::sequential_stream::SequentialStreamError;
+use crate::resource_file::structs::resource_byte_stream::ResourceByteStream;
+use crate::resource_file::structs::resources_file::{
+    LockFileError, UnlockFileError,
+};
+use async_trait::async_trait;
+use tokio::io::AsyncWrite;
+
+/// 下载到内存、写入器或字节流时可能出现的错误类型。
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadStreamError {
+    /// 目标是文件夹，没有内容可以读取。
+    #[error("[{0}] 是文件夹，无法读取内容")]
+    IsDir(String),
+
+    /// 读取远程内容失败，内部已经按配置重试过。
+    #[error(transparent)]
+    Stream(#[from] SequentialStreamError),
+
+    /// 写入调用方提供的写入器失败。
+    #[error("写入数据失败: {0}")]
+    Write(std::io::Error),
+
+    /// 文件超过了 [`download_to_bytes`](DownloadStream::download_to_bytes) 允许的大小。
+    #[error("文件大小超过上限 {max_size} 字节")]
+    TooLarge { max_size: u64 },
+
+    /// 文件加锁失败，可能是并发访问冲突。
+    #[error(transparent)]
+    LockFileError(#[from] LockFileError),
+
+    /// 文件解锁失败。
+    #[error(transparent)]
+    UnlockFileError(#[from] UnlockFileError),
+}
+
+/// 不落盘的下载方式。
+///
+/// 与 [`Download`](crate::resource_file::traits::download::Download) 共用同一套配置：
+/// 暂停、限速、超时和重试都按 资源 → 账号 → 全局 的顺序生效，
+/// 进度同样写入 `ResourceFileProperty::download_bytes` 和 `progress`，每次开始时从 0 计算。
+///
+/// 数据只能按顺序交给使用者，不会分片下载；
+/// 重连时服务器返回的内容属于另一个版本，会返回
+/// [`RemoteChanged`](crate::resource_file::structs::remote_validator::RemoteChangedError) 错误，
+/// 已经交出去的数据无法撤回，所以不会按配置从头重新下载。
+#[async_trait]
+pub trait DownloadStream {
+    /// 把文件内容写入 `writer`，返回写入的字节数。
+    ///
+    /// 写完后会调用一次 `flush`，不会 `shutdown`。
+    async fn download_to_writer<W>(
+        &self,
+        writer: &mut W,
+    ) -> Result<u64, DownloadStreamError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized;
+
+    /// 把文件内容读到内存里。
+    ///
+    /// # 参数
+    ///
+    /// * `max_size` - 允许的最大字节数，已知文件大小超过它时不会发出请求，
+    ///   读取过程中超过时立即停止。
+    async fn download_to_bytes(
+        &self,
+        max_size: u64,
+    ) -> Result<Vec<u8>, DownloadStreamError>;
+
+    /// 打开一个按顺序产出文件内容的字节流。
+    ///
+    /// 请求在第一次读取时才发出。字节流不会锁定文件，
+    /// 同一个资源文件同时打开多个字节流时，进度会被互相覆盖。
+    fn open_stream(
+        &self,
+    ) -> Result<ResourceByteStream, DownloadStreamError>;
+}
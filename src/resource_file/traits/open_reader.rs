@@ -0,0 +1,34 @@
+use crate::resource_file::structs::remote_reader::{
+    RemoteReader, RemoteReaderOptions,
+};
+
+/// 打开远程读取器时可能出现的错误类型。
+#[derive(Debug, thiserror::Error)]
+pub enum OpenReaderError {
+    /// 目标是文件夹，没有内容可以读取。
+    #[error("[{0}] 是文件夹，无法读取内容")]
+    IsDir(String),
+
+    /// 不知道文件大小时无法按区间读取，也无法从末尾 Seek。
+    #[error("文件大小未知，无法随机读取 {0}")]
+    UnknownFileSize(String),
+}
+
+/// 随机读取远程文件。
+///
+/// 返回的 [`RemoteReader`] 实现了 `AsyncRead` 和 `AsyncSeek`，
+/// 读取时按需发出 Range 请求，超时、重试、暂停和限速都沿用下载配置。
+///
+/// 读取器不锁定文件，也不更新下载进度。
+pub trait OpenReader {
+    /// 使用默认参数打开读取器。
+    fn open_reader(&self) -> Result<RemoteReader, OpenReaderError> {
+        self.open_reader_with_options(RemoteReaderOptions::default())
+    }
+
+    /// 指定块大小、预读窗口和缓存块数打开读取器。
+    fn open_reader_with_options(
+        &self,
+        options: RemoteReaderOptions,
+    ) -> Result<RemoteReader, OpenReaderError>;
+}
@@ -0,0 +1,17 @@
+use crate::resource_file::structs::download_handle::DownloadHandle;
+
+/// 在后台开始下载，不等待下载完成。
+///
+/// 与 [`Download`](crate::resource_file::traits::download::Download) 走同一套流程和配置，
+/// 区别只是下载放进了单独的 tokio 任务，调用方通过返回的 [`DownloadHandle`]
+/// 暂停、恢复、取消，或者监听进度、等待结果。
+///
+/// 必须在 tokio 运行时内调用。
+pub trait StartDownload {
+    /// 开始下载并立即返回句柄。
+    ///
+    /// # 参数
+    ///
+    /// * `output_absolute_path` - 下载文件的输出路径（绝对路径）。
+    fn start_download(self, output_absolute_path: &str) -> DownloadHandle;
+}
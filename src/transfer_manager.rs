@@ -0,0 +1,703 @@
+pub mod queued_transfer_state;
+pub mod transfer_priority;
+pub mod transfer_queue_file;
+
+use crate::client::structs::client_key::ClientKey;
+use crate::client::structs::reactive_child_clients::ReactiveChildClients;
+use crate::global_config::global_config::GlobalConfig;
+use crate::resource_file::structs::download_handle::{
+    CancelDownloadError, DownloadHandle, remove_journaled_files,
+    remove_partial_files,
+};
+use crate::resource_file::structs::resource_config::ResourceConfig;
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use crate::resource_file::structs::resources_file::ResourcesFile;
+use crate::resource_file::structs::transfer_progress::TransferProgress;
+use crate::resource_file::traits::download::Download;
+use crate::transfer_manager::queued_transfer_state::QueuedTransferState;
+use crate::transfer_manager::transfer_priority::TransferPriority;
+use crate::transfer_manager::transfer_queue_file::{
+    QueuedTransferRecord, TRANSFER_QUEUE_VERSION, TransferQueueFile,
+};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+
+/// 传输任务的编号，同一个管理器内唯一
+pub type TransferId = u64;
+
+/// 默认同时下载的文件数
+pub const DEFAULT_MAX_CONCURRENT_FILES: usize = 3;
+
+/// 默认所有文件加起来的最大连接数
+pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum TransferManagerError {
+    #[error("账号不存在: {0}")]
+    AccountNotFound(String),
+
+    #[error("[{0}] 是文件夹，只能排队下载文件")]
+    NotAFile(String),
+
+    #[error("传输任务不存在: {0}")]
+    TransferNotFound(TransferId),
+
+    #[error("读写队列文件失败: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("队列文件格式错误: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("不支持的队列文件版本: {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("队列文件中的记录无效: {0}")]
+    InvalidRecord(String),
+
+    #[error("已经打开了队列文件: {0}")]
+    QueueFileAlreadyOpen(PathBuf),
+
+    #[error("停止下载失败: {0}")]
+    CancelDownloadError(#[from] CancelDownloadError),
+}
+
+/// 队列中一个传输任务的快照
+#[derive(Debug, Clone)]
+pub struct TransferInfo {
+    pub id: TransferId,
+    pub client_key: ClientKey,
+    pub name: String,
+    pub remote_path: String, // 远程文件的完整路径
+    pub save_absolute_path: String, // 保存目录，文件名与远程文件相同
+    pub priority: TransferPriority,
+    pub state: QueuedTransferState,
+    pub progress: Option<TransferProgress>, // 开始过才有值
+}
+
+#[derive(Debug)]
+struct TransferEntry {
+    id: TransferId,
+    key: ClientKey,
+    data: Arc<ResourceFileData>,
+    config: ResourceConfig, // 与调用方的文件共享，加入队列后修改依然生效
+    save_absolute_path: String,
+    priority: TransferPriority,
+    state: QueuedTransferState,
+    resources_file: Option<ResourcesFile>, // 最近一次运行使用的文件，用来读取进度
+    handle: Option<DownloadHandle>,        // 运行中才有值
+    run: u64, // 每次开始运行时更新，旧的任务结束时据此忽略自己的结果
+}
+
+impl TransferEntry {
+    fn to_info(&self) -> TransferInfo {
+        TransferInfo {
+            id: self.id,
+            client_key: self.key.clone(),
+            name: self.data.name.clone(),
+            remote_path: self.data.absolute_path.clone(),
+            save_absolute_path: self.save_absolute_path.clone(),
+            priority: self.priority,
+            state: self.state.clone(),
+            progress: self.resources_file.as_ref().and_then(|file| {
+                file.get_progress().get_current().map(|p| (*p).clone())
+            }),
+        }
+    }
+
+    fn to_record(&self) -> QueuedTransferRecord {
+        QueuedTransferRecord::new(
+            &self.key,
+            &self.data,
+            &self.save_absolute_path,
+            self.priority,
+            self.state == QueuedTransferState::Paused,
+            self.config
+                .get_current()
+                .map(|cfg| cfg.as_ref().clone())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Debug)]
+struct QueueState {
+    next_id: TransferId,
+    next_run: u64,
+    entries: Vec<TransferEntry>, // 队列顺序，同一优先级内靠前的先开始
+    max_concurrent_files: usize,
+    max_connections: usize,
+    connection_limiter: Arc<Semaphore>,
+    queue_file: Option<PathBuf>,
+}
+
+impl QueueState {
+    fn position(
+        &self,
+        id: TransferId,
+    ) -> Result<usize, TransferManagerError> {
+        self.entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or(TransferManagerError::TransferNotFound(id))
+    }
+
+    fn running_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.state == QueuedTransferState::Running)
+            .count()
+    }
+
+    /// 下一个可以开始的任务：优先级最高的里面排在最前面的
+    fn next_pending(&self) -> Option<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                entry.state == QueuedTransferState::Pending
+            })
+            .max_by_key(|(index, entry)| (entry.priority, Reverse(*index)))
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(Debug)]
+struct TransferManagerInner {
+    state: Mutex<QueueState>,
+    save_lock: tokio::sync::Mutex<()>, // 保证队列文件按修改顺序写入
+    child_clients: ReactiveChildClients,
+    global_config: GlobalConfig,
+}
+
+/// # 传输管理器
+///
+/// 由 `WebDavClient` 持有，所有排队的下载共用同一组并发限制：
+/// 同时下载的文件数，以及所有文件加起来的连接数（分片下载每个分片占一个连接）。
+///
+/// 队列按优先级和顺序调度，可以随时暂停、恢复、移除和调整顺序。
+/// 调用 [`open_queue_file`](Self::open_queue_file) 之后，
+/// 每次修改都会写回队列文件，重启后再次打开即可继续；
+/// 分片下载会从分片记录处续传。
+///
+/// 可以安全地 `clone`，所有副本共享同一个队列。
+#[derive(Debug, Clone)]
+pub struct TransferManager {
+    inner: Arc<TransferManagerInner>,
+}
+
+impl TransferManager {
+    pub(crate) fn new(
+        child_clients: ReactiveChildClients,
+        global_config: GlobalConfig,
+    ) -> Self {
+        let state = QueueState {
+            next_id: 1,
+            next_run: 0,
+            entries: vec![],
+            max_concurrent_files: DEFAULT_MAX_CONCURRENT_FILES,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            connection_limiter: Arc::new(Semaphore::new(
+                DEFAULT_MAX_CONNECTIONS,
+            )),
+            queue_file: None,
+        };
+
+        Self {
+            inner: Arc::new(TransferManagerInner {
+                state: Mutex::new(state),
+                save_lock: tokio::sync::Mutex::new(()),
+                child_clients,
+                global_config,
+            }),
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, QueueState> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn max_concurrent_files(&self) -> usize {
+        self.lock_state().max_concurrent_files
+    }
+
+    /// 设置同时下载的文件数，至少为 1
+    ///
+    /// 调大后立即开始排队中的任务，调小时已经在下载的任务不受影响。
+    pub fn set_max_concurrent_files(&self, count: usize) {
+        self.lock_state().max_concurrent_files = count.max(1);
+        self.schedule();
+    }
+
+    pub fn max_connections(&self) -> usize {
+        self.lock_state().max_connections
+    }
+
+    /// 设置所有文件加起来的最大连接数，至少为 1
+    ///
+    /// 对之后开始的任务生效，已经在下载的任务沿用原来的限制直到结束。
+    pub fn set_max_connections(&self, count: usize) {
+        let count = count.max(1);
+        let mut state = self.lock_state();
+        state.max_connections = count;
+        state.connection_limiter = Arc::new(Semaphore::new(count));
+    }
+
+    /// 把文件加入队列，返回任务编号
+    ///
+    /// # 参数
+    /// * `key` - 文件所属的账号，开始下载时按它获取最新的凭据
+    /// * `file` - 要下载的文件，不能是文件夹；它的资源配置（下载方式、限速等）
+    ///   会随任务一起保存，之后对它的修改依然会作用于排队的任务
+    /// * `save_absolute_path` - 保存目录，与 `download` 的参数相同
+    /// * `priority` - 优先级
+    pub async fn enqueue(
+        &self,
+        key: &ClientKey,
+        file: &ResourcesFile,
+        save_absolute_path: &str,
+        priority: TransferPriority,
+    ) -> Result<TransferId, TransferManagerError> {
+        if !self.inner.child_clients.receiver.borrow().contains_key(key) {
+            return Err(TransferManagerError::AccountNotFound(
+                key.get_username(),
+            ));
+        }
+
+        let data = file.get_data();
+        if data.is_dir {
+            return Err(TransferManagerError::NotAFile(data.name.clone()));
+        }
+
+        let id = {
+            let mut state = self.lock_state();
+            let id = state.next_id;
+            state.next_id += 1;
+
+            state.entries.push(TransferEntry {
+                id,
+                key: key.clone(),
+                data,
+                config: file.get_reactive_config(),
+                save_absolute_path: save_absolute_path.to_string(),
+                priority,
+                state: QueuedTransferState::Pending,
+                resources_file: None,
+                handle: None,
+                run: 0,
+            });
+
+            id
+        };
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(id)
+    }
+
+    /// 按队列顺序列出所有任务，包括已经完成和出错的
+    pub fn list(&self) -> Vec<TransferInfo> {
+        self.lock_state()
+            .entries
+            .iter()
+            .map(TransferEntry::to_info)
+            .collect()
+    }
+
+    /// 获取单个任务的快照
+    pub fn get(&self, id: TransferId) -> Option<TransferInfo> {
+        self.lock_state()
+            .entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(TransferEntry::to_info)
+    }
+
+    /// 暂停任务
+    ///
+    /// 正在下载的任务会被停止并让出名额，已经下载的部分保留，恢复后续传。
+    pub async fn pause(
+        &self,
+        id: TransferId,
+    ) -> Result<(), TransferManagerError> {
+        let handle = {
+            let mut state = self.lock_state();
+            let index = state.position(id)?;
+            let entry = &mut state.entries[index];
+
+            // 下载已经结束、还没来得及更新状态的任务交给 finish 处理，
+            // 否则已经完成的任务会停在暂停状态，恢复后又下载一遍
+            if entry
+                .handle
+                .as_ref()
+                .is_some_and(DownloadHandle::is_finished)
+            {
+                return Ok(());
+            }
+
+            match entry.state {
+                QueuedTransferState::Pending
+                | QueuedTransferState::Running => {
+                    entry.state = QueuedTransferState::Paused;
+                    entry.handle.take()
+                }
+                _ => return Ok(()),
+            }
+        };
+
+        if let Some(handle) = handle {
+            handle.cancel(true).await?;
+        }
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(())
+    }
+
+    /// 恢复被暂停或出错的任务，重新排队
+    pub async fn resume(
+        &self,
+        id: TransferId,
+    ) -> Result<(), TransferManagerError> {
+        {
+            let mut state = self.lock_state();
+            let index = state.position(id)?;
+            let entry = &mut state.entries[index];
+
+            match entry.state {
+                QueuedTransferState::Paused
+                | QueuedTransferState::Failed(_) => {
+                    entry.state = QueuedTransferState::Pending;
+                }
+                _ => return Ok(()),
+            }
+        }
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(())
+    }
+
+    /// 从队列中移除任务，正在下载时会先停止
+    ///
+    /// `keep_partial` 为 `false` 时删除没下载完的文件和分片记录，
+    /// 已经下载完成的文件不会被删除。
+    pub async fn remove(
+        &self,
+        id: TransferId,
+        keep_partial: bool,
+    ) -> Result<(), TransferManagerError> {
+        let entry = {
+            let mut state = self.lock_state();
+            let index = state.position(id)?;
+            state.entries.remove(index)
+        };
+
+        match entry.handle {
+            Some(handle) => handle.cancel(keep_partial).await?,
+            None if !keep_partial => {
+                remove_stale_partial_files(&entry).await?;
+            }
+            None => {}
+        }
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(())
+    }
+
+    /// 修改任务的优先级，对还没开始的任务生效
+    pub async fn set_priority(
+        &self,
+        id: TransferId,
+        priority: TransferPriority,
+    ) -> Result<(), TransferManagerError> {
+        {
+            let mut state = self.lock_state();
+            let index = state.position(id)?;
+            state.entries[index].priority = priority;
+        }
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(())
+    }
+
+    /// 把任务移动到队列的第 `index` 个位置（从 0 开始，超出范围时放到末尾）
+    ///
+    /// 只影响同一优先级内的先后顺序，不会打断正在下载的任务。
+    pub async fn reorder(
+        &self,
+        id: TransferId,
+        index: usize,
+    ) -> Result<(), TransferManagerError> {
+        {
+            let mut state = self.lock_state();
+            let position = state.position(id)?;
+            let entry = state.entries.remove(position);
+            let index = index.min(state.entries.len());
+            state.entries.insert(index, entry);
+        }
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(())
+    }
+
+    /// 打开队列文件
+    ///
+    /// 文件已存在时把其中的任务加入队列（之前在下载中的任务重新排队），
+    /// 之后每次修改都会写回这个文件，返回恢复出的任务编号。
+    ///
+    /// 恢复出的任务在开始时才查找账号，请先导入账号再打开队列文件。
+    /// 每个管理器只能打开一次队列文件，再次调用返回
+    /// [`QueueFileAlreadyOpen`](TransferManagerError::QueueFileAlreadyOpen)，
+    /// 避免同一批任务被重复加入队列。
+    pub async fn open_queue_file(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<TransferId>, TransferManagerError> {
+        let records = match tokio::fs::read(path).await {
+            Ok(json) => {
+                let file: TransferQueueFile =
+                    serde_json::from_slice(&json)?;
+                if file.version != TRANSFER_QUEUE_VERSION {
+                    return Err(TransferManagerError::UnsupportedVersion(
+                        file.version,
+                    ));
+                }
+                file.transfers
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e.into()),
+        };
+
+        // 先全部解析，任意一条无效都不会加入队列
+        let mut restored = Vec::with_capacity(records.len());
+        for record in records {
+            let key = record
+                .client_key()
+                .map_err(TransferManagerError::InvalidRecord)?;
+            let data = record.to_resource_file_data(key.get_base_url());
+            restored.push((key, data, record));
+        }
+
+        let ids = {
+            let mut state = self.lock_state();
+
+            if let Some(opened) = &state.queue_file {
+                return Err(TransferManagerError::QueueFileAlreadyOpen(
+                    opened.clone(),
+                ));
+            }
+
+            let mut ids = Vec::with_capacity(restored.len());
+
+            for (key, data, record) in restored {
+                let id = state.next_id;
+                state.next_id += 1;
+
+                state.entries.push(TransferEntry {
+                    id,
+                    key,
+                    data: Arc::new(data),
+                    config: ResourceConfig::new(record.config),
+                    save_absolute_path: record.save_absolute_path,
+                    priority: record.priority,
+                    state: if record.paused {
+                        QueuedTransferState::Paused
+                    } else {
+                        QueuedTransferState::Pending
+                    },
+                    resources_file: None,
+                    handle: None,
+                    run: 0,
+                });
+                ids.push(id);
+            }
+
+            state.queue_file = Some(path.to_path_buf());
+            ids
+        };
+
+        self.persist().await?;
+        self.schedule();
+
+        Ok(ids)
+    }
+
+    /// 在名额范围内开始排队中的任务
+    fn schedule(&self) {
+        let mut state = self.lock_state();
+
+        while state.running_count() < state.max_concurrent_files {
+            let Some(index) = state.next_pending() else {
+                break;
+            };
+
+            self.start(&mut state, index);
+        }
+    }
+
+    /// 开始第 `index` 个任务，调用方持有队列的锁
+    fn start(&self, state: &mut QueueState, index: usize) {
+        state.next_run += 1;
+        let run = state.next_run;
+        let connection_limiter = state.connection_limiter.clone();
+        let entry = &mut state.entries[index];
+
+        // 每次开始时重新获取账号，账号更新过凭据也能使用最新的
+        let http_client = self
+            .inner
+            .child_clients
+            .receiver
+            .borrow()
+            .get(&entry.key)
+            .cloned();
+
+        let Some(http_client) = http_client else {
+            entry.state = QueuedTransferState::Failed(
+                TransferManagerError::AccountNotFound(
+                    entry.key.get_username(),
+                )
+                .to_string(),
+            );
+            return;
+        };
+
+        let mut resources_file = ResourcesFile::new(
+            (*entry.data).clone(),
+            http_client,
+            self.inner.global_config.clone(),
+        );
+        resources_file.set_reactive_config(entry.config.clone());
+        resources_file.set_connection_limiter(connection_limiter);
+
+        let manager = self.clone();
+        let id = entry.id;
+        let save_absolute_path = entry.save_absolute_path.clone();
+        let download_file = resources_file.clone();
+
+        let task = tokio::spawn(async move {
+            let result = download_file.download(&save_absolute_path).await;
+            manager.finish(id, run, result.as_ref().err()).await;
+            result
+        });
+
+        entry.handle =
+            Some(DownloadHandle::new(resources_file.clone(), task));
+        entry.resources_file = Some(resources_file);
+        entry.state = QueuedTransferState::Running;
+        entry.run = run;
+    }
+
+    /// 下载任务结束后更新状态，并开始下一个任务
+    async fn finish(
+        &self,
+        id: TransferId,
+        run: u64,
+        error: Option<&impl ToString>,
+    ) {
+        {
+            let mut state = self.lock_state();
+
+            // 移除或重新开始之后，旧的任务不再修改状态
+            if let Some(entry) = state
+                .entries
+                .iter_mut()
+                .find(|entry| entry.id == id && entry.run == run)
+            {
+                match (&entry.state, error) {
+                    (QueuedTransferState::Running, Some(e)) => {
+                        entry.handle = None;
+                        entry.state =
+                            QueuedTransferState::Failed(e.to_string());
+                    }
+                    // 下载完成的同时被暂停，文件已经完整落地，不需要再恢复
+                    (
+                        QueuedTransferState::Running
+                        | QueuedTransferState::Paused,
+                        None,
+                    ) => {
+                        entry.handle = None;
+                        entry.state = QueuedTransferState::Done;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // 写入失败时不影响下载结果，下次修改队列时会再次写入
+        if let Err(e) = self.persist().await {
+            eprintln!("[transfer_manager] 写入队列文件失败: {}", e);
+        }
+        self.schedule();
+    }
+
+    /// 把还没完成的任务写入队列文件，没有打开队列文件时什么也不做
+    async fn persist(&self) -> Result<(), TransferManagerError> {
+        let _guard = self.inner.save_lock.lock().await;
+
+        let (path, file) = {
+            let state = self.lock_state();
+            let Some(path) = state.queue_file.clone() else {
+                return Ok(());
+            };
+
+            let transfers = state
+                .entries
+                .iter()
+                .filter(|entry| entry.state != QueuedTransferState::Done)
+                .map(TransferEntry::to_record)
+                .collect();
+
+            (
+                path,
+                TransferQueueFile {
+                    version: TRANSFER_QUEUE_VERSION,
+                    transfers,
+                },
+            )
+        };
+
+        let json = serde_json::to_vec_pretty(&file)?;
+
+        // 先写临时文件再替换，写到一半退出也不会留下损坏的队列文件
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        tokio::fs::write(&temp, json).await?;
+        tokio::fs::rename(&temp, &path).await?;
+
+        Ok(())
+    }
+}
+
+/// 删除没有在运行的任务留下的未完成文件
+///
+/// 本次运行中下载过的任务知道自己写到了哪个文件；
+/// 从队列文件恢复、还没开始过的任务只删除旁边有分片记录的文件，
+/// 没有记录的文件无法确定是不是这个任务留下的。
+async fn remove_stale_partial_files(
+    entry: &TransferEntry,
+) -> Result<(), TransferManagerError> {
+    if let Some(resources_file) = &entry.resources_file {
+        remove_partial_files(&resources_file.get_reactive_state()).await?;
+        return Ok(());
+    }
+
+    let target =
+        Path::new(&entry.save_absolute_path).join(&entry.data.name);
+    remove_journaled_files(&target).await?;
+
+    Ok(())
+}
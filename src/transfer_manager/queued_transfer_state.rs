@@ -0,0 +1,24 @@
+/// 传输任务在队列里的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueuedTransferState {
+    /// 等待空闲名额
+    Pending,
+    Running,
+    /// 被手动暂停，恢复之前不会开始
+    Paused,
+    Done,
+    /// 下载出错，内容为错误信息，恢复后重新排队
+    Failed(String),
+}
+
+impl QueuedTransferState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueuedTransferState::Pending => "pending",
+            QueuedTransferState::Running => "running",
+            QueuedTransferState::Paused => "paused",
+            QueuedTransferState::Done => "done",
+            QueuedTransferState::Failed(_) => "failed",
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// 传输优先级，优先级高的先开始，同一优先级按队列顺序
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum TransferPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl TransferPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferPriority::Low => "low",
+            TransferPriority::Normal => "normal",
+            TransferPriority::High => "high",
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::client::structs::client_key::ClientKey;
+use crate::resource_file::structs::resource_config::ResourceConfigData;
+use crate::resource_file::structs::resource_file_data::ResourceFileData;
+use crate::transfer_manager::transfer_priority::TransferPriority;
+use chrono::{DateTime, FixedOffset};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// 当前队列文件的格式版本
+pub const TRANSFER_QUEUE_VERSION: u32 = 1;
+
+/// 单个传输任务的持久化记录
+///
+/// 远程文件的信息直接保存下来，重启后不需要重新列目录；
+/// 校验值不保存，恢复后的下载只按大小校验。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTransferRecord {
+    pub base_url: String,
+    pub username: String,
+    pub relative_root_path: String,
+    pub absolute_path: String,
+    pub name: String,
+    pub size: Option<u64>,
+    pub last_modified: Option<DateTime<FixedOffset>>,
+    pub mime: Option<String>,
+    pub owner: Option<String>,
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub privileges: Vec<String>,
+    pub save_absolute_path: String,
+    #[serde(default)]
+    pub priority: TransferPriority,
+    #[serde(default)]
+    pub paused: bool,
+    /// 加入队列时的资源配置，旧版本的队列文件没有这一项
+    #[serde(default)]
+    pub config: ResourceConfigData,
+}
+
+/// 队列文件的顶层结构，只保存还没有完成的任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferQueueFile {
+    pub version: u32,
+    pub transfers: Vec<QueuedTransferRecord>,
+}
+
+impl QueuedTransferRecord {
+    pub(crate) fn new(
+        key: &ClientKey,
+        data: &ResourceFileData,
+        save_absolute_path: &str,
+        priority: TransferPriority,
+        paused: bool,
+        config: ResourceConfigData,
+    ) -> Self {
+        Self {
+            base_url: key.get_base_url().to_string(),
+            username: key.get_username(),
+            relative_root_path: data.relative_root_path.clone(),
+            absolute_path: data.absolute_path.clone(),
+            name: data.name.clone(),
+            size: data.size,
+            last_modified: data.last_modified,
+            mime: data.mime.clone(),
+            owner: data.owner.clone(),
+            etag: data.etag.clone(),
+            privileges: data.privileges.clone(),
+            save_absolute_path: save_absolute_path.to_string(),
+            priority,
+            paused,
+            config,
+        }
+    }
+
+    pub(crate) fn client_key(&self) -> Result<ClientKey, String> {
+        ClientKey::new(&self.base_url, &self.username)
+    }
+
+    pub(crate) fn to_resource_file_data(
+        &self,
+        base_url: Url,
+    ) -> ResourceFileData {
+        ResourceFileData {
+            base_url,
+            relative_root_path: self.relative_root_path.clone(),
+            absolute_path: self.absolute_path.clone(),
+            name: self.name.clone(),
+            is_dir: false,
+            size: self.size,
+            last_modified: self.last_modified,
+            mime: self.mime.clone(),
+            owner: self.owner.clone(),
+            etag: self.etag.clone(),
+            privileges: self.privileges.clone(),
+            checksums: vec![],
+        }
+    }
+}
@@ -2,8 +2,15 @@ use crate::{load_account, WEBDAV_ENV_PATH_1};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
-use webdav_client::client::traits::account::{Account, AccountError};
+use webdav_client::client::structs::account_config::AccountConfigData;
+use webdav_client::client::structs::account_credentials::AccountCredentials;
+use webdav_client::client::traits::account::{
+    Account, AccountError, UpdateAccountError,
+};
 use webdav_client::client::WebDavClient;
+use webdav_client::client::structs::client_value::HttpClient;
+use webdav_client::client::structs::reactive_child_clients::ReactiveChildClients;
+use webdav_client::client::enums::account_event::AccountEvent;
 
 #[tokio::test]
 async fn test_add_account() -> Result<(), AccountError> {
@@ -154,3 +161,190 @@ async fn test_remove_account_force() -> Result<(), AccountError> {
         }
     }
 }
+
+#[tokio::test]
+async fn test_account_events() -> Result<(), String> {
+    let client = WebDavClient::new();
+    let mut events = client.subscribe_account_events();
+
+    let key = client
+        .add_account("https://dav.example.com/dav/", "username", "password")
+        .map_err(|e| e.to_string())?;
+    client
+        .add_account("https://dav.example.com/dav/", "username", "password")
+        .map_err(|e| e.to_string())?;
+    client.remove_account(&key).map_err(|e| e.to_string())?;
+
+    let expected = vec![
+        AccountEvent::AccountAdded(key.clone()),
+        AccountEvent::AccountUpdated(key.clone()),
+        AccountEvent::AccountRemoved(key),
+    ];
+
+    for expected_event in expected {
+        let event = events.recv().await.map_err(|e| e.to_string())?;
+        println!("收到账号事件：{:?}", event);
+        assert_eq!(event, expected_event);
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_add_accounts() -> Result<(), String> {
+    let client = Arc::new(WebDavClient::new());
+    let mut events = client.subscribe_account_events();
+
+    // 并发添加时每个账号都必须保留下来，且都是 Added 事件
+    let tasks: Vec<_> = (0..32)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client.add_account(
+                    "https://dav.example.com/dav/",
+                    &format!("user-{}", i),
+                    "password",
+                )
+            })
+        })
+        .collect();
+
+    let mut keys = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        keys.push(
+            task.await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?,
+        );
+    }
+
+    for key in &keys {
+        client.get_http_client(key).map_err(|e| e.to_string())?;
+    }
+
+    for _ in 0..32 {
+        let event = events.recv().await.map_err(|e| e.to_string())?;
+        assert!(matches!(event, AccountEvent::AccountAdded(_)));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_account() -> Result<(), String> {
+    let client = WebDavClient::new();
+    let base_url = "https://dav.example.com/dav/";
+
+    let key = client
+        .add_account(base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 模拟资源文件一直持有客户端，此时删除账号会失败，但更新不受影响
+    let held_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+
+    let new_key = client
+        .update_account(
+            &key,
+            &AccountCredentials::basic("username", "new_password"),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+    assert_eq!(new_key, key);
+
+    let expected = HttpClient::new(base_url, "username", "new_password")?;
+    assert_eq!(*held_client, expected);
+
+    println!("更新账号测试成功：{:?}", held_client);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_account_in_place_notifies_watchers()
+-> Result<(), String> {
+    let clients = ReactiveChildClients::new();
+    let key = clients
+        .add_account("https://dav.example.com/dav/", "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let mut receiver = clients.get_reactive_receiver();
+    receiver.mark_unchanged();
+
+    clients
+        .update_account(
+            &key,
+            &AccountCredentials::basic("username", "new_password"),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+
+    // key 没变，但订阅账号表的一方需要知道账号已经更新
+    assert!(receiver.has_changed().map_err(|e| e.to_string())?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_account_username_and_options() -> Result<(), String> {
+    let client = WebDavClient::new();
+    let base_url = "https://dav.example.com/dav/";
+
+    let key = client
+        .add_account(base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    let held_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+    let mut events = client.subscribe_account_events();
+
+    let options = AccountConfigData {
+        max_retries: Some(7),
+        ..Default::default()
+    };
+    let new_key = client
+        .update_account(
+            &key,
+            &AccountCredentials::basic("renamed", "new_password"),
+            Some(options.clone()),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // 换了用户名之后账号移到新的 key 下，但还是同一个客户端
+    assert_eq!(new_key.get_username(), "renamed");
+    assert!(client.get_http_client(&key).is_err());
+    let moved_client =
+        client.get_http_client(&new_key).map_err(|e| e.to_string())?;
+    assert!(Arc::ptr_eq(&held_client, &moved_client));
+
+    let expected = HttpClient::new(base_url, "renamed", "new_password")?;
+    assert_eq!(*held_client, expected);
+    assert_eq!(held_client.get_config_data(), options);
+
+    assert_eq!(
+        events.recv().await.map_err(|e| e.to_string())?,
+        AccountEvent::AccountRemoved(key)
+    );
+    assert_eq!(
+        events.recv().await.map_err(|e| e.to_string())?,
+        AccountEvent::AccountAdded(new_key.clone())
+    );
+
+    // 新用户名已被其他账号占用时，更新失败且原账号保持不变
+    let other_key = client
+        .add_account(base_url, "other", "password")
+        .map_err(|e| e.to_string())?;
+    match client.update_account(
+        &new_key,
+        &AccountCredentials::basic("other", "password"),
+        None,
+    ) {
+        Err(AccountError::UpdateAccountError(
+            UpdateAccountError::KeyConflict(_),
+        )) => {}
+        other => return Err(format!("期望 KeyConflict，实际为 {:?}", other)),
+    }
+    assert_eq!(*held_client, expected);
+    client.get_http_client(&other_key).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
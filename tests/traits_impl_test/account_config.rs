@@ -0,0 +1,81 @@
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::structs::resource_file_data::ResourceFileData;
+
+#[tokio::test]
+async fn test_download_config_resolution() -> Result<(), String> {
+    let client = WebDavClient::new();
+    let base_url = "https://dav.example.com/dav/";
+
+    let key = client
+        .add_account(base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let data = ResourceFileData {
+        base_url: reqwest::Url::parse(base_url)
+            .map_err(|e| e.to_string())?,
+        relative_root_path: "/a.bin".to_string(),
+        absolute_path: format!("{}a.bin", base_url),
+        name: "a.bin".to_string(),
+        is_dir: false,
+        size: Some(1024),
+        last_modified: None,
+        mime: None,
+        owner: None,
+        etag: None,
+        privileges: vec![],
+        checksums: vec![],
+    };
+
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+    let resources_file =
+        data.to_resources_file(http_client, client.get_global_config());
+    let download_config = resources_file.get_download_config();
+
+    // 账号和资源都没有设置时沿用全局配置
+    let global_timeout = client
+        .get_global_config()
+        .get_current()
+        .map(|cfg| cfg.timeout_secs);
+    assert_eq!(download_config.timeout_secs(), global_timeout);
+    assert_eq!(download_config.max_thread_count(), None);
+
+    // 账号配置覆盖全局配置，并且对已经拿到的资源文件实时生效
+    let account_config =
+        client.get_account_config(&key).map_err(|e| e.to_string())?;
+    account_config
+        .set_max_speed(Some(1024 * 1024))
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(1))
+        .map_err(|e| e.to_string())?;
+    assert_eq!(download_config.max_speed(), Some(1024 * 1024));
+    assert_eq!(download_config.max_thread_count(), Some(1));
+
+    // 资源配置优先级最高
+    resources_file
+        .get_reactive_config()
+        .update_field(|cfg| cfg.max_thread_count = Some(4))
+        .map_err(|e| e.to_string())?;
+    assert_eq!(download_config.max_thread_count(), Some(4));
+
+    // 账号暂停时等待，恢复后继续
+    account_config.pause().map_err(|e| e.to_string())?;
+    assert!(download_config.is_paused());
+
+    let waiting_config = download_config.clone();
+    let waiting =
+        tokio::spawn(async move { waiting_config.wait_if_paused().await });
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished());
+
+    account_config.resume().map_err(|e| e.to_string())?;
+    tokio::time::timeout(Duration::from_secs(1), waiting)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
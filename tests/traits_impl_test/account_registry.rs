@@ -0,0 +1,118 @@
+use webdav_client::client::WebDavClient;
+use webdav_client::client::structs::account_registry_file::RegistryKey;
+use webdav_client::client::traits::account::Account;
+use webdav_client::client::traits::account_registry::{
+    AccountRegistry, AccountRegistryError,
+};
+
+#[tokio::test]
+async fn test_export_and_import_accounts() -> Result<(), String> {
+    let client = WebDavClient::new();
+
+    let key_1 = client
+        .add_account(
+            "https://dav.example.com/dav/",
+            "user-1",
+            "password-1",
+        )
+        .map_err(|e| e.to_string())?;
+    let key_2 = client
+        .add_account("https://nas.local:5006/", "user-2", "password-2")
+        .map_err(|e| e.to_string())?;
+
+    // 账号配置需要随账号一起导出
+    client
+        .get_account_config(&key_1)
+        .map_err(|e| e.to_string())?
+        .set_max_speed(Some(1024 * 1024))
+        .map_err(|e| e.to_string())?;
+
+    let path = std::env::temp_dir().join("webdav-client-accounts.json");
+    let passphrase = RegistryKey::Passphrase("口令".to_string());
+
+    let count = client
+        .export_accounts(&path, &passphrase)
+        .await
+        .map_err(|e| e.to_string())?;
+    assert_eq!(count, 2);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path)
+            .map_err(|e| e.to_string())?
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // 文件里不能出现明文密码
+    let text =
+        std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    assert!(!text.contains("password-1") && !text.contains("password-2"));
+
+    let restored_client = WebDavClient::new();
+    let mut keys = restored_client
+        .import_accounts(&path, &passphrase)
+        .await
+        .map_err(|e| e.to_string())?;
+    keys.sort_by_key(|k| k.get_username());
+
+    assert_eq!(keys, vec![key_1.clone(), key_2]);
+    let restored_config = restored_client
+        .get_account_config(&key_1)
+        .map_err(|e| e.to_string())?;
+    assert_eq!(
+        restored_config.get_current().and_then(|cfg| cfg.max_speed),
+        Some(1024 * 1024)
+    );
+
+    // 错误口令必须解密失败
+    let wrong = RegistryKey::Passphrase("wrong".to_string());
+    match WebDavClient::new().import_accounts(&path, &wrong).await {
+        Err(AccountRegistryError::Decrypt(_)) => {}
+        other => return Err(format!("期望 Decrypt，实际为 {:?}", other)),
+    }
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_rejects_excessive_kdf_iterations() -> Result<(), String> {
+    let client = WebDavClient::new();
+    client
+        .add_account("https://dav.example.com/dav/", "user", "password")
+        .map_err(|e| e.to_string())?;
+
+    let path =
+        std::env::temp_dir().join("webdav-client-accounts-kdf.json");
+    let passphrase = RegistryKey::Passphrase("口令".to_string());
+
+    client
+        .export_accounts(&path, &passphrase)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 篡改派生轮数，导入必须立即拒绝而不是真的去跑 u32::MAX 轮
+    let text =
+        std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut file: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    file["kdf"]["iterations"] = serde_json::json!(u32::MAX);
+    std::fs::write(&path, file.to_string()).map_err(|e| e.to_string())?;
+
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        WebDavClient::new().import_accounts(&path, &passphrase),
+    )
+    .await
+    .map_err(|_| "导入超时".to_string())?;
+
+    let _ = std::fs::remove_file(&path);
+
+    match result {
+        Err(AccountRegistryError::KeyMismatch(_)) => Ok(()),
+        other => Err(format!("期望 KeyMismatch，实际为 {:?}", other)),
+    }
+}
@@ -0,0 +1,153 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, is_range_probe, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::chunk_strategy::ChunkStrategy;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::structs::adaptive_chunking::AdaptiveChunking;
+use webdav_client::resource_file::structs::transfer_progress::TransferState;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 8 * 1024 * 1024;
+const MIN_CHUNK_SIZE: u64 = 256 * 1024;
+const MAX_CHUNK_SIZE: u64 = 1024 * 1024;
+
+// 每个分片请求都要等这么久才响应，延迟远大于传输时间
+const RESPONSE_LATENCY: Duration = Duration::from_millis(50);
+
+/// 启动服务器，返回同时在处理的分片请求数的峰值
+fn start_counting_server() -> (MockServer, Arc<AtomicUsize>) {
+    let content = test_content(CONTENT_LEN);
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_recorder = peak.clone();
+
+    let server = MockServer::start(move |request, _| {
+        if is_range_probe(request) {
+            return range_response(request, &content);
+        }
+
+        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        peak_recorder.fetch_max(current, Ordering::SeqCst);
+
+        std::thread::sleep(RESPONSE_LATENCY);
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        range_response(request, &content)
+    });
+
+    (server, peak)
+}
+
+fn adaptive_strategy(max_connections: u32) -> ChunkStrategy {
+    ChunkStrategy::Adaptive(AdaptiveChunking {
+        min_chunk_size: MIN_CHUNK_SIZE,
+        max_chunk_size: MAX_CHUNK_SIZE,
+        min_connections: 1,
+        max_connections,
+        target_request_ms: 1,
+    })
+}
+
+#[tokio::test]
+async fn test_adaptive_chunking() -> Result<(), String> {
+    let (server, peak) = start_counting_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_chunk_strategy(Some(adaptive_strategy(3)))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("adaptive-chunking");
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+    assert!(!dir.join("a.bin.wdpart").exists());
+
+    let progress =
+        resources_file.get_progress().get_current().ok_or("没有进度")?;
+    assert_eq!(progress.state, TransferState::Done);
+
+    // 第一个分片用下限，测得延迟远大于传输时间后分片变大
+    let sizes: Vec<u64> =
+        progress.chunks.iter().map(|chunk| chunk.total_bytes()).collect();
+    assert_eq!(sizes[0], MIN_CHUNK_SIZE);
+    assert!(sizes.iter().any(|&size| size > MIN_CHUNK_SIZE));
+    assert!(sizes.iter().all(|&size| size <= MAX_CHUNK_SIZE));
+    assert_eq!(sizes.iter().sum::<u64>(), CONTENT_LEN as u64);
+    assert!(
+        progress
+            .chunks
+            .iter()
+            .all(|chunk| chunk.state == TransferState::Done)
+    );
+
+    // 分片首尾相接，没有重叠也没有遗漏
+    for pair in progress.chunks.windows(2) {
+        assert_eq!(pair[0].end + 1, pair[1].start);
+    }
+
+    // 从一个连接开始，吞吐量随连接数提升时继续增加，但不超过上限
+    let peak = peak.load(Ordering::SeqCst);
+    assert!(peak >= 2, "并发数没有增加: {}", peak);
+    assert!(peak <= 3, "并发数超过上限: {}", peak);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_adaptive_chunking_max_thread_count() -> Result<(), String> {
+    let (server, peak) = start_counting_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 全局开启自适应，文件自己限制了线程数
+    client
+        .get_global_config()
+        .set_chunk_strategy(adaptive_strategy(4))
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("adaptive-chunking-thread-count");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 64 * 1024;
+const OLD_CONTENT: &[u8] = b"previous good copy";
+
+#[tokio::test]
+async fn test_atomic_download_replaces_target() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_atomic_write(true)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("atomic-replace");
+    std::fs::write(dir.join("a.bin"), OLD_CONTENT)
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert!(!dir.join(".a.bin.wdtmp").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_atomic_download_failure_keeps_target() -> Result<(), String>
+{
+    let server = MockServer::start(|_, _| MockResponse::new(404, vec![]));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("atomic-failure");
+    std::fs::write(dir.join("a.bin"), OLD_CONTENT)
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_atomic_write(Some(true))
+        .map_err(|e| e.to_string())?;
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+    assert!(result.is_err());
+
+    let local =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(local, OLD_CONTENT);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_atomic_download_size_mismatch_keeps_target()
+-> Result<(), String> {
+    // 服务器返回的内容比列目录时的大小短
+    let short = test_content(CONTENT_LEN - 1);
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &short)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("atomic-size-mismatch");
+    std::fs::write(dir.join("a.bin"), OLD_CONTENT)
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_atomic_write(Some(true))
+        .map_err(|e| e.to_string())?;
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+    assert!(result.is_err());
+
+    let local =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(local, OLD_CONTENT);
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_file_data, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use chrono::{DateTime, Duration, Utc};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::conflict_policy::ConflictPolicy;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::enums::download_outcome::DownloadOutcome;
+use webdav_client::resource_file::traits::download::{
+    Download, DownloadError,
+};
+
+const CONTENT_LEN: usize = 64 * 1024;
+
+fn serve_content() -> MockServer {
+    let content = test_content(CONTENT_LEN);
+    MockServer::start(move |request, _| range_response(request, &content))
+}
+
+#[tokio::test]
+async fn test_conflict_overwrite_same_size_chunked() -> Result<(), String>
+{
+    let server = serve_content();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 大小相同但内容不同的旧文件，以前分片下载会当成已经下载完成
+    let dir = temp_download_dir("conflict-overwrite");
+    std::fs::write(dir.join("a.bin"), vec![0u8; CONTENT_LEN])
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+    assert_eq!(
+        resources_file
+            .get_outcome()
+            .get_current_borrow()
+            .clone()
+            .flatten(),
+        Some(DownloadOutcome::Downloaded(dir.join("a.bin")))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conflict_skip() -> Result<(), String> {
+    let server = serve_content();
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_conflict_policy(ConflictPolicy::Skip)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("conflict-skip");
+    std::fs::write(dir.join("a.bin"), b"local")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let local =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(local, b"local");
+    assert_eq!(server.request_count(), 0);
+    assert_eq!(
+        resources_file
+            .get_outcome()
+            .get_current_borrow()
+            .clone()
+            .flatten(),
+        Some(DownloadOutcome::Skipped(dir.join("a.bin")))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conflict_rename() -> Result<(), String> {
+    let server = serve_content();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("conflict-rename");
+    std::fs::write(dir.join("a.bin"), b"first")
+        .map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("a (1).bin"), b"second")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_conflict_policy(Some(ConflictPolicy::Rename))
+        .map_err(|e| e.to_string())?;
+
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 已有的两个文件保持原样，新文件保存为下一个编号
+    let first =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(first, b"first");
+    let second =
+        std::fs::read(dir.join("a (1).bin")).map_err(|e| e.to_string())?;
+    assert_eq!(second, b"second");
+
+    let renamed = dir.join("a (2).bin");
+    let downloaded = std::fs::read(&renamed).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+    assert_eq!(
+        resources_file
+            .get_outcome()
+            .get_current_borrow()
+            .clone()
+            .flatten(),
+        Some(DownloadOutcome::Renamed(renamed))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conflict_keep_newer() -> Result<(), String> {
+    let server = serve_content();
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_conflict_policy(ConflictPolicy::KeepNewer)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("conflict-keep-newer");
+    std::fs::write(dir.join("a.bin"), b"local")
+        .map_err(|e| e.to_string())?;
+
+    let build = |last_modified: DateTime<Utc>| {
+        let mut data = mock_file_data(&server, "a.bin", CONTENT_LEN);
+        data.last_modified = Some(last_modified.fixed_offset());
+        data.to_resources_file(
+            http_client.clone(),
+            client.get_global_config(),
+        )
+    };
+
+    // 远程文件比本地旧，保留本地文件
+    build(Utc::now() - Duration::days(1))
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let local =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(local, b"local");
+    assert_eq!(server.request_count(), 0);
+
+    // 远程文件比本地新，覆盖本地文件
+    build(Utc::now() + Duration::days(1))
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let local =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(local, test_content(CONTENT_LEN));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conflict_fail() -> Result<(), String> {
+    let server = serve_content();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    client
+        .get_account_config(&key)
+        .map_err(|e| e.to_string())?
+        .set_conflict_policy(Some(ConflictPolicy::Fail))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("conflict-fail");
+    std::fs::write(dir.join("a.bin"), b"local")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    assert!(matches!(
+        result,
+        Err(DownloadError::LocalFileExists(path)) if path == dir.join("a.bin")
+    ));
+    assert_eq!(server.request_count(), 0);
+
+    Ok(())
+}
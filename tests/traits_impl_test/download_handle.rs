@@ -0,0 +1,179 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, is_range_probe, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use std::path::Path;
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::structs::download_handle::DownloadHandle;
+use webdav_client::resource_file::structs::transfer_progress::TransferState;
+use webdav_client::resource_file::traits::start_download::StartDownload;
+
+// 分片大小是 4M，三个分片
+const CHUNKED_LEN: usize = 9 * 1024 * 1024;
+const CONTENT_LEN: usize = 64 * 1024;
+
+// 第一个分片立即返回，其它分片等这么久才开始响应
+const SLOW_CHUNK_DELAY: Duration = Duration::from_millis(1500);
+
+fn journal_of(path: &Path) -> std::path::PathBuf {
+    let mut journal = path.as_os_str().to_owned();
+    journal.push(".wdpart");
+    journal.into()
+}
+
+/// 第一个分片完成、其它分片还在等待响应时返回句柄
+async fn start_slow_chunked_download(
+    name: &str,
+) -> Result<(DownloadHandle, std::path::PathBuf, MockServer), String> {
+    let content = test_content(CHUNKED_LEN);
+    let server = MockServer::start(move |request, _| {
+        let response = range_response(request, &content);
+
+        let is_first_chunk = request
+            .headers
+            .get("range")
+            .is_some_and(|range| range.starts_with("bytes=0-"));
+
+        if is_range_probe(request) || is_first_chunk {
+            response
+        } else {
+            response.delay(SLOW_CHUNK_DELAY)
+        }
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CHUNKED_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir(name);
+    let handle = resources_file.start_download(dir.to_str().unwrap());
+
+    let mut watcher = handle.progress();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let progress =
+                watcher.changed().await.map_err(|e| e.to_string())?;
+            if progress
+                .chunks
+                .first()
+                .is_some_and(|chunk| chunk.state == TransferState::Done)
+            {
+                return Ok::<_, String>(());
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    Ok((handle, dir.join("a.bin"), server))
+}
+
+#[tokio::test]
+async fn test_download_handle_pause_resume() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let dir = temp_download_dir("handle-pause");
+
+    // 后台任务还没开始运行就已经暂停
+    let handle = resources_file.start_download(dir.to_str().unwrap());
+    handle.pause().map_err(|e| e.to_string())?;
+
+    let mut watcher = handle.progress();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let progress =
+                watcher.changed().await.map_err(|e| e.to_string())?;
+            if progress.state == TransferState::Paused {
+                return Ok::<_, String>(());
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    assert!(!handle.is_finished());
+
+    handle.resume().map_err(|e| e.to_string())?;
+
+    let resources_file = handle.await.map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(
+        resources_file.get_file_lock().get_current().map(|v| *v),
+        Some(false)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_handle_cancel_keep_partial() -> Result<(), String> {
+    let (handle, path, _server) =
+        start_slow_chunked_download("handle-cancel-keep").await?;
+
+    let progress = handle.progress();
+
+    handle.cancel(true).await.map_err(|e| e.to_string())?;
+
+    let cancelled = progress.borrow().ok_or("没有进度")?;
+    assert_eq!(cancelled.state, TransferState::Cancelled);
+    assert_eq!(cancelled.chunks[0].state, TransferState::Done);
+    assert!(
+        cancelled.chunks[1..]
+            .iter()
+            .all(|chunk| chunk.state == TransferState::Cancelled)
+    );
+
+    // 分片记录只包含第一个分片，被取消的分片不会再写入
+    let journal =
+        std::fs::read(journal_of(&path)).map_err(|e| e.to_string())?;
+    tokio::time::sleep(SLOW_CHUNK_DELAY * 2).await;
+    assert_eq!(
+        std::fs::read(journal_of(&path)).map_err(|e| e.to_string())?,
+        journal
+    );
+    assert!(path.exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_handle_cancel() -> Result<(), String> {
+    let (handle, path, _server) =
+        start_slow_chunked_download("handle-cancel").await?;
+
+    handle.cancel(false).await.map_err(|e| e.to_string())?;
+
+    assert!(!path.exists());
+    assert!(!journal_of(&path).exists());
+
+    // 分片任务已经被中止，不会再把记录写回来
+    tokio::time::sleep(SLOW_CHUNK_DELAY * 2).await;
+    assert!(!path.exists());
+    assert!(!journal_of(&path).exists());
+
+    Ok(())
+}
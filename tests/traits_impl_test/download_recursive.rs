@@ -0,0 +1,327 @@
+use crate::traits_impl_test::mock_server::{
+    MockRequest, MockResponse, MockServer, range_response,
+    temp_download_dir, test_content,
+};
+use std::time::UNIX_EPOCH;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::client::traits::download_recursive::DownloadRecursivePath;
+use webdav_client::resource_file::structs::recursive_download::{
+    RecursiveDownloadOptions, RecursiveDownloadOutcome,
+    RecursiveDownloadSkipReason,
+};
+
+const A_LEN: usize = 3000;
+const B_LEN: usize = 5000;
+
+// 所有条目共用的远程修改时间，2019-01-01T00:00:00Z
+const LAST_MODIFIED: &str = "Tue, 01 Jan 2019 00:00:00 GMT";
+const LAST_MODIFIED_SECS: u64 = 1_546_300_800;
+
+fn dir_response(href: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+         <D:resourcetype><D:collection/></D:resourcetype>\
+         <D:getlastmodified>{}</D:getlastmodified>\
+         </D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat>\
+         </D:response>",
+        href, LAST_MODIFIED
+    )
+}
+
+fn file_response(href: &str, len: usize) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+         <D:resourcetype/><D:getcontentlength>{}</D:getcontentlength>\
+         <D:getlastmodified>{}</D:getlastmodified>\
+         </D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat>\
+         </D:response>",
+        href, len, LAST_MODIFIED
+    )
+}
+
+fn multi_status(responses: &[String]) -> MockResponse {
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <D:multistatus xmlns:D=\"DAV:\">{}</D:multistatus>",
+        responses.concat()
+    );
+    MockResponse::new(207, body)
+        .header("Content-Type", "application/xml; charset=utf-8")
+}
+
+/// 远程目录结构：
+///
+/// ```text
+/// /dav/root/
+/// ├── a.bin
+/// ├── broken.bin（GET 返回 404）
+/// ├── empty/
+/// └── sub/
+///     └── b.bin
+/// ```
+fn tree_handler(request: &MockRequest) -> MockResponse {
+    let path = request.path.trim_end_matches('/');
+    let depth_zero =
+        request.headers.get("depth").map(String::as_str) == Some("0");
+
+    if request.method == "PROPFIND" {
+        return match path {
+            "/dav/root" if depth_zero => {
+                multi_status(&[dir_response("/dav/root/")])
+            }
+            "/dav/root" => multi_status(&[
+                dir_response("/dav/root/"),
+                file_response("/dav/root/a.bin", A_LEN),
+                file_response("/dav/root/broken.bin", 10),
+                dir_response("/dav/root/empty/"),
+                dir_response("/dav/root/sub/"),
+            ]),
+            "/dav/root/sub" => multi_status(&[
+                dir_response("/dav/root/sub/"),
+                file_response("/dav/root/sub/b.bin", B_LEN),
+            ]),
+            "/dav/root/empty" => {
+                multi_status(&[dir_response("/dav/root/empty/")])
+            }
+            _ => MockResponse::new(404, vec![]),
+        };
+    }
+
+    match path {
+        "/dav/root/a.bin" => range_response(request, &test_content(A_LEN)),
+        "/dav/root/sub/b.bin" => {
+            range_response(request, &test_content(B_LEN))
+        }
+        _ => MockResponse::new(404, vec![]),
+    }
+}
+
+#[tokio::test]
+async fn test_download_recursive() -> Result<(), String> {
+    let server = MockServer::start(|request, _| tree_handler(request));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("download_recursive");
+    let save_path = dir.to_string_lossy().to_string();
+
+    let report = client
+        .download_recursive(
+            &key,
+            "root",
+            &save_path,
+            RecursiveDownloadOptions::default(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 文件夹结构在本地重建，文件内容完整
+    let a = std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(a, test_content(A_LEN));
+    let b = std::fs::read(dir.join("sub").join("b.bin"))
+        .map_err(|e| e.to_string())?;
+    assert_eq!(b, test_content(B_LEN));
+    assert!(dir.join("empty").is_dir());
+
+    // 两个文件、两个文件夹成功，一个文件失败
+    assert_eq!(report.downloaded().count(), 4);
+    assert_eq!(report.skipped().count(), 0);
+
+    let failed: Vec<_> = report.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].remote_path, "/dav/root/broken.bin");
+    assert!(!report.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_recursive_skip_empty_dirs() -> Result<(), String> {
+    let server = MockServer::start(|request, _| tree_handler(request));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("download_recursive_skip_empty");
+    let save_path = dir.to_string_lossy().to_string();
+
+    let options = RecursiveDownloadOptions {
+        max_concurrent_files: 1,
+        include_empty_dirs: false,
+    };
+
+    let report = client
+        .download_recursive(&key, "root/", &save_path, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert!(!dir.join("empty").exists());
+    assert!(dir.join("sub").join("b.bin").is_file());
+
+    let skipped: Vec<_> = report.skipped().collect();
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].remote_path, "/dav/root/empty/");
+    assert!(skipped[0].is_dir);
+    assert!(matches!(
+        skipped[0].outcome,
+        RecursiveDownloadOutcome::Skipped(
+            RecursiveDownloadSkipReason::EmptyDir
+        )
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_recursive_not_found() -> Result<(), String> {
+    let server = MockServer::start(|request, _| tree_handler(request));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("download_recursive_not_found");
+    let save_path = dir.to_string_lossy().to_string();
+
+    let result = client
+        .download_recursive(
+            &key,
+            "missing",
+            &save_path,
+            RecursiveDownloadOptions::default(),
+        )
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+fn modified_secs(path: &std::path::Path) -> Result<u64, String> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_err(|e| e.to_string())?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+async fn test_download_recursive_preserve_mtime() -> Result<(), String> {
+    let server = MockServer::start(|request, _| tree_handler(request));
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_preserve_mtime(true)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("download_recursive_preserve_mtime");
+    let save_path = dir.to_string_lossy().to_string();
+
+    client
+        .download_recursive(
+            &key,
+            "root",
+            &save_path,
+            RecursiveDownloadOptions::default(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 文件和创建的文件夹都使用远程的修改时间，
+    // 文件夹在里面的文件写完之后才设置，不会被覆盖
+    for path in [
+        dir.join("a.bin"),
+        dir.join("sub"),
+        dir.join("sub").join("b.bin"),
+        dir.join("empty"),
+    ] {
+        assert_eq!(
+            modified_secs(&path)?,
+            LAST_MODIFIED_SECS,
+            "{:?}",
+            path
+        );
+    }
+
+    Ok(())
+}
+
+/// 只有空文件夹的目录结构：
+///
+/// ```text
+/// /dav/nested/
+/// └── inner/
+///     └── leaf/
+/// ```
+fn nested_empty_handler(request: &MockRequest) -> MockResponse {
+    let path = request.path.trim_end_matches('/');
+    let depth_zero =
+        request.headers.get("depth").map(String::as_str) == Some("0");
+
+    match path {
+        "/dav/nested" if depth_zero => {
+            multi_status(&[dir_response("/dav/nested/")])
+        }
+        "/dav/nested" => multi_status(&[
+            dir_response("/dav/nested/"),
+            dir_response("/dav/nested/inner/"),
+        ]),
+        "/dav/nested/inner" => multi_status(&[
+            dir_response("/dav/nested/inner/"),
+            dir_response("/dav/nested/inner/leaf/"),
+        ]),
+        "/dav/nested/inner/leaf" => {
+            multi_status(&[dir_response("/dav/nested/inner/leaf/")])
+        }
+        _ => MockResponse::new(404, ""),
+    }
+}
+
+#[tokio::test]
+async fn test_download_recursive_prunes_nested_empty_dirs()
+-> Result<(), String> {
+    let server =
+        MockServer::start(|request, _| nested_empty_handler(request));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("download_recursive_nested_empty");
+    let save_path = dir.to_string_lossy().to_string();
+
+    let options = RecursiveDownloadOptions {
+        max_concurrent_files: 1,
+        include_empty_dirs: false,
+    };
+
+    let report = client
+        .download_recursive(&key, "nested/", &save_path, options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // inner 有一个子项，但整棵子树里没有文件，也不应该留下
+    assert!(!dir.join("inner").exists());
+    assert_eq!(report.downloaded().count(), 0);
+    assert_eq!(report.skipped().count(), 2);
+    assert!(report.skipped().all(|entry| matches!(
+        entry.outcome,
+        RecursiveDownloadOutcome::Skipped(
+            RecursiveDownloadSkipReason::EmptyDir
+        )
+    )));
+
+    Ok(())
+}
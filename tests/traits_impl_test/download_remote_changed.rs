@@ -0,0 +1,243 @@
+use crate::traits_impl_test::mock_server::{
+    MockRequest, MockResponse, MockServer, mock_file_data,
+    mock_resources_file, range_response, temp_download_dir, test_content,
+};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::enums::remote_changed_policy::RemoteChangedPolicy;
+use webdav_client::resource_file::traits::download::{
+    Download, DownloadError,
+};
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const CONTENT_LEN: usize = 64 * 1024;
+const CUT_AT: usize = 10_000;
+
+/// 新版本的内容，与 `test_content` 生成的旧版本不同
+fn new_content(len: usize) -> Vec<u8> {
+    test_content(len).into_iter().map(|b| b.wrapping_add(1)).collect()
+}
+
+/// 服务器上的文件已经更新为 v2，`If-Range` 不匹配时按规范返回完整的新文件
+fn serve_v2(request: &MockRequest, content: &[u8]) -> MockResponse {
+    let stale = request
+        .headers
+        .get("if-range")
+        .is_some_and(|if_range| if_range != "\"v2\"");
+
+    if stale {
+        return MockResponse::new(200, content.to_vec())
+            .header("ETag", "\"v2\"");
+    }
+
+    range_response(request, content).header("ETag", "\"v2\"")
+}
+
+/// 第一次请求返回 v1 并中途断开，之后服务器上的文件变成 v2
+fn changing_server() -> MockServer {
+    let old = test_content(CONTENT_LEN);
+    let new = new_content(CONTENT_LEN);
+
+    MockServer::start(move |request, index| {
+        if index == 0 {
+            return MockResponse::new(200, old.clone())
+                .header("ETag", "\"v1\"")
+                .cut_after(CUT_AT);
+        }
+
+        if index == 1 {
+            assert_eq!(
+                request.headers.get("if-range").map(String::as_str),
+                Some("\"v1\"")
+            );
+        }
+
+        serve_v2(request, &new)
+    })
+}
+
+#[tokio::test]
+async fn test_not_chunked_restarts_when_remote_changed()
+-> Result<(), String> {
+    let server = changing_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let dir = temp_download_dir("remote-changed-restart");
+
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 默认丢弃 v1 的内容，从头下载 v2
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, new_content(CONTENT_LEN));
+    assert_eq!(server.request_count(), 3);
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CONTENT_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_not_chunked_fails_when_remote_changed() -> Result<(), String>
+{
+    let server = changing_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_remote_changed_policy(Some(RemoteChangedPolicy::Fail))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("remote-changed-fail");
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    match result {
+        Err(DownloadError::RemoteChanged(e)) => {
+            assert_eq!(e.expected.etag.as_deref(), Some("v1"));
+            assert_eq!(e.current.etag.as_deref(), Some("v2"));
+        }
+        other => {
+            return Err(format!("应该返回 RemoteChanged: {:?}", other));
+        }
+    }
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_resume_restarts_when_remote_changed()
+-> Result<(), String> {
+    let len = CHUNK_SIZE * 2 + 100;
+    let new = new_content(len);
+    let served = new.clone();
+
+    let server =
+        MockServer::start(move |request, _| serve_v2(request, &served));
+
+    // 上次下载 v1 时完成了第一个分片
+    let dir = temp_download_dir("remote-changed-chunked");
+    std::fs::write(dir.join("a.bin"), test_content(CHUNK_SIZE))
+        .map_err(|e| e.to_string())?;
+    std::fs::write(
+        dir.join("a.bin.wdpart"),
+        format!(
+            r#"{{"remote_size":{},"etag":"v1","completed":[[0,{}]]}}"#,
+            len,
+            CHUNK_SIZE - 1
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 列目录时拿到的还是旧的 ETag，续传请求会带上 If-Range: "v1"
+    let mut data = mock_file_data(&server, "a.bin", len);
+    data.etag = Some("v1".to_string());
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+    let resources_file =
+        data.to_resources_file(http_client, client.get_global_config());
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(1))
+        .map_err(|e| e.to_string())?;
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, new);
+    assert!(!dir.join("a.bin.wdpart").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_resume_fails_when_remote_changed()
+-> Result<(), String> {
+    let len = CHUNK_SIZE * 2 + 100;
+    let new = new_content(len);
+
+    let server =
+        MockServer::start(move |request, _| serve_v2(request, &new));
+
+    // 上次下载 v1 时完成了第一个分片，这次列目录拿到的已经是 v2
+    let dir = temp_download_dir("remote-changed-chunked-fail");
+    std::fs::write(dir.join("a.bin"), test_content(CHUNK_SIZE))
+        .map_err(|e| e.to_string())?;
+    let journal = format!(
+        r#"{{"remote_size":{},"etag":"v1","completed":[[0,{}]]}}"#,
+        len,
+        CHUNK_SIZE - 1
+    );
+    std::fs::write(dir.join("a.bin.wdpart"), &journal)
+        .map_err(|e| e.to_string())?;
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let mut data = mock_file_data(&server, "a.bin", len);
+    data.etag = Some("v2".to_string());
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+    let resources_file =
+        data.to_resources_file(http_client, client.get_global_config());
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_remote_changed_policy(Some(RemoteChangedPolicy::Fail))
+        .map_err(|e| e.to_string())?;
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    match result {
+        Err(DownloadError::RemoteChanged(e)) => {
+            assert_eq!(e.expected.etag.as_deref(), Some("v1"));
+            assert_eq!(e.current.etag.as_deref(), Some("v2"));
+        }
+        other => {
+            return Err(format!("应该返回 RemoteChanged: {:?}", other));
+        }
+    }
+
+    // 已下载的内容和记录都保留，也没有发出任何请求
+    let partial =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(partial, test_content(CHUNK_SIZE));
+    let kept = std::fs::read_to_string(dir.join("a.bin.wdpart"))
+        .map_err(|e| e.to_string())?;
+    assert_eq!(kept, journal);
+    assert_eq!(server.request_count(), 0);
+
+    Ok(())
+}
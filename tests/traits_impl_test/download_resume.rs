@@ -0,0 +1,255 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, is_range_probe, mock_file_data,
+    mock_resources_file, range_response, temp_download_dir, test_content,
+};
+use std::sync::{Arc, Mutex};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::structs::resources_file::ResourcesFile;
+use webdav_client::resource_file::traits::download::Download;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const CONTENT_LEN: usize = CHUNK_SIZE * 2 + 100;
+
+/// 记录每个分片请求的 Range，按请求顺序排列，不包括探测请求
+fn recording_server(
+    content: Vec<u8>,
+    fail_range: Option<String>,
+) -> (MockServer, Arc<Mutex<Vec<String>>>) {
+    let ranges = Arc::new(Mutex::new(vec![]));
+    let recorded = ranges.clone();
+
+    let server = MockServer::start(move |request, _| {
+        if is_range_probe(request) {
+            return range_response(request, &content);
+        }
+
+        let range =
+            request.headers.get("range").cloned().unwrap_or_default();
+        recorded.lock().unwrap().push(range.clone());
+
+        if fail_range.as_deref() == Some(range.as_str()) {
+            return MockResponse::new(404, vec![]);
+        }
+        range_response(request, &content)
+    });
+
+    (server, ranges)
+}
+
+/// 强制分片并单线程下载，分片按顺序执行，便于断言请求
+fn single_thread_chunked(
+    resources_file: &ResourcesFile,
+) -> Result<(), String> {
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(1))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_download_resumes_missing_chunks()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let second_chunk =
+        format!("bytes={}-{}", CHUNK_SIZE, CHUNK_SIZE * 2 - 1);
+
+    // 第一次下载：第二个分片失败，第一个分片已经完成
+    let (server, _) =
+        recording_server(content.clone(), Some(second_chunk.clone()));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    single_thread_chunked(&resources_file)?;
+
+    let dir = temp_download_dir("resume-missing-chunks");
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+    assert!(result.is_err());
+    assert!(dir.join("a.bin.wdpart").exists());
+
+    // 开始下载时就预分配了完整长度，还没下载的部分是空洞
+    let partial =
+        std::fs::metadata(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(partial.len(), CONTENT_LEN as u64);
+
+    // 第二次下载：只请求还没完成的分片
+    let (server, ranges) = recording_server(content.clone(), None);
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    single_thread_chunked(&resources_file)?;
+
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![
+            second_chunk,
+            format!("bytes={}-{}", CHUNK_SIZE * 2, CONTENT_LEN - 1),
+        ]
+    );
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert!(!dir.join("a.bin.wdpart").exists());
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CONTENT_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_full_length_file_with_journal_is_not_complete()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let (server, ranges) = recording_server(content.clone(), None);
+
+    // 文件已经是完整长度，但记录显示第一个分片还没写完
+    let dir = temp_download_dir("resume-full-length");
+    let mut local = content.clone();
+    local[..CHUNK_SIZE].fill(0);
+    std::fs::write(dir.join("a.bin"), &local)
+        .map_err(|e| e.to_string())?;
+    std::fs::write(
+        dir.join("a.bin.wdpart"),
+        format!(
+            r#"{{"remote_size":{},"etag":null,"completed":[[{},{}]]}}"#,
+            CONTENT_LEN,
+            CHUNK_SIZE,
+            CONTENT_LEN - 1
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    single_thread_chunked(&resources_file)?;
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![format!("bytes=0-{}", CHUNK_SIZE - 1)]
+    );
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert!(!dir.join("a.bin.wdpart").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_full_length_temp_file_without_journal_is_redownloaded()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let (server, ranges) = recording_server(content.clone(), None);
+
+    // 原子下载留下的临时文件长度完整，但没有记录，无法判断内容是否有效
+    let dir = temp_download_dir("resume-stale-temp");
+    std::fs::write(dir.join(".a.bin.wdtmp"), vec![0u8; CONTENT_LEN])
+        .map_err(|e| e.to_string())?;
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    single_thread_chunked(&resources_file)?;
+    resources_file
+        .get_reactive_config()
+        .set_atomic_write(Some(true))
+        .map_err(|e| e.to_string())?;
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(ranges.lock().unwrap().len(), 3);
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_journal_with_different_etag_is_discarded()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let (server, ranges) = recording_server(content.clone(), None);
+
+    let dir = temp_download_dir("resume-etag-changed");
+    std::fs::write(dir.join("a.bin"), vec![0u8; CONTENT_LEN])
+        .map_err(|e| e.to_string())?;
+    std::fs::write(
+        dir.join("a.bin.wdpart"),
+        format!(
+            r#"{{"remote_size":{},"etag":"old","completed":[[0,{}]]}}"#,
+            CONTENT_LEN,
+            CHUNK_SIZE - 1
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 远程文件的 ETag 已经变了，旧记录作废，从头下载
+    let mut data = mock_file_data(&server, "a.bin", CONTENT_LEN);
+    data.etag = Some("new".to_string());
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+    let resources_file =
+        data.to_resources_file(http_client, client.get_global_config());
+    single_thread_chunked(&resources_file)?;
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ranges = ranges.lock().unwrap().clone();
+    assert_eq!(ranges.len(), 3);
+    assert_eq!(ranges[0], format!("bytes=0-{}", CHUNK_SIZE - 1));
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert!(!dir.join("a.bin.wdpart").exists());
+
+    Ok(())
+}
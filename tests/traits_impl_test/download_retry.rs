@@ -0,0 +1,237 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, is_range_probe, mock_resources_file,
+    range_response, temp_download_dir, test_content,
+};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 64 * 1024;
+const CUT_AT: usize = 10_000;
+
+#[tokio::test]
+async fn test_not_chunked_download_resumes_after_disconnect()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第一次请求中途断开，之后必须带着 Range 从断点继续
+    let server = MockServer::start(move |request, index| {
+        if index == 0 {
+            return MockResponse::new(200, served.clone())
+                .cut_after(CUT_AT);
+        }
+
+        assert_eq!(
+            request.headers.get("range").map(String::as_str),
+            Some("bytes=10000-")
+        );
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let dir = temp_download_dir("retry-not-chunked");
+
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(server.request_count(), 2);
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CONTENT_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_download_resumes_after_disconnect()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第 0 个请求是 Range 探测，第 1 个才是分片
+    let server = MockServer::start(move |request, index| {
+        if is_range_probe(request) {
+            return range_response(request, &served);
+        }
+
+        if index == 1 {
+            return range_response(request, &served).cut_after(CUT_AT);
+        }
+
+        assert_eq!(
+            request.headers.get("range").map(String::as_str),
+            Some(format!("bytes={}-{}", CUT_AT, CONTENT_LEN - 1).as_str())
+        );
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "b.bin", CONTENT_LEN);
+
+    // 阈值调到最小，强制走分片下载
+    resources_file
+        .get_reactive_config()
+        .update_field(|cfg| cfg.large_file_threshold = Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("retry-chunked");
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("b.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(server.request_count(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_does_not_retry_not_found() -> Result<(), String> {
+    let server = MockServer::start(|request, _| {
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/dav/c.bin");
+        MockResponse::new(404, "not found")
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "c.bin", 16);
+    let dir = temp_download_dir("retry-not-found");
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_does_not_retry_not_implemented()
+-> Result<(), String> {
+    // 501 表示服务器不支持这个请求，和 5xx 的临时故障不同，重试也没用
+    let server = MockServer::start(|_, _| {
+        MockResponse::new(501, "not implemented")
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "d.bin", 16);
+    let dir = temp_download_dir("retry-not-implemented");
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_retries_are_capped_when_progress_is_made()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+
+    // 每次都只返回一点数据就断开，虽然每次都有进展，重试次数也必须封顶
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content).cut_after(100)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "e.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_max_retries(Some(2))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("retry-capped");
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_download_retries_short_range_body()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第 1 个请求的 Content-Range 是完整的分片，响应体却正常结束在中途
+    let server = MockServer::start(move |request, index| {
+        if is_range_probe(request) || index != 1 {
+            return range_response(request, &served);
+        }
+
+        MockResponse::new(206, served[..CUT_AT].to_vec()).header(
+            "Content-Range",
+            &format!("bytes 0-{}/{}", CONTENT_LEN - 1, CONTENT_LEN),
+        )
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "f.bin", CONTENT_LEN);
+
+    // 阈值调到最小，强制走分片下载
+    resources_file
+        .get_reactive_config()
+        .update_field(|cfg| cfg.large_file_threshold = Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("retry-short-range");
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("f.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(server.request_count(), 3);
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, mock_resources_file, range_response,
+    test_content,
+};
+use futures_util::StreamExt;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::traits::download_stream::{
+    DownloadStream, DownloadStreamError,
+};
+
+const CONTENT_LEN: usize = 64 * 1024;
+const CUT_AT: usize = 10_000;
+
+#[tokio::test]
+async fn test_download_to_bytes() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    let bytes = resources_file
+        .download_to_bytes(CONTENT_LEN as u64)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(bytes, content);
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CONTENT_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_to_bytes_too_large() -> Result<(), String> {
+    let server = MockServer::start(|_, _| MockResponse::new(200, vec![]));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    // 已知大小超过上限，不发请求
+    let result = resources_file.download_to_bytes(1024).await;
+    assert!(matches!(
+        result,
+        Err(DownloadStreamError::TooLarge { max_size: 1024 })
+    ));
+    assert_eq!(server.request_count(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_to_writer_resumes_after_disconnect()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第一次请求中途断开，之后带着 Range 从断点继续
+    let server = MockServer::start(move |request, index| {
+        if index == 0 {
+            return MockResponse::new(200, served.clone())
+                .cut_after(CUT_AT);
+        }
+
+        assert_eq!(
+            request.headers.get("range").map(String::as_str),
+            Some(format!("bytes={}-", CUT_AT).as_str())
+        );
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    let mut writer: Vec<u8> = vec![];
+    let written = resources_file
+        .download_to_writer(&mut writer)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(written, CONTENT_LEN as u64);
+    assert_eq!(writer, content);
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_open_stream_skips_when_range_ignored() -> Result<(), String>
+{
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 服务器忽略 Range，重连后从头返回完整内容
+    let server = MockServer::start(move |_, index| {
+        let response = MockResponse::new(200, served.clone());
+        if index == 0 { response.cut_after(CUT_AT) } else { response }
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    let mut stream =
+        resources_file.open_stream().map_err(|e| e.to_string())?;
+    let mut received = vec![];
+    while let Some(chunk) = stream.next().await {
+        received.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+    }
+
+    assert_eq!(received, content);
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CONTENT_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_to_bytes_resumes_after_short_body()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第一次响应体正常结束，但只有一部分数据
+    let server = MockServer::start(move |request, index| {
+        if index == 0 {
+            return MockResponse::new(200, served[..CUT_AT].to_vec());
+        }
+
+        assert_eq!(
+            request.headers.get("range").map(String::as_str),
+            Some(format!("bytes={}-", CUT_AT).as_str())
+        );
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    let bytes = resources_file
+        .download_to_bytes(CONTENT_LEN as u64)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert_eq!(bytes, content);
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_to_writer_retries_are_capped() -> Result<(), String>
+{
+    let content = test_content(CONTENT_LEN);
+
+    // 每次都只返回一点数据，虽然每次都有进展，重试次数也必须封顶
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content).cut_after(100)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_max_retries(Some(2))
+        .map_err(|e| e.to_string())?;
+
+    let mut writer: Vec<u8> = vec![];
+    let result = resources_file.download_to_writer(&mut writer).await;
+
+    assert!(result.is_err());
+    assert_eq!(writer.len(), 300);
+    assert_eq!(server.request_count(), 3);
+
+    Ok(())
+}
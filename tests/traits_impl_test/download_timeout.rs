@@ -0,0 +1,164 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use std::time::{Duration, Instant};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::enums::depth::Depth;
+use webdav_client::client::traits::account::Account;
+use webdav_client::client::traits::folders::Folders;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 64 * 1024;
+const HANG: Duration = Duration::from_secs(10);
+
+#[tokio::test]
+async fn test_download_response_timeout_then_retry() -> Result<(), String>
+{
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第一次请求迟迟不返回响应头，超时后重试成功
+    let server = MockServer::start(move |request, index| {
+        let response = range_response(request, &served);
+        if index == 0 { response.delay(HANG) } else { response }
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    client
+        .get_account_config(&key)
+        .map_err(|e| e.to_string())?
+        .set_timeout(Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let dir = temp_download_dir("timeout-response");
+
+    let started = Instant::now();
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert!(started.elapsed() < HANG);
+    assert_eq!(server.request_count(), 2);
+    assert_eq!(
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?,
+        content
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_download_idle_timeout_then_resume()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+
+    // 第一个分片请求发了一部分数据后卡住，读取超时后从断点继续，
+    // 第 0 个请求是 Range 探测
+    let server = MockServer::start(move |request, index| {
+        let response = range_response(request, &served);
+        if index == 1 {
+            response.cut_after(10_000).stall(HANG)
+        } else {
+            response
+        }
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "b.bin", CONTENT_LEN);
+    resources_file
+        .get_reactive_config()
+        .update_field(|cfg| {
+            cfg.large_file_threshold = Some(1);
+            cfg.timeout_secs = Some(1);
+        })
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("timeout-idle");
+
+    let started = Instant::now();
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert!(started.elapsed() < HANG);
+    assert_eq!(server.request_count(), 3);
+    assert_eq!(
+        std::fs::read(dir.join("b.bin")).map_err(|e| e.to_string())?,
+        content
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_folders_timeout() -> Result<(), String> {
+    let server = MockServer::start(|_, _| {
+        MockResponse::new(207, "<D:multistatus xmlns:D=\"DAV:\"/>")
+            .delay(HANG)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    client
+        .get_global_config()
+        .set_timeout(1)
+        .map_err(|e| e.to_string())?;
+
+    // get_folders 会把单个路径的错误打印出来并跳过，这里只要求它不再卡住
+    let started = Instant::now();
+    let data = client
+        .get_folders(&key, &vec!["./".to_string()], &Depth::One)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert!(started.elapsed() < HANG);
+    assert!(data.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_folders_idle_timeout() -> Result<(), String> {
+    // 响应头正常返回，响应体发了一部分就卡住
+    let server = MockServer::start(|_, _| {
+        MockResponse::new(207, "<D:multistatus xmlns:D=\"DAV:\"/>")
+            .cut_after(10)
+            .stall(HANG)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    client
+        .get_global_config()
+        .set_timeout(1)
+        .map_err(|e| e.to_string())?;
+
+    let started = Instant::now();
+    let data = client
+        .get_folders(&key, &vec!["./".to_string()], &Depth::One)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    assert!(started.elapsed() < HANG);
+    assert!(data.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, is_range_probe, mock_resources_file, range_response,
+    temp_download_dir, test_content,
+};
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::global_config::host_policy::{HostPattern, HostPolicy};
+use webdav_client::global_config::host_policy_registry::HostPolicyRegistry;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::structs::remote_reader::RemoteReaderOptions;
+use webdav_client::resource_file::traits::download::Download;
+use webdav_client::resource_file::traits::open_reader::OpenReader;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+#[tokio::test]
+async fn test_host_policy_registry() -> Result<(), String> {
+    let registry = HostPolicyRegistry::default();
+
+    // 默认不允许对坚果云分片
+    assert!(!registry.allows_chunked("https://dav.jianguoyun.com/dav/a"));
+    assert!(registry.allows_chunked("https://dav.example.com/a"));
+
+    let wildcard = HostPattern::Host("*.Example.com".into());
+    assert!(wildcard.matches("https://dav.example.com/a"));
+    assert!(wildcard.matches("http://a.b.example.com:8080/"));
+    assert!(!wildcard.matches("https://example.com/a"));
+    assert!(!wildcard.matches("https://badexample.com/a"));
+
+    let host = HostPattern::Host("example.com".into());
+    assert!(host.matches("https://example.com/a"));
+    assert!(!host.matches("https://dav.example.com/a"));
+
+    // 新加的策略排在前面，先于更宽泛的规则匹配
+    registry
+        .set(HostPolicy {
+            allow_chunked: false,
+            ..HostPolicy::new(wildcard.clone())
+        })
+        .set(HostPolicy {
+            max_concurrency: Some(2),
+            ..HostPolicy::new(HostPattern::UrlPrefix(
+                "https://dav.example.com/fast/".into(),
+            ))
+        });
+
+    assert!(registry.allows_chunked("https://dav.example.com/fast/a"));
+    assert!(!registry.allows_chunked("https://dav.example.com/slow/a"));
+    assert_eq!(registry.list().len(), 3);
+
+    // 相同匹配规则原地替换
+    registry.set(HostPolicy::new(wildcard.clone()));
+    assert_eq!(registry.list().len(), 3);
+    assert!(registry.allows_chunked("https://dav.example.com/slow/a"));
+
+    let path = temp_download_dir("host-policy-file").join("policies.json");
+    registry.save(&path).await.map_err(|e| e.to_string())?;
+
+    let restored = HostPolicyRegistry::new(vec![]);
+    let count = restored.load(&path).await.map_err(|e| e.to_string())?;
+    assert_eq!(count, 3);
+    assert_eq!(restored.list(), registry.list());
+
+    assert!(restored.remove(&wildcard).is_some());
+    assert!(restored.remove(&wildcard).is_none());
+    assert_eq!(restored.list().len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_host_policy_disallows_chunking() -> Result<(), String> {
+    let content = test_content(2 * CHUNK_SIZE);
+    let served = content.clone();
+
+    let server = MockServer::start(move |request, _| {
+        assert!(!request.headers.contains_key("range"));
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    client.get_global_config().get_host_policies().set(HostPolicy {
+        allow_chunked: false,
+        ..HostPolicy::new(HostPattern::UrlPrefix(server.base_url.clone()))
+    });
+
+    // 自动模式下文件超过阈值，但策略不允许分片，连探测请求都不发
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_large_file_threshold(Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("host-policy-not-chunked");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(server.request_count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_host_policy_max_concurrency() -> Result<(), String> {
+    let content = test_content(4 * CHUNK_SIZE);
+    let served = content.clone();
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_recorder = peak.clone();
+
+    let server = MockServer::start(move |request, _| {
+        if is_range_probe(request) {
+            return range_response(request, &served);
+        }
+
+        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        peak_recorder.fetch_max(current, Ordering::SeqCst);
+
+        std::thread::sleep(Duration::from_millis(100));
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    client.get_global_config().get_host_policies().set(HostPolicy {
+        max_concurrency: Some(1),
+        ..HostPolicy::new(HostPattern::Host("127.0.0.1".into()))
+    });
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(4))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("host-policy-max-concurrency");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded.len(), content.len());
+
+    // 文件自己允许 4 个线程，但主机策略只允许 1 个连接
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_host_policy_max_requests_per_minute() -> Result<(), String> {
+    let content = test_content(2 * CHUNK_SIZE);
+    let served = content.clone();
+
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    client.get_global_config().get_host_policies().set(HostPolicy {
+        max_requests_per_minute: Some(2),
+        ..HostPolicy::new(HostPattern::UrlPrefix(server.base_url.clone()))
+    });
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    // 探测请求和第一个分片用完了这一分钟的名额，第二个分片要等到一分钟后
+    let dir = temp_download_dir("host-policy-max-requests");
+    let result = tokio::time::timeout(
+        Duration::from_secs(1),
+        resources_file.download(dir.to_str().unwrap()),
+    )
+    .await;
+
+    assert!(result.is_err(), "请求频率没有被限制");
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
+
+const READER_OPTIONS: RemoteReaderOptions = RemoteReaderOptions {
+    block_size: 4096,
+    read_ahead: 4096,
+    cache_blocks: 8,
+};
+
+#[tokio::test]
+async fn test_host_policy_applies_to_remote_reader() -> Result<(), String> {
+    let content = test_content(64 * 1024);
+    let served = content.clone();
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_recorder = peak.clone();
+
+    let server = MockServer::start(move |request, _| {
+        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        peak_recorder.fetch_max(current, Ordering::SeqCst);
+
+        std::thread::sleep(Duration::from_millis(100));
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    client.get_global_config().get_host_policies().set(HostPolicy {
+        max_concurrency: Some(1),
+        max_requests_per_minute: Some(4),
+        ..HostPolicy::new(HostPattern::UrlPrefix(server.base_url.clone()))
+    });
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+
+    // 两个读取器同时读取，主机策略只允许一个连接
+    let read_block = |offset: u64| {
+        let resources_file = resources_file.clone();
+        async move {
+            let mut reader = resources_file
+                .open_reader_with_options(READER_OPTIONS)
+                .map_err(|e| e.to_string())?;
+            let mut buffer = vec![0u8; 4096];
+            reader
+                .seek(SeekFrom::Start(offset))
+                .await
+                .map_err(|e| e.to_string())?;
+            reader
+                .read_exact(&mut buffer)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>(buffer)
+        }
+    };
+
+    let (first, second) = tokio::join!(read_block(0), read_block(8192));
+    assert_eq!(first?, content[..4096]);
+    assert_eq!(second?, content[8192..12_288]);
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+
+    // 再读两个块用完这一分钟的名额，第五个请求要等到一分钟后
+    read_block(16_384).await?;
+    read_block(24_576).await?;
+
+    let result =
+        tokio::time::timeout(Duration::from_secs(1), read_block(32_768))
+            .await;
+    assert!(result.is_err(), "请求频率没有被限制");
+    assert_eq!(server.request_count(), 4);
+
+    Ok(())
+}
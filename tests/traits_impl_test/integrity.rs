@@ -0,0 +1,329 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, mock_file_data, mock_resources_file,
+    range_response, temp_download_dir, test_content,
+};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::checksum_algorithm::ChecksumAlgorithm;
+use webdav_client::resource_file::structs::checksum::{
+    Checksum, IntegrityError,
+};
+use webdav_client::resource_file::traits::download::{
+    Download, DownloadError,
+};
+
+const CONTENT_LEN: usize = 64 * 1024;
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[tokio::test]
+async fn test_download_exposes_hash() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-hash");
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let state = resources_file.get_reactive_state();
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let hash = state
+        .get_hash()
+        .get_current_borrow()
+        .clone()
+        .flatten()
+        .ok_or("没有摘要")?;
+    assert_eq!(hash.sha256, sha256_hex(&content));
+
+    // 服务器没有提供 MD5 和 SHA-1，不需要额外计算
+    assert_eq!(hash.md5, None);
+    assert_eq!(hash.sha1, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_header_checksum_algorithms_are_hashed()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let md5 = format!("{:x}", Md5::digest(&content));
+    let header = format!("MD5:{}", md5);
+    let served = content.clone();
+    let server = MockServer::start(move |_, _| {
+        MockResponse::new(200, served.clone())
+            .header("OC-Checksum", &header)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-header-md5");
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let state = resources_file.get_reactive_state();
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 响应头只提供了 MD5，只多算这一种
+    let hash = state
+        .get_hash()
+        .get_current_borrow()
+        .clone()
+        .flatten()
+        .ok_or("没有摘要")?;
+    assert_eq!(hash.md5, Some(md5));
+    assert_eq!(hash.sha1, None);
+    assert_eq!(hash.sha256, sha256_hex(&content));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_header_checksum_mismatch() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let server = MockServer::start(move |_, _| {
+        MockResponse::new(200, content.clone())
+            .header("OC-Checksum", &format!("SHA256:{}", "0".repeat(64)))
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-header");
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+    assert!(matches!(
+        result,
+        Err(DownloadError::Integrity(IntegrityError::ChecksumMismatch {
+            algorithm: ChecksumAlgorithm::Sha256,
+            ..
+        }))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_propfind_checksum_mismatch() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 列目录时得到的校验值和实际内容不一致
+    let mut data = mock_file_data(&server, "a.bin", CONTENT_LEN);
+    data.checksums =
+        Checksum::parse_list(&format!("SHA1:{}", "0".repeat(40)));
+
+    let http_client = client.get_http_client(&key).expect("账号不存在");
+    let resources_file =
+        data.to_resources_file(http_client, client.get_global_config());
+
+    let dir = temp_download_dir("integrity-propfind");
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+    assert!(matches!(
+        result,
+        Err(DownloadError::Integrity(IntegrityError::ChecksumMismatch {
+            algorithm: ChecksumAlgorithm::Sha1,
+            ..
+        }))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sidecar_checksum() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let good = sha256_hex(&content);
+    let served = content.clone();
+    let server =
+        MockServer::start(move |request, _| match request.path.as_str() {
+            "/dav/good.bin.sha256" => MockResponse::new(
+                200,
+                format!("{}  good.bin\n", good).into_bytes(),
+            ),
+            "/dav/bad.bin.sha256" => {
+                MockResponse::new(200, "0".repeat(64).into_bytes())
+            }
+            _ => range_response(request, &served),
+        });
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_sidecar_checksum(true)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-sidecar");
+
+    let good_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "good.bin",
+        CONTENT_LEN,
+    );
+    good_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let bad_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "bad.bin",
+        CONTENT_LEN,
+    );
+    let result = bad_file.download(dir.to_str().unwrap()).await;
+    assert!(matches!(
+        result,
+        Err(DownloadError::Integrity(IntegrityError::ChecksumMismatch {
+            algorithm: ChecksumAlgorithm::Sha256,
+            ..
+        }))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_requests_identity_encoding() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let md5 = format!("{:x}", Md5::digest(&content));
+    let served = content.clone();
+
+    // 压缩后的响应体与 Content-MD5 对不上，下载必须要求服务器不压缩
+    let server = MockServer::start(move |request, _| {
+        assert_eq!(
+            request.headers.get("accept-encoding").map(String::as_str),
+            Some("identity")
+        );
+        MockResponse::new(200, served.clone()).header("Content-MD5", &md5)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-identity");
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[test]
+fn test_entity_checksums_ignored_for_encoded_response() {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("content-encoding", "gzip".parse().unwrap());
+    headers.insert(
+        "content-md5",
+        "d41d8cd98f00b204e9800998ecf8427e".parse().unwrap(),
+    );
+    headers.insert(
+        "oc-checksum",
+        "SHA1:da39a3ee5e6b4b0d3255bfef95601890afd80709".parse().unwrap(),
+    );
+
+    // Content-MD5 是按压缩后的实体算的，只保留对应文件本身的 OC-Checksum
+    let checksums = Checksum::from_headers(&headers);
+    assert_eq!(checksums.len(), 1);
+    assert_eq!(checksums[0].algorithm, ChecksumAlgorithm::Sha1);
+}
+
+#[tokio::test]
+async fn test_chunked_download_hashes_only_with_checksums()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_large_file_threshold(1)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("integrity-chunked-hash");
+
+    // 没有任何校验值，下载完不再重新读取文件计算摘要
+    let plain =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let state = plain.get_reactive_state();
+    plain
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(
+        state.get_hash().get_current_borrow().clone().flatten().is_none()
+    );
+
+    // 有校验值时照常重新读取文件并校验
+    let mut data = mock_file_data(&server, "b.bin", CONTENT_LEN);
+    data.checksums =
+        Checksum::parse_list(&format!("SHA256:{}", sha256_hex(&content)));
+    let http_client = client.get_http_client(&key).expect("账号不存在");
+    let checked =
+        data.to_resources_file(http_client, client.get_global_config());
+    let state = checked.get_reactive_state();
+    checked
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let hash = state
+        .get_hash()
+        .get_current_borrow()
+        .clone()
+        .flatten()
+        .ok_or("没有摘要")?;
+    assert_eq!(hash.sha256, sha256_hex(&content));
+
+    Ok(())
+}
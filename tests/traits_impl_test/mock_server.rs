@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::structs::client_key::ClientKey;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::structs::resource_file_data::ResourceFileData;
+use webdav_client::resource_file::structs::resources_file::ResourcesFile;
+
+/// 测试用的请求，header 名统一转成小写
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+/// 测试用的响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// 只发送前 n 个字节就断开连接，用来模拟网络中断
+    pub cut_after: Option<usize>,
+    /// 发送响应头之前等待，用来模拟服务器无响应
+    pub delay: Option<Duration>,
+    /// 发送完（或截断后）保持连接不动的时间，用来模拟数据流卡住
+    pub stall: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+            cut_after: None,
+            delay: None,
+            stall: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn cut_after(mut self, len: usize) -> Self {
+        self.cut_after = Some(len);
+        self
+    }
+
+    pub fn delay(mut self, duration: Duration) -> Self {
+        self.delay = Some(duration);
+        self
+    }
+
+    pub fn stall(mut self, duration: Duration) -> Self {
+        self.stall = Some(duration);
+        self
+    }
+}
+
+/// 只依赖标准库的 HTTP/1.1 测试服务器，每个连接处理一个请求后关闭
+pub struct MockServer {
+    pub base_url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    /// 启动服务器，`handler` 的第二个参数是从 0 开始的请求序号
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest, usize) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener =
+            TcpListener::bind("127.0.0.1:0").expect("绑定端口失败");
+        let addr = listener.local_addr().expect("获取地址失败");
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let handler = Arc::new(handler);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let handler = handler.clone();
+
+                thread::spawn(move || {
+                    handle_connection(stream, index, handler.as_ref())
+                });
+            }
+        });
+
+        Self { base_url: format!("http://{}/dav/", addr), requests }
+    }
+
+    /// 已经收到的请求数量
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+fn handle_connection<F>(stream: TcpStream, index: usize, handler: &F)
+where
+    F: Fn(&MockRequest, usize) -> MockResponse,
+{
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() {
+            return;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(
+                name.trim().to_ascii_lowercase(),
+                value.trim().to_string(),
+            );
+        }
+    }
+
+    // 读掉请求体，避免客户端写入时被重置
+    if let Some(len) =
+        headers.get("content-length").and_then(|v| v.parse::<usize>().ok())
+    {
+        let mut body = vec![0u8; len];
+        let _ = reader.read_exact(&mut body);
+    }
+
+    let request = MockRequest { method, path, headers };
+    let response = handler(&request, index);
+
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let mut stream = reader.into_inner();
+    let body = match response.cut_after {
+        Some(len) => &response.body[..len.min(response.body.len())],
+        None => &response.body[..],
+    };
+
+    if let Some(delay) = response.delay {
+        thread::sleep(delay);
+    }
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body);
+    let _ = stream.flush();
+
+    if let Some(stall) = response.stall {
+        thread::sleep(stall);
+    }
+}
+
+/// 分片下载前探测服务器是否支持 Range 的请求
+pub fn is_range_probe(request: &MockRequest) -> bool {
+    request.headers.get("range").map(String::as_str) == Some("bytes=0-0")
+}
+
+/// 解析 `bytes=start-end` 或 `bytes=start-`
+pub fn parse_range(
+    request: &MockRequest,
+    len: usize,
+) -> Option<(usize, usize)> {
+    let range = request.headers.get("range")?.strip_prefix("bytes=")?;
+    let (start, end) = range.split_once('-')?;
+    let start = start.parse().ok()?;
+    let end = if end.is_empty() { len - 1 } else { end.parse().ok()? };
+    Some((start, end.min(len - 1)))
+}
+
+/// 按请求里的 Range 返回 206，没有 Range 时返回完整内容
+pub fn range_response(
+    request: &MockRequest,
+    content: &[u8],
+) -> MockResponse {
+    match parse_range(request, content.len()) {
+        Some((start, end)) => {
+            MockResponse::new(206, content[start..=end].to_vec()).header(
+                "Content-Range",
+                &format!("bytes {}-{}/{}", start, end, content.len()),
+            )
+        }
+        None => MockResponse::new(200, content.to_vec()),
+    }
+}
+
+/// 测试服务器上文件的原始数据
+pub fn mock_file_data(
+    server: &MockServer,
+    name: &str,
+    size: usize,
+) -> ResourceFileData {
+    ResourceFileData {
+        base_url: reqwest::Url::parse(&server.base_url).expect("地址错误"),
+        relative_root_path: format!("/{}", name),
+        absolute_path: format!("{}{}", server.base_url, name),
+        name: name.to_string(),
+        is_dir: false,
+        size: Some(size as u64),
+        last_modified: None,
+        mime: None,
+        owner: None,
+        etag: None,
+        privileges: vec![],
+        checksums: vec![],
+    }
+}
+
+/// 为测试服务器上的文件构建一个资源文件
+pub fn mock_resources_file(
+    client: &WebDavClient,
+    key: &ClientKey,
+    server: &MockServer,
+    name: &str,
+    size: usize,
+) -> ResourcesFile {
+    let data = mock_file_data(server, name, size);
+
+    let http_client = client.get_http_client(key).expect("账号不存在");
+    data.to_resources_file(http_client, client.get_global_config())
+}
+
+/// 每个测试使用独立的临时目录
+pub fn temp_download_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("webdav-client-{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("创建临时目录失败");
+    dir
+}
+
+/// 生成确定的测试内容
+pub fn test_content(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
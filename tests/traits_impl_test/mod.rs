@@ -1,4 +1,27 @@
 mod folder;
 mod account;
+mod account_config;
+mod account_registry;
+mod adaptive_chunking;
+mod atomic_write;
+mod conflict_policy;
 mod download;
-mod local_folders;
\ No newline at end of file
+mod download_handle;
+mod download_recursive;
+mod download_remote_changed;
+mod download_resume;
+mod download_retry;
+mod download_stream;
+mod download_timeout;
+mod host_policy;
+mod integrity;
+mod local_folders;
+mod mock_server;
+mod preserve_metadata;
+mod range_support;
+mod remote_reader;
+mod resource_config;
+mod speed_limit;
+mod transfer_manager;
+mod transfer_progress;
+mod verify_account;
\ No newline at end of file
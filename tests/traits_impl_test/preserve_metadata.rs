@@ -0,0 +1,138 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_file_data, range_response, temp_download_dir,
+    test_content,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 64 * 1024;
+
+fn remote_modified() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap()
+}
+
+fn modified_secs(path: &Path) -> Result<i64, String> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_err(|e| e.to_string())?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+async fn test_preserve_mtime() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("preserve-mtime");
+
+    let build = |name: &str| {
+        let mut data = mock_file_data(&server, name, CONTENT_LEN);
+        data.last_modified = Some(remote_modified().fixed_offset());
+        data.to_resources_file(
+            http_client.clone(),
+            client.get_global_config(),
+        )
+    };
+
+    // 默认不修改，本地修改时间是下载的时间
+    build("a.bin")
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(
+        modified_secs(&dir.join("a.bin"))? > remote_modified().timestamp()
+    );
+
+    // 开启后分片下载同样生效
+    let resources_file = build("b.bin");
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_preserve_mtime(Some(true))
+        .map_err(|e| e.to_string())?;
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert_eq!(
+        modified_secs(&dir.join("b.bin"))?,
+        remote_modified().timestamp()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_preserve_readonly() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content)
+    });
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_preserve_readonly(true)
+        .map_err(|e| e.to_string())?;
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+    let http_client =
+        client.get_http_client(&key).map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("preserve-readonly");
+
+    let build = |name: &str, privileges: &[&str]| {
+        let mut data = mock_file_data(&server, name, CONTENT_LEN);
+        data.privileges =
+            privileges.iter().map(|p| p.to_string()).collect();
+        data.to_resources_file(
+            http_client.clone(),
+            client.get_global_config(),
+        )
+    };
+
+    let is_readonly = |name: &str| {
+        std::fs::metadata(dir.join(name))
+            .map(|meta| meta.permissions().readonly())
+            .map_err(|e| e.to_string())
+    };
+
+    build("read.bin", &["read"])
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(is_readonly("read.bin")?);
+
+    build("write.bin", &["read", "write"])
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(!is_readonly("write.bin")?);
+
+    // 服务器没有返回权限信息时不修改
+    build("unknown.bin", &[])
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(!is_readonly("unknown.bin")?);
+
+    Ok(())
+}
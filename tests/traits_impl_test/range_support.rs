@@ -0,0 +1,202 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, is_range_probe, mock_resources_file,
+    range_response, temp_download_dir, test_content,
+};
+use std::sync::{Arc, Mutex};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::traits::download::Download;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+#[tokio::test]
+async fn test_chunked_falls_back_when_range_ignored() -> Result<(), String>
+{
+    let content = test_content(64 * 1024);
+    let served = content.clone();
+    let ranges = Arc::new(Mutex::new(vec![]));
+    let recorded = ranges.clone();
+
+    // 服务器忽略 Range，总是返回 200 和完整内容
+    let server = MockServer::start(move |request, _| {
+        recorded
+            .lock()
+            .unwrap()
+            .push(request.headers.get("range").cloned());
+        MockResponse::new(200, served.clone())
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("range-ignored");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+
+    // 探测失败后直接不分片下载，没有发出任何分片请求
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![Some("bytes=0-0".to_string()), None]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chunked_falls_back_on_wrong_content_range()
+-> Result<(), String> {
+    let len = CHUNK_SIZE + 100;
+    let content = test_content(len);
+    let served = content.clone();
+
+    // 探测请求正常，分片请求却总是返回整个文件
+    let server = MockServer::start(move |request, _| {
+        if is_range_probe(request) {
+            return range_response(request, &served);
+        }
+
+        if request.headers.contains_key("range") {
+            return MockResponse::new(206, served.clone()).header(
+                "Content-Range",
+                &format!("bytes 0-{}/{}", len - 1, len),
+            );
+        }
+
+        MockResponse::new(200, served.clone())
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", len);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(1))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("range-wrong-content-range");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert!(!dir.join("a.bin.wdpart").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_probe_retries_transient_errors() -> Result<(), String> {
+    let content = test_content(64 * 1024);
+    let served = content.clone();
+    let ranges = Arc::new(Mutex::new(vec![]));
+    let recorded = ranges.clone();
+
+    // 第一次探测遇到 503，重试后服务器正常返回 206
+    let server = MockServer::start(move |request, index| {
+        recorded
+            .lock()
+            .unwrap()
+            .push(request.headers.get("range").cloned());
+
+        if index == 0 {
+            return MockResponse::new(503, "busy");
+        }
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("range-probe-retry");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+
+    // 503 不代表不支持 Range，重试探测后照样分片下载
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![
+            Some("bytes=0-0".to_string()),
+            Some("bytes=0-0".to_string()),
+            Some(format!("bytes=0-{}", content.len() - 1)),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_range_probe_propagates_final_errors() -> Result<(), String> {
+    // 探测时文件已经不存在，既不重试也不改为不分片下载
+    let server =
+        MockServer::start(|_, _| MockResponse::new(404, "not found"));
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", 64 * 1024);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("range-probe-not-found");
+    let result = resources_file.download(dir.to_str().unwrap()).await;
+
+    assert!(result.is_err());
+    assert_eq!(server.request_count(), 1);
+
+    Ok(())
+}
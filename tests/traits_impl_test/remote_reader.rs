@@ -0,0 +1,151 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_resources_file, range_response, test_content,
+};
+use std::io::SeekFrom;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::structs::remote_reader::RemoteReaderOptions;
+use webdav_client::resource_file::traits::open_reader::OpenReader;
+
+const CONTENT_LEN: usize = 100_000;
+
+const OPTIONS: RemoteReaderOptions = RemoteReaderOptions {
+    block_size: 4096,
+    read_ahead: 16 * 1024,
+    cache_blocks: 8,
+};
+
+#[tokio::test]
+async fn test_remote_reader_seek_and_read() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let mut reader = resources_file
+        .open_reader_with_options(OPTIONS)
+        .map_err(|e| e.to_string())?;
+
+    // 跨块读取
+    let mut buffer = vec![0u8; 10_000];
+    reader.seek(SeekFrom::Start(3000)).await.map_err(|e| e.to_string())?;
+    reader.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+    assert_eq!(buffer, content[3000..13_000]);
+
+    // 从末尾读取
+    let mut tail = vec![];
+    reader.seek(SeekFrom::End(-500)).await.map_err(|e| e.to_string())?;
+    reader.read_to_end(&mut tail).await.map_err(|e| e.to_string())?;
+    assert_eq!(tail, content[CONTENT_LEN - 500..]);
+
+    // 超出文件末尾时读到 0 字节
+    reader.seek(SeekFrom::Current(10)).await.map_err(|e| e.to_string())?;
+    assert_eq!(
+        reader.read(&mut buffer).await.map_err(|e| e.to_string())?,
+        0
+    );
+
+    // 不能 Seek 到开头之前
+    assert!(reader.seek(SeekFrom::Current(-1_000_000)).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remote_reader_uses_cache() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let mut reader = resources_file
+        .open_reader_with_options(OPTIONS)
+        .map_err(|e| e.to_string())?;
+
+    // 一次请求预读 4 块
+    let mut buffer = vec![0u8; 100];
+    reader.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+    assert_eq!(server.request_count(), 1);
+
+    // 预读窗口内的位置和跳回去都不会再发请求
+    reader
+        .seek(SeekFrom::Start(15_000))
+        .await
+        .map_err(|e| e.to_string())?;
+    reader.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+    assert_eq!(buffer, content[15_000..15_100]);
+    reader.seek(SeekFrom::Start(50)).await.map_err(|e| e.to_string())?;
+    reader.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+    assert_eq!(buffer, content[50..150]);
+    assert_eq!(server.request_count(), 1);
+
+    // 预读窗口之外才发新的请求
+    reader
+        .seek(SeekFrom::Start(40_000))
+        .await
+        .map_err(|e| e.to_string())?;
+    reader.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+    assert_eq!(buffer, content[40_000..40_100]);
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remote_reader_window_fits_cache() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    // 预读 4 块，但只能缓存 1 块
+    let mut reader = resources_file
+        .open_reader_with_options(RemoteReaderOptions {
+            cache_blocks: 1,
+            ..OPTIONS
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut buffer = vec![0u8; 10_000];
+    let read = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        reader.read_exact(&mut buffer),
+    )
+    .await
+    .map_err(|_| {
+        "读取没有结束，预读的块把请求的块挤出了缓存".to_string()
+    })?;
+    read.map_err(|e| e.to_string())?;
+    assert_eq!(buffer, content[..10_000]);
+
+    // 每块只请求一次
+    assert_eq!(server.request_count(), 3);
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_resources_file, range_response, temp_download_dir,
+    test_content,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::traits::download::Download;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+#[tokio::test]
+async fn test_resource_config_forces_not_chunked() -> Result<(), String> {
+    let content = test_content(64 * 1024);
+    let served = content.clone();
+
+    let server = MockServer::start(move |request, _| {
+        assert!(!request.headers.contains_key("range"));
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+
+    // 阈值设得再小，强制不分片时也只发一个完整请求
+    resources_file
+        .get_reactive_config()
+        .set_large_file_threshold(Some(1))
+        .map_err(|e| e.to_string())?
+        .set_download_mode(DownloadMode::NotChunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("resource-config-not-chunked");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    assert_eq!(server.request_count(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resource_config_thread_count() -> Result<(), String> {
+    // 9 个分片，线程数为 8 时最多同时有 8 个请求
+    let len = CHUNK_SIZE * 8 + 1;
+    let served = Arc::new(test_content(len));
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (current, max) = (in_flight.clone(), max_in_flight.clone());
+
+    let server = MockServer::start(move |request, _| {
+        assert!(request.headers.contains_key("range"));
+
+        let running = current.fetch_add(1, Ordering::SeqCst) + 1;
+        max.fetch_max(running, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(300));
+        current.fetch_sub(1, Ordering::SeqCst);
+
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 全局阈值保持默认，资源配置强制分片并单独指定线程数
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", len);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(8))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("resource-config-thread-count");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(len));
+    // 1 个 Range 探测请求加 9 个分片
+    assert_eq!(server.request_count(), 10);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 8);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resource_config_forces_chunked_small_file()
+-> Result<(), String> {
+    let content = test_content(64 * 1024);
+    let served = content.clone();
+
+    let server = MockServer::start(move |request, _| {
+        assert!(request.headers.contains_key("range"));
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 小于全局阈值的文件默认不分片，强制分片后走 Range 请求
+    let resources_file = mock_resources_file(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        content.len(),
+    );
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("resource-config-chunked");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+    // 1 个 Range 探测请求加 1 个分片
+    assert_eq!(server.request_count(), 2);
+
+    Ok(())
+}
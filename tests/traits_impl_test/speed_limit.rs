@@ -0,0 +1,103 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_resources_file, range_response, temp_download_dir,
+    test_content,
+};
+use std::time::{Duration, Instant};
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::traits::download::Download;
+
+const CONTENT_LEN: usize = 256 * 1024;
+
+#[tokio::test]
+async fn test_file_speed_limit() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+
+    // 128KB/s 下载 256KB，去掉第一块的透支至少也要一秒多
+    resources_file
+        .get_reactive_config()
+        .update_field(|cfg| cfg.max_speed = Some(128 * 1024))
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("speed-limit-file");
+
+    let started = Instant::now();
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    let elapsed = started.elapsed();
+
+    println!("限速下载耗时：{:?}", elapsed);
+    assert!(elapsed >= Duration::from_secs(1));
+    assert_eq!(
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?,
+        content
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_global_speed_limit_changes_mid_transfer()
+-> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    // 32KB/s 需要 8 秒，中途取消限速后应该很快完成
+    let global_config = client.get_global_config();
+    global_config
+        .set_max_speed(Some(32 * 1024))
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "b.bin", CONTENT_LEN);
+    let dir = temp_download_dir("speed-limit-live");
+
+    let unlimit = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        global_config
+            .set_max_speed(None)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    });
+
+    let started = Instant::now();
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+    let elapsed = started.elapsed();
+
+    unlimit.await.map_err(|e| e.to_string())??;
+
+    println!("中途取消限速后耗时：{:?}", elapsed);
+    assert!(elapsed >= Duration::from_millis(400));
+    assert!(elapsed < Duration::from_secs(4));
+    assert_eq!(
+        std::fs::read(dir.join("b.bin")).map_err(|e| e.to_string())?,
+        content
+    );
+
+    Ok(())
+}
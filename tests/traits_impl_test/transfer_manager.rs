@@ -0,0 +1,425 @@
+use crate::traits_impl_test::mock_server::{
+    MockServer, mock_resources_file, range_response, temp_download_dir,
+    test_content,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::structs::client_key::ClientKey;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::transfer_manager::queued_transfer_state::QueuedTransferState;
+use webdav_client::transfer_manager::transfer_priority::TransferPriority;
+use webdav_client::transfer_manager::{
+    TransferId, TransferManager, TransferManagerError,
+};
+
+const CONTENT_LEN: usize = 64 * 1024;
+
+// 每个文件的响应都要等这么久，保证排队的任务不会立刻完成
+const SLOW_RESPONSE: Duration = Duration::from_millis(500);
+
+/// 启动一个慢速服务器，按顺序记录每个被请求的文件名
+fn start_recording_server() -> (MockServer, Arc<Mutex<Vec<String>>>) {
+    let content = test_content(CONTENT_LEN);
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let recorder = requested.clone();
+
+    let server = MockServer::start(move |request, _| {
+        let name = request.path.rsplit('/').next().unwrap_or_default();
+        let mut requested = recorder.lock().unwrap();
+        if !requested.iter().any(|n| n == name) {
+            requested.push(name.to_string());
+        }
+
+        range_response(request, &content).delay(SLOW_RESPONSE)
+    });
+
+    (server, requested)
+}
+
+async fn wait_for_state(
+    manager: &TransferManager,
+    id: TransferId,
+    expected: QueuedTransferState,
+) -> Result<(), String> {
+    tokio::time::timeout(Duration::from_secs(20), async {
+        loop {
+            let info = manager.get(id).ok_or("任务不存在")?;
+            if info.state == expected {
+                return Ok::<_, String>(());
+            }
+            if let QueuedTransferState::Failed(e) = info.state {
+                return Err(e);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+async fn enqueue(
+    client: &WebDavClient,
+    key: &ClientKey,
+    server: &MockServer,
+    name: &str,
+    dir: &std::path::Path,
+    priority: TransferPriority,
+) -> Result<TransferId, String> {
+    let file = mock_resources_file(client, key, server, name, CONTENT_LEN);
+    client
+        .get_transfer_manager()
+        .enqueue(key, &file, dir.to_str().unwrap(), priority)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tokio::test]
+async fn test_transfer_manager_priority() -> Result<(), String> {
+    let (server, requested) = start_recording_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let manager = client.get_transfer_manager();
+    manager.set_max_concurrent_files(1);
+
+    let dir = temp_download_dir("manager-priority");
+
+    // 第一个任务立即开始，其余的排队
+    let first = enqueue(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        &dir,
+        TransferPriority::Low,
+    )
+    .await?;
+    let low = enqueue(
+        &client,
+        &key,
+        &server,
+        "b.bin",
+        &dir,
+        TransferPriority::Low,
+    )
+    .await?;
+    let normal = enqueue(
+        &client,
+        &key,
+        &server,
+        "c.bin",
+        &dir,
+        TransferPriority::Normal,
+    )
+    .await?;
+    let high = enqueue(
+        &client,
+        &key,
+        &server,
+        "d.bin",
+        &dir,
+        TransferPriority::High,
+    )
+    .await?;
+    let moved = enqueue(
+        &client,
+        &key,
+        &server,
+        "e.bin",
+        &dir,
+        TransferPriority::Normal,
+    )
+    .await?;
+
+    // 同一优先级内按队列顺序，e 移到 c 前面
+    manager.reorder(moved, 0).await.map_err(|e| e.to_string())?;
+
+    let states: Vec<_> = manager
+        .list()
+        .into_iter()
+        .map(|info| (info.id, info.state))
+        .collect();
+    assert_eq!(states[0], (moved, QueuedTransferState::Pending));
+    assert_eq!(states[1], (first, QueuedTransferState::Running));
+
+    for id in [first, low, normal, high, moved] {
+        wait_for_state(&manager, id, QueuedTransferState::Done).await?;
+    }
+
+    assert_eq!(
+        *requested.lock().unwrap(),
+        vec!["a.bin", "d.bin", "e.bin", "c.bin", "b.bin"]
+    );
+
+    let content = test_content(CONTENT_LEN);
+    for name in ["a.bin", "b.bin", "c.bin", "d.bin", "e.bin"] {
+        let downloaded =
+            std::fs::read(dir.join(name)).map_err(|e| e.to_string())?;
+        assert_eq!(downloaded, content);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_manager_pause_resume_remove() -> Result<(), String>
+{
+    let (server, _) = start_recording_server();
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let manager = client.get_transfer_manager();
+    manager.set_max_concurrent_files(1);
+
+    let dir = temp_download_dir("manager-pause");
+
+    let running = enqueue(
+        &client,
+        &key,
+        &server,
+        "a.bin",
+        &dir,
+        TransferPriority::Normal,
+    )
+    .await?;
+    let pending = enqueue(
+        &client,
+        &key,
+        &server,
+        "b.bin",
+        &dir,
+        TransferPriority::Normal,
+    )
+    .await?;
+
+    // 暂停正在下载的任务会让出名额
+    manager.pause(running).await.map_err(|e| e.to_string())?;
+    assert_eq!(
+        manager.get(running).map(|info| info.state),
+        Some(QueuedTransferState::Paused)
+    );
+    assert_eq!(
+        manager.get(pending).map(|info| info.state),
+        Some(QueuedTransferState::Running)
+    );
+
+    manager.remove(pending, false).await.map_err(|e| e.to_string())?;
+    assert!(manager.get(pending).is_none());
+    assert!(!dir.join("b.bin").exists());
+
+    assert!(matches!(
+        manager.remove(pending, false).await,
+        Err(TransferManagerError::TransferNotFound(id)) if id == pending
+    ));
+
+    manager.resume(running).await.map_err(|e| e.to_string())?;
+    wait_for_state(&manager, running, QueuedTransferState::Done).await?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_manager_queue_file() -> Result<(), String> {
+    let (server, _) = start_recording_server();
+    let dir = temp_download_dir("manager-queue-file");
+    let queue_file = dir.join("queue.json");
+
+    {
+        let client = WebDavClient::new();
+        let key = client
+            .add_account(&server.base_url, "username", "password")
+            .map_err(|e| e.to_string())?;
+
+        let manager = client.get_transfer_manager();
+        let restored = manager
+            .open_queue_file(&queue_file)
+            .await
+            .map_err(|e| e.to_string())?;
+        assert!(restored.is_empty());
+
+        manager.set_max_concurrent_files(1);
+
+        let running = enqueue(
+            &client,
+            &key,
+            &server,
+            "a.bin",
+            &dir,
+            TransferPriority::Low,
+        )
+        .await?;
+        let pending = enqueue(
+            &client,
+            &key,
+            &server,
+            "b.bin",
+            &dir,
+            TransferPriority::High,
+        )
+        .await?;
+
+        // 先暂停排队的任务，之后没有任务会在后台继续改写队列文件
+        manager.pause(pending).await.map_err(|e| e.to_string())?;
+        manager.pause(running).await.map_err(|e| e.to_string())?;
+    }
+
+    // 模拟重启：新的客户端导入同一个账号后打开队列文件
+    let client = WebDavClient::new();
+    client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let manager = client.get_transfer_manager();
+    manager.set_max_concurrent_files(1);
+
+    let restored = manager
+        .open_queue_file(&queue_file)
+        .await
+        .map_err(|e| e.to_string())?;
+    assert_eq!(restored.len(), 2);
+
+    let infos = manager.list();
+    assert_eq!(infos[0].name, "a.bin");
+    assert_eq!(infos[0].priority, TransferPriority::Low);
+    assert_eq!(infos[0].state, QueuedTransferState::Paused);
+    assert_eq!(infos[1].name, "b.bin");
+    assert_eq!(infos[1].priority, TransferPriority::High);
+    assert_eq!(infos[1].state, QueuedTransferState::Paused);
+
+    manager.resume(restored[1]).await.map_err(|e| e.to_string())?;
+    wait_for_state(&manager, restored[1], QueuedTransferState::Done)
+        .await?;
+    manager.resume(restored[0]).await.map_err(|e| e.to_string())?;
+    wait_for_state(&manager, restored[0], QueuedTransferState::Done)
+        .await?;
+
+    let content = test_content(CONTENT_LEN);
+    for name in ["a.bin", "b.bin"] {
+        let downloaded =
+            std::fs::read(dir.join(name)).map_err(|e| e.to_string())?;
+        assert_eq!(downloaded, content);
+    }
+
+    // 同一个管理器不能再次打开队列文件，否则任务会被重复加入
+    assert!(matches!(
+        manager.open_queue_file(&queue_file).await,
+        Err(TransferManagerError::QueueFileAlreadyOpen(_))
+    ));
+    assert_eq!(manager.list().len(), 2);
+
+    // 完成的任务不再写入队列文件
+    let restored = WebDavClient::new()
+        .get_transfer_manager()
+        .open_queue_file(&queue_file)
+        .await
+        .map_err(|e| e.to_string())?;
+    assert!(restored.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_manager_keeps_resource_config() -> Result<(), String>
+{
+    let content = test_content(CONTENT_LEN);
+    let ranged = Arc::new(Mutex::new(Vec::<String>::new()));
+    let recorder = ranged.clone();
+
+    // 默认阈值下小文件不会分片，出现 Range 请求说明用上了强制分片的配置
+    let server = MockServer::start(move |request, _| {
+        if request.headers.contains_key("range") {
+            let name = request.path.rsplit('/').next().unwrap_or_default();
+            recorder.lock().unwrap().push(name.to_string());
+        }
+        range_response(request, &content).delay(SLOW_RESPONSE)
+    });
+
+    let dir = temp_download_dir("manager-resource-config");
+    let queue_file = dir.join("queue.json");
+
+    let chunked_file = |client: &WebDavClient, key: &ClientKey, name| {
+        let file =
+            mock_resources_file(client, key, &server, name, CONTENT_LEN);
+        file.get_reactive_config()
+            .set_download_mode(DownloadMode::Chunked)
+            .map_err(|e| e.to_string())?;
+        Ok::<_, String>(file)
+    };
+
+    {
+        let client = WebDavClient::new();
+        let key = client
+            .add_account(&server.base_url, "username", "password")
+            .map_err(|e| e.to_string())?;
+
+        let manager = client.get_transfer_manager();
+        manager
+            .open_queue_file(&queue_file)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let id = manager
+            .enqueue(
+                &key,
+                &chunked_file(&client, &key, "a.bin")?,
+                dir.to_str().unwrap(),
+                TransferPriority::Normal,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        wait_for_state(&manager, id, QueuedTransferState::Done).await?;
+        assert!(ranged.lock().unwrap().iter().any(|name| name == "a.bin"));
+
+        // 暂停的任务连同配置一起写入队列文件
+        let id = manager
+            .enqueue(
+                &key,
+                &chunked_file(&client, &key, "b.bin")?,
+                dir.to_str().unwrap(),
+                TransferPriority::Normal,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        manager.pause(id).await.map_err(|e| e.to_string())?;
+    }
+
+    ranged.lock().unwrap().clear();
+    let _ = std::fs::remove_file(dir.join("b.bin"));
+    let _ = std::fs::remove_file(dir.join("b.bin.wdpart"));
+
+    // 模拟重启，恢复出的任务也要使用保存下来的配置
+    let client = WebDavClient::new();
+    client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let manager = client.get_transfer_manager();
+    let restored = manager
+        .open_queue_file(&queue_file)
+        .await
+        .map_err(|e| e.to_string())?;
+    assert_eq!(restored.len(), 1);
+
+    manager.resume(restored[0]).await.map_err(|e| e.to_string())?;
+    wait_for_state(&manager, restored[0], QueuedTransferState::Done)
+        .await?;
+    assert!(ranged.lock().unwrap().iter().any(|name| name == "b.bin"));
+
+    let downloaded =
+        std::fs::read(dir.join("b.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, test_content(CONTENT_LEN));
+
+    Ok(())
+}
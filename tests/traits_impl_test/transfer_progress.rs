@@ -0,0 +1,207 @@
+use crate::traits_impl_test::mock_server::{
+    MockResponse, MockServer, is_range_probe, mock_resources_file,
+    range_response, temp_download_dir, test_content,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::resource_file::enums::download_mode::DownloadMode;
+use webdav_client::resource_file::structs::transfer_progress::TransferState;
+use webdav_client::resource_file::traits::download::Download;
+
+// 分片大小是 4M，三个分片
+const CHUNKED_LEN: usize = 9 * 1024 * 1024;
+const CONTENT_LEN: usize = 64 * 1024;
+
+#[tokio::test]
+async fn test_transfer_progress_chunked() -> Result<(), String> {
+    let content = test_content(CHUNKED_LEN);
+    let served = content.clone();
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &served)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CHUNKED_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?;
+
+    let dir = temp_download_dir("progress-chunked");
+    let resources_file = resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let downloaded =
+        std::fs::read(dir.join("a.bin")).map_err(|e| e.to_string())?;
+    assert_eq!(downloaded, content);
+
+    let progress =
+        resources_file.get_progress().get_current().ok_or("没有进度")?;
+    assert_eq!(progress.state, TransferState::Done);
+    assert_eq!(progress.total_bytes, Some(CHUNKED_LEN as u64));
+    assert_eq!(progress.done_bytes, CHUNKED_LEN as u64);
+    assert_eq!(progress.percentage(), Some(100.0));
+    assert_eq!(progress.remaining_bytes(), Some(0));
+    assert_eq!(progress.eta, Some(Duration::ZERO));
+
+    assert_eq!(progress.chunks.len(), 3);
+    for chunk in &progress.chunks {
+        assert_eq!(chunk.state, TransferState::Done);
+        assert_eq!(chunk.done_bytes, chunk.total_bytes());
+    }
+    assert_eq!(
+        progress.chunks.iter().map(|c| c.total_bytes()).sum::<u64>(),
+        CHUNKED_LEN as u64
+    );
+    assert_eq!(
+        resources_file.get_download_bytes().get_current().map(|v| *v),
+        Some(CHUNKED_LEN)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_progress_paused() -> Result<(), String> {
+    let content = test_content(CONTENT_LEN);
+    let server = MockServer::start(move |request, _| {
+        range_response(request, &content)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CONTENT_LEN);
+    let config = resources_file.get_reactive_config();
+    config.pause().map_err(|e| e.to_string())?;
+
+    let mut watcher = resources_file.get_progress().watch();
+
+    let dir = temp_download_dir("progress-paused");
+    let task = tokio::spawn(async move {
+        resources_file.download(dir.to_str().unwrap()).await
+    });
+
+    // 收到第一块数据后停在暂停状态
+    let paused = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let progress =
+                watcher.changed().await.map_err(|e| e.to_string())?;
+            if progress.state == TransferState::Paused {
+                return Ok::<_, String>(progress);
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    assert!(paused.done_bytes < CONTENT_LEN as u64);
+
+    config.resume().map_err(|e| e.to_string())?;
+
+    let resources_file = task
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let progress =
+        resources_file.get_progress().get_current().ok_or("没有进度")?;
+    assert_eq!(progress.state, TransferState::Done);
+    assert_eq!(progress.done_bytes, CONTENT_LEN as u64);
+    assert!(progress.chunks.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_progress_single_chunk_retrying() -> Result<(), String>
+{
+    let content = test_content(CHUNKED_LEN);
+    let failed_once = Arc::new(AtomicBool::new(false));
+
+    // 第一个分片先失败一次，其它分片迟迟不返回，保持在下载中
+    let server = MockServer::start(move |request, _| {
+        if is_range_probe(request) {
+            return range_response(request, &content);
+        }
+
+        let first_chunk = request
+            .headers
+            .get("range")
+            .is_some_and(|range| range.starts_with("bytes=0-"));
+
+        if !first_chunk {
+            return range_response(request, &content)
+                .delay(Duration::from_millis(1500));
+        }
+
+        if !failed_once.swap(true, Ordering::SeqCst) {
+            return MockResponse::new(503, "busy");
+        }
+        range_response(request, &content)
+    });
+
+    let client = WebDavClient::new();
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let resources_file =
+        mock_resources_file(&client, &key, &server, "a.bin", CHUNKED_LEN);
+    resources_file
+        .get_reactive_config()
+        .set_download_mode(DownloadMode::Chunked)
+        .map_err(|e| e.to_string())?
+        .set_max_thread_count(Some(3))
+        .map_err(|e| e.to_string())?;
+
+    let mut watcher = resources_file.get_progress().watch();
+    let observer = tokio::spawn(async move {
+        let mut saw_chunk_retrying = false;
+
+        while let Ok(progress) = watcher.changed().await {
+            let chunk_retrying = progress
+                .chunks
+                .iter()
+                .any(|c| matches!(c.state, TransferState::Retrying(_)));
+            let chunk_running = progress
+                .chunks
+                .iter()
+                .any(|c| c.state == TransferState::Running);
+
+            if chunk_retrying && chunk_running {
+                saw_chunk_retrying = true;
+                assert_eq!(progress.state, TransferState::Running);
+            }
+
+            if progress.state.is_finished() {
+                break;
+            }
+        }
+
+        saw_chunk_retrying
+    });
+
+    let dir = temp_download_dir("progress-chunk-retrying");
+    resources_file
+        .download(dir.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let saw_chunk_retrying = observer.await.map_err(|e| e.to_string())?;
+    assert!(saw_chunk_retrying);
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use crate::traits_impl_test::mock_server::{MockResponse, MockServer};
+use crate::{WEBDAV_ENV_PATH_1, load_account};
+use std::io::Write;
+use std::net::TcpListener;
+use std::time::Duration;
+use webdav_client::client::WebDavClient;
+use webdav_client::client::traits::account::Account;
+use webdav_client::client::traits::verify_account::{
+    VerifyAccount, VerifyAccountError,
+};
+
+#[tokio::test]
+async fn test_add_account_verified() -> Result<(), VerifyAccountError> {
+    let client = WebDavClient::new();
+    let webdav_account = load_account(WEBDAV_ENV_PATH_1);
+
+    let key = client
+        .add_account_verified(
+            &webdav_account.url,
+            &webdav_account.username,
+            &webdav_account.password,
+        )
+        .await?;
+
+    println!(
+        "校验并插入账号成功：{}  {}",
+        key.get_base_url(),
+        key.get_username()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_account_wrong_password() -> Result<(), String> {
+    let client = WebDavClient::new();
+    let webdav_account = load_account(WEBDAV_ENV_PATH_1);
+
+    let key = client
+        .add_account(
+            &webdav_account.url,
+            &webdav_account.username,
+            "wrong-password",
+        )
+        .map_err(|e| e.to_string())?;
+
+    match client.verify_account(&key).await {
+        Err(VerifyAccountError::Unauthorized(url)) => {
+            println!("错误密码被正确识别：{}", url);
+            Ok(())
+        }
+        other => Err(format!("期望 Unauthorized，实际为 {:?}", other)),
+    }
+}
+
+#[tokio::test]
+async fn test_add_account_verified_dns_error() -> Result<(), String> {
+    let client = WebDavClient::new();
+
+    let result = client
+        .add_account_verified(
+            "https://not-exist-host-12345.invalid/dav/",
+            "username",
+            "password",
+        )
+        .await;
+
+    match result {
+        Err(VerifyAccountError::Dns(e)) => {
+            println!("DNS 错误被正确识别：{}", e);
+            Ok(())
+        }
+        other => Err(format!("期望 Dns，实际为 {:?}", other)),
+    }
+}
+
+/// 让模拟服务器对校验请求返回 `response`，返回校验结果
+async fn verify_with_response(
+    response: MockResponse,
+) -> Result<Result<(), VerifyAccountError>, String> {
+    let server = MockServer::start(move |request, _| {
+        assert_eq!(request.method, "PROPFIND");
+        assert_eq!(
+            request.headers.get("depth").map(String::as_str),
+            Some("0")
+        );
+        response.clone()
+    });
+
+    let client = WebDavClient::new();
+    client
+        .get_global_config()
+        .set_timeout(1)
+        .map_err(|e| e.to_string())?;
+
+    let key = client
+        .add_account(&server.base_url, "username", "password")
+        .map_err(|e| e.to_string())?;
+
+    let result = client.verify_account(&key).await;
+    assert_eq!(server.request_count(), 1);
+
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_verify_account_unauthorized() -> Result<(), String> {
+    match verify_with_response(MockResponse::new(401, "")).await? {
+        Err(VerifyAccountError::Unauthorized(_)) => Ok(()),
+        other => Err(format!("期望 Unauthorized，实际为 {:?}", other)),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_forbidden() -> Result<(), String> {
+    match verify_with_response(MockResponse::new(403, "")).await? {
+        Err(VerifyAccountError::Forbidden(_)) => Ok(()),
+        other => Err(format!("期望 Forbidden，实际为 {:?}", other)),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_not_webdav() -> Result<(), String> {
+    // 普通网站对任何方法都返回 200 和一个网页
+    let response = MockResponse::new(200, "<html></html>")
+        .header("Content-Type", "text/html");
+
+    match verify_with_response(response).await? {
+        Err(VerifyAccountError::NotWebDavEndpoint(_)) => Ok(()),
+        other => {
+            Err(format!("期望 NotWebDavEndpoint，实际为 {:?}", other))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_timeout() -> Result<(), String> {
+    // 全局超时是 1 秒，服务器 5 秒后才响应
+    let response =
+        MockResponse::new(207, "").delay(Duration::from_secs(5));
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(3),
+        verify_with_response(response),
+    )
+    .await
+    .map_err(|_| "校验请求没有超时".to_string())??;
+
+    match result {
+        Err(VerifyAccountError::Timeout(_)) => Ok(()),
+        other => Err(format!("期望 Timeout，实际为 {:?}", other)),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_not_found_is_not_webdav() -> Result<(), String>
+{
+    // 服务器存在，但基础地址的路径填错了
+    match verify_with_response(MockResponse::new(404, "")).await? {
+        Err(VerifyAccountError::NotWebDavEndpoint(_)) => Ok(()),
+        other => {
+            Err(format!("期望 NotWebDavEndpoint，实际为 {:?}", other))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_tls_error() -> Result<(), String> {
+    // 对 https 地址直接返回明文 HTTP，TLS 握手一定失败
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    std::thread::spawn(move || {
+        if let Ok((mut stream, _)) = listener.accept() {
+            let _ = stream.write_all(b"HTTP/1.1 207 Mock\r\n\r\n");
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    });
+
+    let client = WebDavClient::new();
+    let result = client
+        .add_account_verified(
+            &format!("https://127.0.0.1:{}/dav/", port),
+            "username",
+            "password",
+        )
+        .await;
+
+    match result {
+        Err(VerifyAccountError::Tls(_)) => Ok(()),
+        other => Err(format!("期望 Tls，实际为 {:?}", other)),
+    }
+}
+
+#[tokio::test]
+async fn test_verify_account_connect_error_mentioning_tls()
+-> Result<(), String> {
+    // 地址里带着 tls 字样，但失败原因是端口没有开放
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    drop(listener);
+
+    let client = WebDavClient::new();
+    let result = client
+        .add_account_verified(
+            &format!("http://127.0.0.1:{}/tls-certificate/", port),
+            "username",
+            "password",
+        )
+        .await;
+
+    match result {
+        Err(VerifyAccountError::Connect(_)) => Ok(()),
+        other => Err(format!("期望 Connect，实际为 {:?}", other)),
+    }
+}